@@ -1,4 +1,6 @@
-use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use reqwest::{Client, header};
 use chrono::{DateTime, Utc};
@@ -13,12 +15,238 @@ use crate::core::error::LdUiError;
 pub struct Topic {
     pub id: u64,
     pub title: String,
+    pub category_id: Option<u64>,
     pub posts_count: u64,
     pub views: u64,
+    #[serde(default)]
+    pub like_count: u64,
     pub created_at: DateTime<Utc>,
     pub last_posted_at: Option<DateTime<Utc>>,
+    pub last_poster_username: Option<String>,
     pub posters: Vec<Poster>,
-    pub tags: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "tag_names")]
+    pub tags: Vec<String>,
+    /// 未读帖子数，仅登录后返回
+    pub unread_posts: Option<u64>,
+    /// 是否为从未打开过的新主题
+    pub unseen: Option<bool>,
+    /// 当前用户对主题的通知级别，仅登录后返回
+    pub notification_level: Option<NotificationLevel>,
+}
+
+/// `/t/{id}.json` 返回的主题详情，帖子另外通过 get_topic_posts 分页加载
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TopicDetails {
+    pub id: u64,
+    pub title: String,
+    pub category_id: Option<u64>,
+    #[serde(default, deserialize_with = "tag_names")]
+    pub tags: Vec<String>,
+    pub posts_count: u64,
+    #[serde(default)]
+    pub views: u64,
+    #[serde(default)]
+    pub like_count: u64,
+    #[serde(default)]
+    pub participant_count: u64,
+    pub created_at: DateTime<Utc>,
+    pub last_posted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub closed: bool,
+    #[serde(default)]
+    pub archived: bool,
+    /// 每页的帖子数
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: u64,
+    pub post_stream: PostStream,
+    pub details: TopicMeta,
+    #[serde(default)]
+    pub suggested_topics: Vec<SuggestedTopic>,
+    #[serde(default)]
+    pub related_topics: Vec<SuggestedTopic>,
+}
+
+impl TopicDetails {
+    /// 按帖子总数计算的页数
+    pub fn page_count(&self) -> u64 {
+        (self.post_stream.stream.len() as u64).div_ceil(self.chunk_size.max(1)).max(1)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PostStream {
+    /// 主题中全部帖子的 ID，按顺序排列
+    #[serde(default)]
+    pub stream: Vec<u64>,
+}
+
+/// 主题详情中的 details 部分：发起人、参与者和当前用户的权限
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TopicMeta {
+    pub notification_level: Option<NotificationLevel>,
+    pub created_by: Option<BasicUser>,
+    pub last_poster: Option<BasicUser>,
+    #[serde(default)]
+    pub participants: Vec<Participant>,
+    #[serde(default)]
+    pub can_create_post: bool,
+    #[serde(default)]
+    pub can_edit: bool,
+    #[serde(default)]
+    pub can_delete: bool,
+    #[serde(default)]
+    pub can_reply_as_new_topic: bool,
+    #[serde(default)]
+    pub can_flag_topic: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BasicUser {
+    pub id: u64,
+    pub username: String,
+    pub name: Option<String>,
+    pub avatar_template: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Participant {
+    pub id: u64,
+    pub username: String,
+    pub avatar_template: Option<String>,
+    /// 在这个主题中的帖子数
+    #[serde(default)]
+    pub post_count: u64,
+}
+
+/// 主题详情中推荐和相关的主题，字段比主题列表中的少
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SuggestedTopic {
+    pub id: u64,
+    pub title: String,
+    pub category_id: Option<u64>,
+    #[serde(default)]
+    pub posts_count: u64,
+    #[serde(default)]
+    pub views: u64,
+    #[serde(default)]
+    pub like_count: u64,
+    pub last_posted_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "tag_names")]
+    pub tags: Vec<String>,
+    pub unread_posts: Option<u64>,
+    #[serde(default)]
+    pub unseen: bool,
+}
+
+/// 投票和撤回投票的响应中，更新后的投票位于 poll 字段
+async fn parse_poll_response(response: reqwest::Response) -> Result<Poll> {
+    let json: Value = response.json().await
+        .map_err(|e| {
+            error!("解析投票响应失败: {}", e);
+            LdUiError::Parse(format!("解析响应失败: {}", e))
+        })?;
+    let poll: Poll = serde_json::from_value(json["poll"].clone())
+        .map_err(|e| {
+            error!("解析投票数据失败: {}", e);
+            LdUiError::Parse(format!("解析投票失败: {}", e))
+        })?;
+    Ok(poll)
+}
+
+fn default_chunk_size() -> u64 {
+    20
+}
+
+/// 标签可能是字符串，也可能是包含 name 的对象（较新版本的 Discourse）
+fn tag_names<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<String>, D::Error> {
+    let tags: Option<Vec<Value>> = Option::deserialize(deserializer)?;
+    Ok(tags
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|tag| match tag {
+            Value::String(name) => Some(name),
+            Value::Object(map) => map.get("name").and_then(Value::as_str).map(str::to_string),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Discourse 的通知级别，序列化为接口使用的数字
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum NotificationLevel {
+    Muted,
+    Regular,
+    Tracking,
+    Watching,
+    /// 只用于分类和标签：新主题的第一个帖子会通知
+    WatchingFirstPost,
+}
+
+impl NotificationLevel {
+    /// 主题可用的级别，按选择器中的显示顺序排列
+    pub const TOPIC_LEVELS: [NotificationLevel; 4] = [
+        NotificationLevel::Watching,
+        NotificationLevel::Tracking,
+        NotificationLevel::Regular,
+        NotificationLevel::Muted,
+    ];
+    
+    /// 分类可用的级别
+    pub const CATEGORY_LEVELS: [NotificationLevel; 5] = [
+        NotificationLevel::Watching,
+        NotificationLevel::Tracking,
+        NotificationLevel::WatchingFirstPost,
+        NotificationLevel::Regular,
+        NotificationLevel::Muted,
+    ];
+    
+    pub fn title(&self) -> &'static str {
+        match self {
+            NotificationLevel::Muted => "静音",
+            NotificationLevel::Regular => "常规",
+            NotificationLevel::Tracking => "跟踪",
+            NotificationLevel::Watching => "关注",
+            NotificationLevel::WatchingFirstPost => "关注新主题",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            NotificationLevel::Muted => "不接收任何通知，不显示在最新列表中",
+            NotificationLevel::Regular => "有人提及或回复你时通知",
+            NotificationLevel::Tracking => "显示未读帖子数，有人提及或回复你时通知",
+            NotificationLevel::Watching => "每个新回复都会通知",
+            NotificationLevel::WatchingFirstPost => "每个新主题的第一个帖子都会通知",
+        }
+    }
+}
+
+impl TryFrom<u8> for NotificationLevel {
+    type Error = String;
+    
+    fn try_from(value: u8) -> std::result::Result<Self, Self::Error> {
+        match value {
+            0 => Ok(NotificationLevel::Muted),
+            1 => Ok(NotificationLevel::Regular),
+            2 => Ok(NotificationLevel::Tracking),
+            3 => Ok(NotificationLevel::Watching),
+            4 => Ok(NotificationLevel::WatchingFirstPost),
+            _ => Err(format!("未知的通知级别: {}", value)),
+        }
+    }
+}
+
+impl From<NotificationLevel> for u8 {
+    fn from(level: NotificationLevel) -> Self {
+        match level {
+            NotificationLevel::Muted => 0,
+            NotificationLevel::Regular => 1,
+            NotificationLevel::Tracking => 2,
+            NotificationLevel::Watching => 3,
+            NotificationLevel::WatchingFirstPost => 4,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -26,6 +254,9 @@ pub struct Poster {
     pub user_id: i64,
     pub primary_group_id: Option<u64>,
     pub description: Option<String>,
+    /// 根据主题列表中的 users 补全
+    #[serde(default)]
+    pub username: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -34,10 +265,208 @@ pub struct Post {
     pub topic_id: u64,
     pub user_id: u64,
     pub username: String,
+    pub avatar_template: Option<String>,
+    pub post_number: u64,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
     pub cooked: String,
+    /// 作者的帖子总数，回复列表中不返回
+    #[serde(default)]
     pub posts_count: u64,
+    /// 回复的帖子楼层，直接回复主题时为空
+    pub reply_to_post_number: Option<u64>,
+    pub reply_to_user: Option<ReplyToUser>,
+    /// 直接回复这个帖子的数量
+    #[serde(default)]
+    pub reply_count: u64,
+    /// 当前用户能否编辑、删除或恢复这个帖子
+    #[serde(default)]
+    pub can_edit: bool,
+    #[serde(default)]
+    pub can_delete: bool,
+    #[serde(default)]
+    pub can_recover: bool,
+    /// 版本号，每次修改加一，从 1 开始
+    #[serde(default = "default_post_version")]
+    pub version: u64,
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// 作者自己删除的帖子在一段时间内仍然显示
+    #[serde(default)]
+    pub user_deleted: bool,
+    /// 帖子中的投票，正文中以 data-poll-name 对应
+    #[serde(default)]
+    pub polls: Vec<Poll>,
+    /// 当前用户在各个投票中选择的选项 ID，键为投票名称
+    #[serde(default)]
+    pub polls_votes: HashMap<String, Vec<String>>,
+}
+
+/// 帖子中的一个投票
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Poll {
+    /// 帖子内唯一的名称，默认为 poll
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: PollKind,
+    #[serde(default)]
+    pub status: PollStatus,
+    /// 公开投票可以看到每个选项的投票人
+    #[serde(default)]
+    pub public: bool,
+    #[serde(default)]
+    pub results: PollResults,
+    /// 多选投票最少和最多选择的选项数
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+    pub options: Vec<PollOption>,
+    #[serde(default)]
+    pub voters: u64,
+    /// 自动结束的时间
+    pub close: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PollKind {
+    #[default]
+    Regular,
+    Multiple,
+    /// 选项为数字的评分投票，只能选一个
+    Number,
+    /// 无法识别的类型（如新版本的 ranked_choice），只能查看
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PollStatus {
+    #[default]
+    Open,
+    Closed,
+    #[serde(other)]
+    Unknown,
+}
+
+/// 什么时候可以看到投票结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PollResults {
+    #[default]
+    Always,
+    OnVote,
+    OnClose,
+    StaffOnly,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PollOption {
+    pub id: String,
+    /// 选项内容，可能包含表情等 HTML
+    pub html: String,
+    /// 不能查看结果时为空
+    pub votes: Option<u64>,
+}
+
+impl Poll {
+    pub fn is_closed(&self) -> bool {
+        self.status == PollStatus::Closed || self.close.is_some_and(|close| close <= Utc::now())
+    }
+
+    /// 无法识别类型或状态的投票只显示，不能投票
+    pub fn is_supported(&self) -> bool {
+        self.kind != PollKind::Unknown && self.status != PollStatus::Unknown
+    }
+
+    /// 最多可以选择的选项数
+    pub fn max_choices(&self) -> usize {
+        match self.kind {
+            PollKind::Multiple => self.max.map_or(self.options.len(), |max| max as usize).max(1),
+            PollKind::Regular | PollKind::Number | PollKind::Unknown => 1,
+        }
+    }
+
+    /// 最少需要选择的选项数
+    pub fn min_choices(&self) -> usize {
+        match self.kind {
+            PollKind::Multiple => self.min.unwrap_or(1).max(1) as usize,
+            PollKind::Regular | PollKind::Number | PollKind::Unknown => 1,
+        }
+    }
+
+    /// 按投票结果的可见性判断能否显示票数，服务器只在可以查看结果时返回票数
+    pub fn shows_results(&self, voted: bool) -> bool {
+        let allowed = match self.results {
+            PollResults::Always | PollResults::StaffOnly | PollResults::Unknown => true,
+            PollResults::OnVote => voted || self.is_closed(),
+            PollResults::OnClose => self.is_closed(),
+        };
+        allowed && self.options.iter().any(|option| option.votes.is_some())
+    }
+
+    /// 全部选项的票数之和，多选投票中大于投票人数
+    pub fn total_votes(&self) -> u64 {
+        self.options.iter().filter_map(|option| option.votes).sum()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReplyToUser {
+    pub username: String,
+    pub avatar_template: Option<String>,
+}
+
+fn default_post_version() -> u64 {
+    1
+}
+
+impl Post {
+    /// 修改过的次数
+    pub fn edit_count(&self) -> u64 {
+        self.version.saturating_sub(1)
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some() || self.user_deleted
+    }
+}
+
+/// 帖子的一次修改，由 `/posts/{id}/revisions/{n}.json` 返回，差异为服务器生成的 HTML
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PostRevision {
+    pub post_id: u64,
+    /// 修改编号，等于修改后的版本号，从 2 开始
+    pub current_revision: u64,
+    pub previous_revision: Option<u64>,
+    pub next_revision: Option<u64>,
+    pub first_revision: Option<u64>,
+    pub last_revision: Option<u64>,
+    pub username: String,
+    pub created_at: DateTime<Utc>,
+    pub edit_reason: Option<String>,
+    pub body_changes: Option<BodyChanges>,
+    pub title_changes: Option<TitleChanges>,
+    pub category_id_changes: Option<ValueChanges<u64>>,
+    pub tags_changes: Option<ValueChanges<Vec<String>>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BodyChanges {
+    /// 按行对比 Markdown 原文的两列表格
+    pub side_by_side_markdown: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TitleChanges {
+    pub inline: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ValueChanges<T> {
+    pub previous: Option<T>,
+    pub current: Option<T>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -50,6 +479,8 @@ pub struct Category {
     pub description: Option<String>,
     pub color: String,
     pub text_color: String,
+    /// 当前用户对分类的通知级别，仅登录后返回
+    pub notification_level: Option<NotificationLevel>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -61,15 +492,73 @@ pub struct User {
     pub trust_level: u64,
 }
 
+/// 加载主题帖子时的筛选条件，可以同时使用
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PostFilter {
+    /// 只显示热门帖子（filter=summary）
+    pub summary: bool,
+    /// 只显示这个用户的帖子（username_filters）
+    pub username: Option<String>,
+}
+
+impl PostFilter {
+    pub fn is_active(&self) -> bool {
+        self.summary || self.username.is_some()
+    }
+
+    /// 筛选条件的说明，如 "热门帖子 · 仅 @user"
+    pub fn describe(&self) -> Option<String> {
+        let parts: Vec<String> = self
+            .summary
+            .then(|| "热门帖子".to_string())
+            .into_iter()
+            .chain(self.username.as_ref().map(|username| format!("仅 @{}", username)))
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+
+    fn query(&self) -> String {
+        let mut query = String::new();
+        if self.summary {
+            query.push_str("&filter=summary");
+        }
+        if let Some(ref username) = self.username {
+            query.push_str(&format!("&username_filters={}", urlencoding::encode(username)));
+        }
+        query
+    }
+}
+
+/// 用户接口的路径，用户名可能包含非 ASCII 字符，按路径段编码
+fn user_path(prefix: &str, username: &str, suffix: &str) -> String {
+    format!("{}/{}{}", prefix, urlencoding::encode(username), suffix)
+}
+
 #[async_trait]
 pub trait DiscourseClient {
     async fn get_latest_topics(&self, page: u32) -> Result<Vec<Topic>>;
-    #[allow(unused)]
-    async fn get_topic(&self, id: u64) -> Result<Topic>;
-    async fn get_topic_posts(&self, topic_id: u64, page: u32) -> Result<Vec<Post>>;
+    async fn get_topic(&self, id: u64) -> Result<TopicDetails>;
+    async fn get_topic_posts(&self, topic_id: u64, page: u32, filter: &PostFilter) -> Result<Vec<Post>>;
     async fn get_categories(&self) -> Result<Vec<Category>>;
     async fn get_user(&self, username: &str) -> Result<User>;
-    async fn create_post(&self, topic_id: u64, content: &str) -> Result<Post>;
+    /// 发布回复，`reply_to_post_number` 为回复的帖子楼层
+    async fn create_post(&self, topic_id: u64, content: &str, reply_to_post_number: Option<u64>) -> Result<Post>;
+    /// 获取直接回复某个帖子的帖子
+    async fn get_post_replies(&self, post_id: u64) -> Result<Vec<Post>>;
+    /// 获取帖子的 Markdown 原文
+    async fn get_post_raw(&self, post_id: u64) -> Result<String>;
+    async fn update_post(&self, post_id: u64, content: &str, edit_reason: &str) -> Result<Post>;
+    async fn delete_post(&self, post_id: u64) -> Result<()>;
+    async fn recover_post(&self, post_id: u64) -> Result<()>;
+    async fn get_post_revision(&self, post_id: u64, revision: u64) -> Result<PostRevision>;
+    /// 在投票中选择选项，返回更新后的投票
+    async fn vote_poll(&self, post_id: u64, poll_name: &str, options: &[String]) -> Result<Poll>;
+    /// 撤回投票，返回更新后的投票
+    async fn unvote_poll(&self, post_id: u64, poll_name: &str) -> Result<Poll>;
+    async fn set_topic_notification_level(&self, topic_id: u64, level: NotificationLevel) -> Result<()>;
+    async fn set_category_notification_level(&self, category_id: u64, level: NotificationLevel) -> Result<()>;
+    /// 静音或取消静音用户
+    async fn set_user_muted(&self, username: &str, muted: bool) -> Result<()>;
 }
 
 pub struct ApiClient {
@@ -105,6 +594,40 @@ impl ApiClient {
     fn url(&self, path: &str) -> String {
         format!("{}{}", self.config.url, path)
     }
+    
+    /// 发送需要登录的表单请求，检查状态码后返回响应
+    async fn send_form(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        params: &[(&str, String)],
+        action: &str,
+    ) -> Result<reqwest::Response> {
+        if self.config.api_key.is_empty() {
+            error!("{}失败: API密钥为空", action);
+            return Err(LdUiError::Unauthorized.into());
+        }
+        
+        let url = self.url(path);
+        debug!("请求URL: {}", url);
+        
+        let response = self.client.request(method, &url)
+            .form(params)
+            .send()
+            .await
+            .map_err(|e| {
+                error!("发送{}请求失败: {}", action, e);
+                LdUiError::Request(e)
+            })?;
+            
+        if !response.status().is_success() {
+            let err_msg = format!("{}失败，状态码: {}", action, response.status());
+            error!("{}", err_msg);
+            return Err(LdUiError::Api(err_msg).into());
+        }
+        debug!("{}请求成功，状态码: {}", action, response.status());
+        Ok(response)
+    }
 }
 
 #[async_trait]
@@ -146,18 +669,28 @@ impl DiscourseClient for ApiClient {
             })?
             .to_owned();
             
-        let topics: Vec<Topic> = serde_json::from_value(Value::Array(topics))
+        let mut topics: Vec<Topic> = serde_json::from_value(Value::Array(topics))
             .map_err(|e| {
                 error!("解析主题数据失败: {}", e);
                 LdUiError::Parse(format!("解析主题失败: {}", e))
             })?;
+        
+        // 参与者只有用户 ID，用户名在单独的 users 列表中
+        let usernames: HashMap<i64, String> = json["users"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|user| Some((user["id"].as_i64()?, user["username"].as_str()?.to_string())))
+            .collect();
+        for poster in topics.iter_mut().flat_map(|topic| topic.posters.iter_mut()) {
+            poster.username = usernames.get(&poster.user_id).cloned();
+        }
             
         info!("成功获取最新主题，共 {} 条", topics.len());
         Ok(topics)
     }
     
-    #[allow(unused)]
-    async fn get_topic(&self, id: u64) -> Result<Topic> {
+    async fn get_topic(&self, id: u64) -> Result<TopicDetails> {
         info!("开始获取主题详情, ID: {}", id);
         let url = self.url(&format!("/t/{}.json", id));
         debug!("请求URL: {}", url);
@@ -177,7 +710,7 @@ impl DiscourseClient for ApiClient {
         }
         debug!("获取主题详情成功，状态码: {}", response.status());
         
-        let topic: Topic = response.json().await
+        let topic: TopicDetails = response.json().await
             .map_err(|e| {
                 error!("解析主题详情失败: {}", e);
                 LdUiError::Parse(format!("解析主题失败: {}", e))
@@ -187,9 +720,9 @@ impl DiscourseClient for ApiClient {
         Ok(topic)
     }
     
-    async fn get_topic_posts(&self, topic_id: u64, page: u32) -> Result<Vec<Post>> {
-        info!("开始获取主题帖子, 主题ID: {}, 页码: {}", topic_id, page);
-        let url = self.url(&format!("/t/topic/{}.json?page={}", topic_id, page));
+    async fn get_topic_posts(&self, topic_id: u64, page: u32, filter: &PostFilter) -> Result<Vec<Post>> {
+        info!("开始获取主题帖子, 主题ID: {}, 页码: {}, 筛选: {:?}", topic_id, page, filter);
+        let url = self.url(&format!("/t/topic/{}.json?page={}{}", topic_id, page, filter.query()));
         debug!("请求URL: {}", url);
         
         let response = self.client.get(&url)
@@ -279,7 +812,7 @@ impl DiscourseClient for ApiClient {
     
     async fn get_user(&self, username: &str) -> Result<User> {
         info!("开始获取用户信息, 用户名: {}", username);
-        let url = self.url(&format!("/users/{}.json", username));
+        let url = self.url(&user_path("/users", username, ".json"));
         debug!("请求URL: {}", url);
         
         let response = self.client.get(&url)
@@ -315,7 +848,7 @@ impl DiscourseClient for ApiClient {
         Ok(user)
     }
     
-    async fn create_post(&self, topic_id: u64, content: &str) -> Result<Post> {
+    async fn create_post(&self, topic_id: u64, content: &str, reply_to_post_number: Option<u64>) -> Result<Post> {
         info!("开始创建帖子, 主题ID: {}", topic_id);
         
         if self.config.api_key.is_empty() {
@@ -333,10 +866,13 @@ impl DiscourseClient for ApiClient {
         };
         debug!("发布内容预览: {}", content_preview);
         
-        let params = [
+        let mut params = vec![
             ("topic_id", topic_id.to_string()),
             ("raw", content.to_string()),
         ];
+        if let Some(post_number) = reply_to_post_number {
+            params.push(("reply_to_post_number", post_number.to_string()));
+        }
         
         let response = self.client.post(&url)
             .form(&params)
@@ -363,4 +899,222 @@ impl DiscourseClient for ApiClient {
         info!("成功创建帖子, 帖子ID: {}", post.id);
         Ok(post)
     }
-} 
\ No newline at end of file
+    
+    async fn get_post_replies(&self, post_id: u64) -> Result<Vec<Post>> {
+        info!("开始获取帖子的回复, 帖子ID: {}", post_id);
+        let url = self.url(&format!("/posts/{}/replies.json", post_id));
+        debug!("请求URL: {}", url);
+        
+        let response = self.client.get(&url)
+            .send()
+            .await
+            .map_err(|e| {
+                error!("请求帖子回复失败: {}", e);
+                LdUiError::Request(e)
+            })?;
+            
+        if !response.status().is_success() {
+            let err_msg = format!("获取帖子回复失败，状态码: {}", response.status());
+            error!("{}", err_msg);
+            return Err(LdUiError::Api(err_msg).into());
+        }
+        debug!("获取帖子回复成功，状态码: {}", response.status());
+        
+        let replies: Vec<Post> = response.json().await
+            .map_err(|e| {
+                error!("解析帖子回复失败: {}", e);
+                LdUiError::Parse(format!("解析回复失败: {}", e))
+            })?;
+            
+        info!("成功获取帖子回复，共 {} 条", replies.len());
+        Ok(replies)
+    }
+    
+    async fn get_post_raw(&self, post_id: u64) -> Result<String> {
+        info!("开始获取帖子原文, 帖子ID: {}", post_id);
+        let url = self.url(&format!("/posts/{}.json", post_id));
+        debug!("请求URL: {}", url);
+        
+        let response = self.client.get(&url)
+            .send()
+            .await
+            .map_err(|e| {
+                error!("请求帖子原文失败: {}", e);
+                LdUiError::Request(e)
+            })?;
+            
+        if !response.status().is_success() {
+            let err_msg = format!("获取帖子原文失败，状态码: {}", response.status());
+            error!("{}", err_msg);
+            return Err(LdUiError::Api(err_msg).into());
+        }
+        debug!("获取帖子原文成功，状态码: {}", response.status());
+        
+        let json: Value = response.json().await
+            .map_err(|e| {
+                error!("解析帖子原文响应失败: {}", e);
+                LdUiError::Parse(format!("解析响应失败: {}", e))
+            })?;
+            
+        let raw = json["raw"]
+            .as_str()
+            .ok_or_else(|| {
+                let err_msg = "响应中没有帖子原文".to_string();
+                error!("{}", err_msg);
+                LdUiError::Parse(err_msg)
+            })?
+            .to_string();
+            
+        info!("成功获取帖子原文, 长度: {}", raw.len());
+        Ok(raw)
+    }
+    
+    async fn update_post(&self, post_id: u64, content: &str, edit_reason: &str) -> Result<Post> {
+        info!("开始修改帖子, 帖子ID: {}", post_id);
+        let path = format!("/posts/{}.json", post_id);
+        let params = [
+            ("post[raw]", content.to_string()),
+            ("post[edit_reason]", edit_reason.to_string()),
+        ];
+        let response = self.send_form(reqwest::Method::PUT, &path, &params, "修改帖子").await?;
+        
+        let json: Value = response.json().await
+            .map_err(|e| {
+                error!("解析修改帖子响应失败: {}", e);
+                LdUiError::Parse(format!("解析响应失败: {}", e))
+            })?;
+            
+        let post: Post = serde_json::from_value(json["post"].clone())
+            .map_err(|e| {
+                error!("解析修改后的帖子数据失败: {}", e);
+                LdUiError::Parse(format!("解析帖子失败: {}", e))
+            })?;
+            
+        info!("成功修改帖子, 帖子ID: {}, 版本: {}", post.id, post.version);
+        Ok(post)
+    }
+    
+    async fn delete_post(&self, post_id: u64) -> Result<()> {
+        info!("删除帖子, 帖子ID: {}", post_id);
+        let path = format!("/posts/{}.json", post_id);
+        self.send_form(reqwest::Method::DELETE, &path, &[], "删除帖子").await?;
+        Ok(())
+    }
+    
+    async fn recover_post(&self, post_id: u64) -> Result<()> {
+        info!("恢复帖子, 帖子ID: {}", post_id);
+        let path = format!("/posts/{}/recover.json", post_id);
+        self.send_form(reqwest::Method::PUT, &path, &[], "恢复帖子").await?;
+        Ok(())
+    }
+    
+    async fn get_post_revision(&self, post_id: u64, revision: u64) -> Result<PostRevision> {
+        info!("开始获取帖子修改记录, 帖子ID: {}, 修改编号: {}", post_id, revision);
+        let url = self.url(&format!("/posts/{}/revisions/{}.json", post_id, revision));
+        debug!("请求URL: {}", url);
+        
+        let response = self.client.get(&url)
+            .send()
+            .await
+            .map_err(|e| {
+                error!("请求帖子修改记录失败: {}", e);
+                LdUiError::Request(e)
+            })?;
+            
+        if !response.status().is_success() {
+            let err_msg = format!("获取修改记录失败，状态码: {}", response.status());
+            error!("{}", err_msg);
+            return Err(LdUiError::Api(err_msg).into());
+        }
+        debug!("获取帖子修改记录成功，状态码: {}", response.status());
+        
+        let revision: PostRevision = response.json().await
+            .map_err(|e| {
+                error!("解析帖子修改记录失败: {}", e);
+                LdUiError::Parse(format!("解析修改记录失败: {}", e))
+            })?;
+            
+        info!("成功获取帖子修改记录, 修改编号: {}", revision.current_revision);
+        Ok(revision)
+    }
+    
+    async fn vote_poll(&self, post_id: u64, poll_name: &str, options: &[String]) -> Result<Poll> {
+        info!("投票, 帖子ID: {}, 投票: {}, 选项: {:?}", post_id, poll_name, options);
+        let mut params = vec![("post_id", post_id.to_string()), ("poll_name", poll_name.to_string())];
+        params.extend(options.iter().map(|option| ("options[]", option.clone())));
+        let response = self.send_form(reqwest::Method::PUT, "/polls/vote", &params, "投票").await?;
+        parse_poll_response(response).await
+    }
+    
+    async fn unvote_poll(&self, post_id: u64, poll_name: &str) -> Result<Poll> {
+        info!("撤回投票, 帖子ID: {}, 投票: {}", post_id, poll_name);
+        let params = [("post_id", post_id.to_string()), ("poll_name", poll_name.to_string())];
+        let response = self.send_form(reqwest::Method::DELETE, "/polls/vote", &params, "撤回投票").await?;
+        parse_poll_response(response).await
+    }
+    
+    async fn set_topic_notification_level(&self, topic_id: u64, level: NotificationLevel) -> Result<()> {
+        info!("设置主题通知级别, 主题ID: {}, 级别: {:?}", topic_id, level);
+        let path = format!("/t/{}/notifications", topic_id);
+        let params = [("notification_level", u8::from(level).to_string())];
+        self.send_form(reqwest::Method::POST, &path, &params, "设置主题通知级别").await?;
+        Ok(())
+    }
+    
+    async fn set_category_notification_level(&self, category_id: u64, level: NotificationLevel) -> Result<()> {
+        info!("设置分类通知级别, 分类ID: {}, 级别: {:?}", category_id, level);
+        let path = format!("/category/{}/notifications", category_id);
+        let params = [("notification_level", u8::from(level).to_string())];
+        self.send_form(reqwest::Method::POST, &path, &params, "设置分类通知级别").await?;
+        Ok(())
+    }
+    
+    async fn set_user_muted(&self, username: &str, muted: bool) -> Result<()> {
+        info!("设置用户静音, 用户: {}, 静音: {}", username, muted);
+        let path = user_path("/u", username, "/notification_level.json");
+        let level = if muted { "mute" } else { "normal" };
+        let params = [("notification_level", level.to_string())];
+        self.send_form(reqwest::Method::PUT, &path, &params, "设置用户静音").await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topic_with_tags(tags: Value) -> Topic {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "title": "标题",
+            "posts_count": 1,
+            "views": 0,
+            "created_at": "2024-01-01T00:00:00Z",
+            "posters": [],
+            "tags": tags,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn topic_tags_accept_strings_and_objects() {
+        assert_eq!(topic_with_tags(serde_json::json!(["rust", "tui"])).tags, vec!["rust", "tui"]);
+        let objects = serde_json::json!([{ "id": 1, "name": "rust", "slug": "rust" }, { "id": 2, "name": "终端", "slug": "2-tag" }]);
+        assert_eq!(topic_with_tags(objects).tags, vec!["rust", "终端"]);
+        assert!(topic_with_tags(Value::Null).tags.is_empty());
+    }
+
+    #[test]
+    fn user_paths_encode_username() {
+        assert_eq!(user_path("/u", "jo.hn-doe_1", "/notification_level.json"), "/u/jo.hn-doe_1/notification_level.json");
+        assert_eq!(user_path("/u", "张三", "/notification_level.json"), "/u/%E5%BC%A0%E4%B8%89/notification_level.json");
+        assert_eq!(user_path("/users", "a b/c", ".json"), "/users/a%20b%2Fc.json");
+    }
+
+    #[test]
+    fn post_filter_query_encodes_username() {
+        let filter = PostFilter { summary: true, username: Some("张三".to_string()) };
+        assert_eq!(filter.query(), "&filter=summary&username_filters=%E5%BC%A0%E4%B8%89");
+        assert_eq!(PostFilter::default().query(), "");
+    }
+}
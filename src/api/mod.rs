@@ -1,3 +1,3 @@
 mod discourse;
 
-pub use discourse::{DiscourseClient, ApiClient, Topic, Post, Category, User}; 
\ No newline at end of file
+pub use discourse::{DiscourseClient, ApiClient, Topic, Post, Category, User, NotificationLevel, PostFilter, PostRevision, Poll, PollKind, PollResults, SuggestedTopic, TopicDetails}; 
\ No newline at end of file
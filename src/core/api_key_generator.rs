@@ -52,10 +52,10 @@ pub fn generate_user_api_key(
     let nonce = generate_nonce();
     
     // 构建请求 URL
-    let params = vec![
+    let params = [
         format!("application_name={}", encode(application_name)),
         format!("client_id={}", encode(&client_id_to_use)),
-        format!("scopes=read"),
+        "scopes=read".to_string(),
         format!("public_key={}", encode(&public_key_pem)),
         format!("nonce={}", encode(&nonce)),
     ];
@@ -1,13 +1,34 @@
+use std::cell::RefCell;
 use std::sync::Arc;
 use std::sync::Mutex;
-use std::collections::HashMap;
-use crossterm::event::{KeyEvent, KeyCode};
+use std::collections::{HashMap, HashSet};
+use crossterm::event::{KeyEvent, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
 
 use crate::core::config::Config;
-use crate::api::{DiscourseClient, ApiClient, Topic, Post, Category, User};
-use crate::core::image::ImageCache;
-use tracing::warn;
+use crate::api::{DiscourseClient, ApiClient, Topic, Post, Category, User, NotificationLevel, PostFilter, SuggestedTopic, TopicDetails};
+use crate::core::image::{absolute_url, avatar_url, extract_image_urls, extract_images, fetch_image, ImageCache, PostImage};
+use crate::core::external;
+use crate::core::gallery::Gallery;
+use crate::core::image_view::ImageView;
+use crate::core::keymap::{Action, Key, KeyMatch, KeyMode, Keymap};
+use crate::core::theme::Theme;
+use crate::core::mouse::{ClickTarget, ClickTracker, HitRegions};
+use crate::core::history::History;
+use crate::core::palette::{Palette, PaletteItem};
+use crate::core::search::TopicSearch;
+use crate::core::topic_list::{visible_topics, TopicFilter, TopicSort};
+use crate::core::ignore::{IgnoreRules, IgnoreTarget};
+use crate::core::composer::{ComposeTarget, Composer};
+use crate::core::revision::RevisionView;
+use crate::core::thread::{ThreadRow, ThreadTree};
+use crate::core::poll::PollVoter;
+use crate::core::html::{render_post, ContentBlock};
+use crate::core::notification::{NotificationPicker, NotificationTarget};
 
 pub type AppResult<T> = std::result::Result<T, anyhow::Error>;
 
@@ -28,6 +49,32 @@ pub enum LoadingState {
     Error(String),
 }
 
+/// 设置页中的选项：生成 API 密钥、切换主题
+pub const SETTINGS_OPTIONS: usize = 2;
+
+/// 加载全部页面进行搜索时最多加载的页数
+const MAX_SEARCH_PAGES: u32 = 100;
+/// 主题每页的帖子数
+const POSTS_PER_PAGE: u64 = 20;
+
+/// 状态消息显示的时长
+const STATUS_MESSAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 后台加载搜索页面的进度
+enum SearchPageEvent {
+    Page { topic_id: u64, page: u32, posts: Vec<Post> },
+    /// 加载结束，第一页就失败时带有错误信息
+    Finished { topic_id: u64, error: Option<String> },
+}
+
+/// 状态栏中显示的临时消息
+#[derive(Debug, Clone)]
+pub struct StatusMessage {
+    pub text: String,
+    pub is_error: bool,
+    pub created_at: Instant,
+}
+
 pub struct App {
     pub config: Config,
     pub client: Arc<dyn DiscourseClient + Send + Sync>,
@@ -41,16 +88,77 @@ pub struct App {
     pub loading_state: LoadingState,
     pub show_help: bool,
     pub should_quit: bool,
-    pub input: String,
-    pub input_mode: bool,
+    /// 打开中的回复或编辑输入框
+    pub composer: Option<Composer>,
     pub image_cache: ImageCache,
     pub image_paths: Arc<Mutex<HashMap<String, PathBuf>>>,
+    /// 等待原图下载完成后执行的保存或外部打开操作
+    pending_image_actions: Vec<(Action, PostImage)>,
+    /// 后台下载结束的图片地址，无论成功与否
+    downloaded_images_tx: mpsc::UnboundedSender<String>,
+    downloaded_images_rx: mpsc::UnboundedReceiver<String>,
     pub selected_image_button: Option<usize>,
     pub showing_image: bool,
-    pub current_image_url: Option<String>,
+    pub current_image: Option<PostImage>,
     pub need_redraw: bool,
     pub viewing_full_post: bool,
     pub post_scroll: u16,
+    pub gallery: Option<Gallery>,
+    pub image_view: ImageView,
+    pub status_message: Option<StatusMessage>,
+    pub keymap: Keymap,
+    pub theme: Theme,
+    /// 组合键中已按下、尚未匹配完成的按键
+    pub pending_keys: Vec<Key>,
+    /// 上一帧中可以点击的区域
+    pub hit_regions: HitRegions,
+    click_tracker: ClickTracker,
+    /// 最近访问的主题、分类和用户
+    pub history: History,
+    /// 打开中的命令面板
+    pub palette: Option<Palette>,
+    /// 当前主题内的搜索
+    pub search: Option<TopicSearch>,
+    /// 正在后台加载全部页面的任务
+    search_loader: Option<JoinHandle<()>>,
+    search_pages_tx: mpsc::UnboundedSender<SearchPageEvent>,
+    search_pages_rx: mpsc::UnboundedReceiver<SearchPageEvent>,
+    /// 主题列表的筛选条件和排序方式
+    pub topic_filter: TopicFilter,
+    pub topic_sort: TopicSort,
+    pub ignore_rules: IgnoreRules,
+    /// 已展开的被忽略帖子
+    pub expanded_posts: HashSet<u64>,
+    /// 已知的主题通知级别，来自主题列表和在应用中的修改
+    pub topic_levels: HashMap<u64, NotificationLevel>,
+    /// 打开中的通知级别选择器
+    pub notification_picker: Option<NotificationPicker>,
+    /// 等待按 y 确认删除的帖子（帖子 ID、楼层）
+    pub pending_delete: Option<(u64, u64)>,
+    /// 打开中的帖子修改历史
+    pub revisions: Option<RevisionView>,
+    /// 打开中的投票窗口
+    pub poll_voter: Option<PollVoter>,
+    /// 已显示剧透的帖子
+    pub revealed_spoilers: HashSet<u64>,
+    /// 已展开的 <details>，为（帖子 ID，帖子中的区域序号）
+    pub expanded_details: HashSet<(u64, usize)>,
+    /// 上一次绘制完整帖子时 <details> 标题所在的行，为（区域序号，行号）
+    pub details_rows: RefCell<Vec<(usize, usize)>>,
+    /// 已展开回复列表的帖子及其回复
+    pub post_replies: HashMap<u64, Vec<Post>>,
+    /// 按回复关系以树形显示主题
+    pub thread_view: bool,
+    /// 树形显示时折叠了回复的帖子
+    pub folded_threads: HashSet<u64>,
+    /// 已加载帖子的回复树，随帖子一起更新
+    thread_trees: HashMap<u64, ThreadTree>,
+    /// 当前主题帖子的筛选条件
+    pub post_filter: PostFilter,
+    /// 已打开主题的详情：分类、标签、参与者和权限
+    pub topic_details: HashMap<u64, TopicDetails>,
+    /// 帖子列表上方的主题信息是否展开
+    pub topic_header_expanded: bool,
 }
 
 impl App {
@@ -58,10 +166,29 @@ impl App {
         // 创建客户端
         let client = Arc::new(ApiClient::new(config.discourse.clone()));
         
+        // 配置加载时已检查过冲突，这里出错时退回默认快捷键
+        let keymap = Keymap::new(&config.keybindings).unwrap_or_else(|e| {
+            warn!("快捷键配置无效，使用默认快捷键: {}", e);
+            Keymap::default()
+        });
+        let theme = Theme::load(&config.theme, &config.theme.name).unwrap_or_else(|e| {
+            warn!("主题配置无效，使用默认主题: {}", e);
+            Theme::default()
+        });
+        
+        let ignore_rules = IgnoreRules::new(&config.ignore).unwrap_or_else(|e| {
+            warn!("忽略规则无效，不忽略任何内容: {}", e);
+            IgnoreRules::default()
+        });
+        let config_topic_sort = config.topics.sort;
+        let thread_view = config.ui.threaded;
+        
         // 创建图片缓存目录
         let cache_dir = dirs::cache_dir()
             .unwrap_or_else(|| std::path::PathBuf::from("./.cache"))
             .join("ldui/images");
+        let (downloaded_images_tx, downloaded_images_rx) = mpsc::unbounded_channel();
+        let (search_pages_tx, search_pages_rx) = mpsc::unbounded_channel();
         
         Self {
             config,
@@ -76,16 +203,51 @@ impl App {
             loading_state: LoadingState::NotLoading,
             show_help: false,
             should_quit: false,
-            input: String::new(),
-            input_mode: false,
+            composer: None,
             image_cache: ImageCache::new(cache_dir),
             image_paths: Arc::new(Mutex::new(HashMap::new())),
+            pending_image_actions: Vec::new(),
+            downloaded_images_tx,
+            downloaded_images_rx,
             selected_image_button: None,
             showing_image: false,
-            current_image_url: None,
+            current_image: None,
             need_redraw: false,
             viewing_full_post: false,
             post_scroll: 0,
+            gallery: None,
+            image_view: ImageView::default(),
+            status_message: None,
+            keymap,
+            theme,
+            pending_keys: Vec::new(),
+            hit_regions: HitRegions::default(),
+            click_tracker: ClickTracker::default(),
+            history: History::default(),
+            palette: None,
+            search: None,
+            search_loader: None,
+            search_pages_tx,
+            search_pages_rx,
+            topic_filter: TopicFilter::default(),
+            topic_sort: config_topic_sort,
+            ignore_rules,
+            expanded_posts: HashSet::new(),
+            topic_levels: HashMap::new(),
+            notification_picker: None,
+            pending_delete: None,
+            revisions: None,
+            poll_voter: None,
+            revealed_spoilers: HashSet::new(),
+            expanded_details: HashSet::new(),
+            details_rows: RefCell::new(Vec::new()),
+            post_replies: HashMap::new(),
+            thread_view,
+            folded_threads: HashSet::new(),
+            thread_trees: HashMap::new(),
+            post_filter: PostFilter::default(),
+            topic_details: HashMap::new(),
+            topic_header_expanded: true,
         }
     }
     
@@ -96,6 +258,17 @@ impl App {
     }
     
     pub async fn tick(&mut self) -> AppResult<()> {
+        // 清除过期的状态消息
+        if self
+            .status_message
+            .as_ref()
+            .is_some_and(|message| message.created_at.elapsed() >= STATUS_MESSAGE_TIMEOUT)
+        {
+            self.status_message = None;
+        }
+        self.finish_image_actions();
+        self.receive_search_pages();
+        
         // 刷新数据
         if !matches!(self.loading_state, LoadingState::Loading) {
             match self.current_tab.clone() {
@@ -118,158 +291,672 @@ impl App {
         Ok(())
     }
     
-    pub async fn handle_key_event(&mut self, key: KeyEvent) -> AppResult<()> {
-        // 如果正在显示图片，任何按键都会关闭图片显示
+    /// 当前界面对应的快捷键模式
+    pub fn key_mode(&self) -> KeyMode {
         if self.showing_image {
-            match key.code {
-                KeyCode::Enter | KeyCode::Esc | KeyCode::Char('o') => {
-                    self.showing_image = false;
-                    self.current_image_url = None;
-                    return Ok(());
-                }
-                _ => return Ok(()), // 忽略其他按键
+            KeyMode::Image
+        } else if let Some(gallery) = &self.gallery {
+            if gallery.previewing {
+                KeyMode::Preview
+            } else {
+                KeyMode::Gallery
             }
+        } else if self.viewing_full_post {
+            KeyMode::Post
+        } else {
+            KeyMode::Normal
+        }
+    }
+    
+    pub async fn handle_key_event(&mut self, key: KeyEvent) -> AppResult<()> {
+        self.adopt_search_scroll();
+        // 帮助窗口打开时，任意键关闭
+        if self.show_help {
+            self.show_help = false;
+            self.pending_keys.clear();
+            self.need_redraw = true;
+            return Ok(());
+        }
+        if self.palette.is_some() {
+            return self.handle_palette_key(key).await;
+        }
+        if self.notification_picker.is_some() {
+            return self.handle_notification_picker_key(key).await;
+        }
+        if self.revisions.is_some() {
+            return self.handle_revisions_key(key).await;
+        }
+        if self.poll_voter.is_some() {
+            return self.handle_poll_key(key).await;
+        }
+        if self.pending_delete.is_some() {
+            return self.handle_delete_confirm_key(key).await;
+        }
+        if self.search.as_ref().is_some_and(|search| search.editing) {
+            return self.handle_search_key(key).await;
+        }
+        if self.topic_filter.editing {
+            self.handle_filter_key(key);
+            return Ok(());
         }
         
-        // 如果正在查看完整帖子
-        if self.viewing_full_post {
-            match key.code {
-                KeyCode::Esc => {
-                    // 退出完整帖子查看模式
-                    self.viewing_full_post = false;
-                    self.post_scroll = 0;
-                    return Ok(());
-                }
-                KeyCode::Char('o') => {
-                    // 'o'键用于查看图片
-                    if let Some(button_index) = self.selected_image_button {
-                        if let Some(posts) = self.posts.get(&self.get_current_topic_id()) {
-                            if self.selected_index < posts.len() {
-                                let post = &posts[self.selected_index];
-                                let image_urls = crate::core::image::extract_image_urls(&post.cooked);
-                                
-                                // 创建可用图片映射
-                                let mut available_images = Vec::new();
-                                for (i, url) in image_urls.iter().enumerate() {
-                                    if self.image_paths.lock().unwrap().get::<str>(url).is_some() {
-                                        available_images.push((i, url.clone()));
-                                    }
-                                }
-                                
-                                if button_index < available_images.len() {
-                                    // 获取真实的URL
-                                    let (_, url) = &available_images[button_index];
-                                    self.showing_image = true;
-                                    self.current_image_url = Some(url.clone());
-                                    return Ok(());
-                                }
-                            }
-                        }
+        let mode = self.key_mode();
+        if self.composer.is_some() && mode == KeyMode::Normal {
+            return self.handle_composer_key(key).await;
+        }
+        
+        self.pending_keys.push(Key::from(key));
+        let action = match self.keymap.resolve(mode, &self.pending_keys) {
+            KeyMatch::Action(action) => action,
+            KeyMatch::Pending => return Ok(()),
+            // 组合键没有完成时按下了其他键，丢弃之前的按键，单独匹配这个键
+            KeyMatch::None if self.pending_keys.len() > 1 => {
+                self.pending_keys = vec![Key::from(key)];
+                match self.keymap.resolve(mode, &self.pending_keys) {
+                    KeyMatch::Action(action) => action,
+                    KeyMatch::Pending => return Ok(()),
+                    KeyMatch::None => {
+                        self.pending_keys.clear();
+                        return Ok(());
                     }
-                    return Ok(());
                 }
-                KeyCode::Enter => {
-                    // Enter键现在只用于返回，不再用于查看图片
-                    self.viewing_full_post = false;
-                    self.post_scroll = 0;
-                    self.selected_image_button = None;
-                    return Ok(());
+            }
+            KeyMatch::None => {
+                self.pending_keys.clear();
+                return Ok(());
+            }
+        };
+        self.pending_keys.clear();
+        
+        self.perform_action(mode, action).await
+    }
+    
+    pub async fn handle_mouse_event(&mut self, event: MouseEvent) -> AppResult<()> {
+        self.adopt_search_scroll();
+        // 帮助窗口打开时，点击任意位置关闭
+        if self.show_help {
+            if matches!(event.kind, MouseEventKind::Down(_)) {
+                self.show_help = false;
+                self.need_redraw = true;
+            }
+            return Ok(());
+        }
+        if self.composer.is_some()
+            || self.palette.is_some()
+            || self.notification_picker.is_some()
+            || self.revisions.is_some()
+            || self.poll_voter.is_some()
+            || self.pending_delete.is_some()
+            || self.topic_filter.editing
+            || self.search.as_ref().is_some_and(|search| search.editing)
+        {
+            return Ok(());
+        }
+        
+        let mode = self.key_mode();
+        // 查看图片时滚轮用于缩放，其他界面用于滚动
+        let scroll = |up: bool| match (mode, up) {
+            (KeyMode::Image | KeyMode::Preview, true) => Action::ZoomIn,
+            (KeyMode::Image | KeyMode::Preview, false) => Action::ZoomOut,
+            (_, true) => Action::Up,
+            (_, false) => Action::Down,
+        };
+        match event.kind {
+            MouseEventKind::ScrollUp => self.perform_action(mode, scroll(true)).await?,
+            MouseEventKind::ScrollDown => self.perform_action(mode, scroll(false)).await?,
+            // 图片和画廊覆盖在其他内容之上，只响应滚轮
+            MouseEventKind::Down(MouseButton::Left) if matches!(mode, KeyMode::Normal | KeyMode::Post) => {
+                if let Some(target) = self.hit_regions.target_at(event.column, event.row) {
+                    let double_click = self.click_tracker.click(&target);
+                    self.handle_click(target, double_click).await?;
                 }
-                KeyCode::Up | KeyCode::Char('k') => {
-                    // 向上滚动
-                    if self.post_scroll > 0 {
-                        self.post_scroll -= 1;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// 点击选中列表项，双击打开；点击标签页、图片按钮和链接直接打开
+    async fn handle_click(&mut self, target: ClickTarget, double_click: bool) -> AppResult<()> {
+        match target {
+            ClickTarget::Tab(index) => {
+                self.viewing_full_post = false;
+                self.post_scroll = 0;
+                match index {
+                    0 => {
+                        self.current_tab = AppTab::Home;
+                        self.selected_index = 0;
                     }
-                    return Ok(());
+                    1 => self.perform_action(KeyMode::Normal, Action::ShowTopics).await?,
+                    2 => self.perform_action(KeyMode::Normal, Action::ShowCategories).await?,
+                    _ => self.perform_action(KeyMode::Normal, Action::ShowSettings).await?,
                 }
-                KeyCode::Down | KeyCode::Char('j') => {
-                    // 向下滚动
-                    self.post_scroll += 1;
-                    return Ok(());
+            }
+            ClickTarget::ListItem(index) => {
+                self.navigate_to(index);
+                if double_click {
+                    self.perform_action(KeyMode::Normal, Action::Select).await?;
                 }
-                KeyCode::Tab | KeyCode::Char('i') => {
-                    // 选择图片
-                    if let Some(posts) = self.posts.get(&self.get_current_topic_id()) {
-                        if self.selected_index < posts.len() {
-                            let post = &posts[self.selected_index];
-                            let image_urls = crate::core::image::extract_image_urls(&post.cooked);
-                            
-                            // 创建可用图片映射
-                            let mut available_images = Vec::new();
-                            for (i, url) in image_urls.iter().enumerate() {
-                                if self.image_paths.lock().unwrap().get::<str>(url).is_some() {
-                                    available_images.push((i, url.clone()));
-                                }
-                            }
-                            
-                            if !available_images.is_empty() {
-                                // 选择第一个图片或切换到下一个图片
-                                if self.selected_image_button.is_none() {
-                                    self.selected_image_button = Some(0);
-                                } else {
-                                    let next_index = (self.selected_image_button.unwrap() + 1) % available_images.len();
-                                    self.selected_image_button = Some(next_index);
-                                }
-                                return Ok(());
-                            }
-                        }
-                    }
+            }
+            ClickTarget::ImageButton(index) => {
+                if let Some(image) = self.available_post_images().get(index).cloned() {
+                    self.selected_image_button = Some(index);
+                    self.show_image(image);
                 }
-                _ => {}
             }
-            // 在完整帖子查看模式下，忽略其他按键
+            ClickTarget::Link(href) => self.open_link(&href).await?,
+            ClickTarget::Details(section) => self.toggle_details(section),
+        }
+        Ok(())
+    }
+    
+    /// 打开正文中的链接：站内用户链接（如 @提及）在应用中打开，其他链接使用浏览器打开
+    async fn open_link(&mut self, href: &str) -> AppResult<()> {
+        let base_url = self.config.discourse.url.clone();
+        let url = absolute_url(&base_url, href);
+        
+        let username = url
+            .strip_prefix(&format!("{}/u/", base_url.trim_end_matches('/')))
+            .and_then(|rest| rest.split(['/', '?', '#']).next())
+            .filter(|username| !username.is_empty())
+            .map(str::to_string);
+        if let Some(username) = username {
+            self.open_user(username).await?;
+            return Ok(());
+        }
+        
+        match external::open_external(std::path::Path::new(&url), None) {
+            Ok(()) => self.set_status(format!("已在浏览器中打开: {}", url), false),
+            Err(e) => self.set_status(format!("无法打开链接: {}", e), true),
+        }
+        Ok(())
+    }
+    
+    /// 在指定模式下执行操作
+    async fn perform_action(&mut self, mode: KeyMode, action: Action) -> AppResult<()> {
+        // 浏览模式下搜索进行中时，翻页键在匹配之间移动
+        let action = match action {
+            Action::NextPage if mode == KeyMode::Normal && self.is_searching() => Action::SearchNext,
+            Action::PrevPage if mode == KeyMode::Normal && self.is_searching() => Action::SearchPrev,
+            action => action,
+        };
+        match action {
+            Action::Quit => {
+                self.should_quit = true;
+                return Ok(());
+            }
+            Action::ToggleHelp => {
+                self.show_help = !self.show_help;
+                // 清除帮助窗口下残留的图片
+                self.need_redraw = true;
+                return Ok(());
+            }
+            Action::OpenPalette => {
+                self.palette = Some(Palette::new(mode, &self.keymap, &self.history));
+                self.need_redraw = true;
+                return Ok(());
+            }
+            Action::Search | Action::SearchNext | Action::SearchPrev | Action::SearchAllPages => {
+                return self.handle_search_action(mode, action).await;
+            }
+            Action::ShowRevisions => {
+                self.open_revisions().await;
+                return Ok(());
+            }
+            Action::ShowPoll => {
+                self.open_poll_voter(0);
+                return Ok(());
+            }
+            Action::ToggleSpoiler => {
+                self.toggle_spoilers();
+                return Ok(());
+            }
+            _ => {}
+        }
+        
+        match mode {
+            KeyMode::Image => self.handle_image_viewer_action(action).await,
+            KeyMode::Gallery | KeyMode::Preview => self.handle_gallery_action(action).await,
+            KeyMode::Post => self.handle_post_action(action),
+            KeyMode::Normal => self.handle_normal_action(action).await?,
+        }
+        Ok(())
+    }
+    
+    /// 命令面板：输入筛选条件，Enter 执行选中项，Esc 关闭
+    async fn handle_palette_key(&mut self, key: KeyEvent) -> AppResult<()> {
+        let Some(palette) = self.palette.as_mut() else {
             return Ok(());
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => {
+                self.palette = None;
+                self.need_redraw = true;
+            }
+            KeyCode::Enter => {
+                let palette = self.palette.take().expect("命令面板已打开");
+                self.need_redraw = true;
+                if let Some(entry) = palette.current() {
+                    self.run_palette_item(palette.mode, entry.item.clone()).await?;
+                }
+            }
+            KeyCode::Up | KeyCode::BackTab => palette.prev(),
+            KeyCode::Down | KeyCode::Tab => palette.next(),
+            KeyCode::Char('p') if ctrl => palette.prev(),
+            KeyCode::Char('n') if ctrl => palette.next(),
+            KeyCode::Backspace => palette.pop_char(),
+            KeyCode::Char(c) if !ctrl => palette.push_char(c),
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// 执行命令面板中选中的条目，操作与快捷键使用同一套分发逻辑
+    async fn run_palette_item(&mut self, mode: KeyMode, item: PaletteItem) -> AppResult<()> {
+        match item {
+            PaletteItem::Action(action) => self.perform_action(mode, action).await?,
+            PaletteItem::Topic(id) => {
+                let title = self.history.topic_title(id).unwrap_or_default().to_string();
+                self.open_topic(id, title).await?;
+            }
+            PaletteItem::Category(id) => {
+                self.close_overlays();
+                self.current_tab = AppTab::Categories;
+                self.load_categories().await?;
+                self.selected_index = self
+                    .categories
+                    .iter()
+                    .position(|category| category.id == id)
+                    .unwrap_or(0);
+            }
+            PaletteItem::User(username) => self.open_user(username).await?,
         }
+        Ok(())
+    }
+    
+    /// 当前主题中是否有输入了内容的搜索
+    fn is_searching(&self) -> bool {
+        let AppTab::Topic(topic_id) = self.current_tab else {
+            return false;
+        };
+        self.search
+            .as_ref()
+            .is_some_and(|search| search.topic_id == topic_id && !search.query.is_empty())
+    }
+    
+    /// 主题内搜索：开始搜索、在匹配之间移动、加载全部页面
+    async fn handle_search_action(&mut self, mode: KeyMode, action: Action) -> AppResult<()> {
+        let topic_id = match self.current_tab {
+            AppTab::Topic(id) => Some(id),
+            _ => None,
+        };
+        let searching = self.is_searching();
         
-        if self.input_mode {
-            match key.code {
-                KeyCode::Enter => {
-                    self.submit_input().await?;
-                    self.input_mode = false;
-                    self.input.clear();
+        match action {
+            Action::SearchNext | Action::SearchPrev if !searching => {
+                let text = match self.keymap.describe(mode, Action::Search) {
+                    Some(key) => format!("没有进行中的搜索，按 {} 开始搜索", key),
+                    None => "没有进行中的搜索".to_string(),
+                };
+                self.set_status(text, false);
+            }
+            Action::SearchNext | Action::SearchPrev => {
+                let search = self.search.as_mut().expect("搜索已开始");
+                let found = if action == Action::SearchNext { search.next() } else { search.prev() };
+                if found.is_some() {
+                    self.show_search_match(true).await?;
+                    self.set_search_status();
                 }
-                KeyCode::Esc => {
-                    self.input_mode = false;
-                    self.input.clear();
+            }
+            Action::Search if topic_id.is_some() => {
+                self.start_search();
+                // 再次搜索时保留已加载的页面，不重复加载
+                let loaded = self.search.as_ref().is_some_and(|search| search.page_count() > 1);
+                if self.config.ui.search_all_pages && !loaded && self.search_loader.is_none() {
+                    self.load_all_search_pages();
                 }
-                KeyCode::Backspace => {
-                    self.input.pop();
+            }
+            Action::SearchAllPages if topic_id.is_some() => {
+                if self.search.as_ref().is_none_or(|search| Some(search.topic_id) != topic_id) {
+                    self.start_search();
                 }
-                KeyCode::Char(c) => {
-                    self.input.push(c);
+                self.load_all_search_pages();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// 开始输入搜索内容，同一主题中再次搜索时保留已加载的页面
+    fn start_search(&mut self) {
+        let AppTab::Topic(topic_id) = self.current_tab else {
+            return;
+        };
+        match self.search.as_mut() {
+            Some(search) if search.topic_id == topic_id => search.restart(self.page, self.selected_index),
+            _ => {
+                let mut search = TopicSearch::new(topic_id, self.page, self.selected_index);
+                search.set_revealed_spoilers(&self.revealed_spoilers);
+                if let Some(posts) = self.posts.get(&topic_id) {
+                    search.set_page(self.page, posts);
                 }
-                _ => {}
+                self.search = Some(search);
+            }
+        }
+    }
+    
+    /// 输入搜索内容时跳到第一处匹配，Enter 完成输入，Esc 取消搜索
+    async fn handle_search_key(&mut self, key: KeyEvent) -> AppResult<()> {
+        let Some(search) = self.search.as_mut() else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.clear_search();
             }
+            KeyCode::Enter => {
+                search.editing = false;
+                if search.query.is_empty() {
+                    self.clear_search();
+                } else {
+                    self.show_search_match(true).await?;
+                    self.set_search_status();
+                }
+            }
+            KeyCode::Backspace => {
+                search.pop_char();
+                self.show_search_match(false).await?;
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                search.push_char(c);
+                self.show_search_match(false).await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// 选中当前匹配所在的帖子，`change_page` 为 false 时不切换到其他页
+    async fn show_search_match(&mut self, change_page: bool) -> AppResult<()> {
+        let Some(search) = self.search.as_ref() else {
+            return Ok(());
+        };
+        let topic_id = search.topic_id;
+        let Some(found) = search.current_match().cloned() else {
+            return Ok(());
+        };
+        if self.current_tab != AppTab::Topic(topic_id) {
             return Ok(());
         }
         
-        match key.code {
-            KeyCode::Char('q') => {
-                self.should_quit = true;
+        if found.page != self.page {
+            if !change_page {
+                return Ok(());
             }
-            KeyCode::Char('?') => {
-                self.show_help = !self.show_help;
+            self.page = found.page;
+            self.load_topic_posts(topic_id).await?;
+        }
+        let index = self
+            .posts
+            .get(&topic_id)
+            .and_then(|posts| posts.iter().position(|post| post.id == found.post_id));
+        if let Some(index) = index {
+            self.reveal_post(index);
+            if index != self.selected_index {
+                self.selected_index = index;
+                self.post_scroll = 0;
+                self.selected_image_button = None;
+            }
+        }
+        if let Some(search) = self.search.as_ref() {
+            search.reveal.set(true);
+        }
+        Ok(())
+    }
+    
+    /// 在状态栏显示当前是第几处匹配
+    fn set_search_status(&mut self) {
+        let Some(search) = self.search.as_ref() else {
+            return;
+        };
+        match search.current {
+            Some(index) => {
+                let text = format!("\"{}\": 第 {}/{} 处匹配", search.query, index + 1, search.matches.len());
+                self.set_status(text, false);
+            }
+            None => {
+                let text = format!("未找到 \"{}\"（已搜索 {} 页）", search.query, search.page_count());
+                self.set_status(text, true);
+            }
+        }
+    }
+    
+    /// 在后台依次加载主题的全部页面加入搜索范围，加载进度在 tick 中接收
+    fn load_all_search_pages(&mut self) {
+        let Some(topic_id) = self.search.as_ref().map(|search| search.topic_id) else {
+            return;
+        };
+        if let Some(loader) = self.search_loader.take() {
+            loader.abort();
+        }
+        
+        let client = Arc::clone(&self.client);
+        let filter = self.post_filter.clone();
+        let current = (self.page, self.posts.get(&topic_id).cloned().unwrap_or_default());
+        let sender = self.search_pages_tx.clone();
+        self.search_loader = Some(tokio::spawn(async move {
+            fetch_search_pages(client, topic_id, filter, current, sender).await;
+        }));
+        self.set_status("正在后台加载全部页面…", false);
+    }
+    
+    /// 把后台加载的页面加入搜索，并在状态栏显示进度
+    fn receive_search_pages(&mut self) {
+        while let Ok(event) = self.search_pages_rx.try_recv() {
+            match event {
+                SearchPageEvent::Page { topic_id, page, posts } => {
+                    let Some(search) = self.search.as_mut().filter(|search| search.topic_id == topic_id) else {
+                        continue;
+                    };
+                    search.set_page(page, &posts);
+                    let text = format!("正在加载全部页面：已加载 {} 页，{} 处匹配", search.page_count(), search.matches.len());
+                    self.set_status(text, false);
+                }
+                SearchPageEvent::Finished { topic_id, error } => {
+                    let Some(search) = self.search.as_ref().filter(|search| search.topic_id == topic_id) else {
+                        continue;
+                    };
+                    self.search_loader = None;
+                    match error {
+                        Some(error) => self.set_status(error, true),
+                        None => {
+                            let text = format!("已加载 {} 页，共 {} 处匹配", search.page_count(), search.matches.len());
+                            self.set_status(text, false);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    /// 结束搜索，停止后台加载
+    fn clear_search(&mut self) {
+        self.search = None;
+        if let Some(loader) = self.search_loader.take() {
+            loader.abort();
+        }
+    }
+    
+    /// 采用绘制时为显示当前匹配计算出的滚动位置
+    fn adopt_search_scroll(&mut self) {
+        if let Some(scroll) = self.search.as_ref().and_then(|search| search.revealed_scroll.take()) {
+            self.post_scroll = scroll;
+        }
+    }
+    
+    /// 关闭完整帖子、图片和画廊，回到列表界面
+    fn close_overlays(&mut self) {
+        self.viewing_full_post = false;
+        self.post_scroll = 0;
+        self.gallery = None;
+        self.showing_image = false;
+        self.current_image = None;
+        self.selected_image_button = None;
+        self.image_view.reset();
+    }
+    
+    /// 打开主题并记录到最近访问
+    async fn open_topic(&mut self, topic_id: u64, title: String) -> AppResult<()> {
+        self.close_overlays();
+        self.clear_search();
+        self.post_replies.clear();
+        self.folded_threads.clear();
+        self.revealed_spoilers.clear();
+        self.expanded_details.clear();
+        self.post_filter = PostFilter::default();
+        self.history.visit_topic(topic_id, &title);
+        self.current_tab = AppTab::Topic(topic_id);
+        self.selected_index = 0;
+        self.page = 1;
+        self.load_topic_details(topic_id).await;
+        self.load_topic_posts(topic_id).await
+    }
+    
+    /// 加载主题详情，失败时帖子仍可浏览，只是没有主题信息
+    async fn load_topic_details(&mut self, topic_id: u64) {
+        match self.client.get_topic(topic_id).await {
+            Ok(details) => {
+                if let Some(level) = details.details.notification_level {
+                    self.topic_levels.insert(topic_id, level);
+                }
+                self.topic_details.insert(topic_id, details);
+            }
+            Err(e) => {
+                warn!("加载主题详情失败: {}", e);
+            }
+        }
+    }
+    
+    /// 打开用户资料并记录到最近访问
+    async fn open_user(&mut self, username: String) -> AppResult<()> {
+        self.close_overlays();
+        self.history.visit_user(&username);
+        self.current_tab = AppTab::User(username.clone());
+        self.selected_index = 0;
+        self.load_user(&username).await
+    }
+    
+    /// 图片查看器：缩放、平移和外部操作，Back 关闭图片显示
+    async fn handle_image_viewer_action(&mut self, action: Action) {
+        if action == Action::Back {
+            self.showing_image = false;
+            self.current_image = None;
+            self.image_view.reset();
+            return;
+        }
+        
+        if let Some(image) = self.current_image.clone() {
+            if self.handle_image_action(action, &image) {
+                return;
+            }
+        }
+        self.handle_image_view_action(action, true);
+    }
+    
+    /// 完整帖子查看模式
+    fn handle_post_action(&mut self, action: Action) {
+        match action {
+            Action::OpenGallery => {
+                self.open_gallery();
+            }
+            Action::Back => {
+                // 退出完整帖子查看模式
+                self.viewing_full_post = false;
+                self.post_scroll = 0;
+                self.selected_image_button = None;
+            }
+            Action::OpenImage => {
+                if let Some(button_index) = self.selected_image_button {
+                    if let Some(image) = self.available_post_images().get(button_index).cloned() {
+                        self.show_image(image);
+                    }
+                }
+            }
+            Action::Up => {
+                // 向上滚动
+                self.post_scroll = self.post_scroll.saturating_sub(1);
+            }
+            Action::Down => {
+                // 向下滚动
+                self.post_scroll += 1;
+            }
+            Action::First => {
+                self.post_scroll = 0;
             }
-            KeyCode::Char('h') | KeyCode::Left => {
+            Action::NextImage => {
+                // 选择图片
+                self.select_next_image_button();
+            }
+            Action::ToggleDetails => {
+                // 切换视图顶部或其下第一个折叠区域，都在上方时切换最后一个
+                let rows = self.details_rows.borrow().clone();
+                let scroll = self.post_scroll as usize;
+                let section = rows
+                    .iter()
+                    .find(|(_, row)| *row >= scroll)
+                    .or(rows.last())
+                    .map(|(section, _)| *section);
+                match section {
+                    Some(section) => self.toggle_details(section),
+                    None => self.set_status("这个帖子中没有折叠内容", false),
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    /// 列表浏览模式
+    async fn handle_normal_action(&mut self, action: Action) -> AppResult<()> {
+        match action {
+            Action::Back => {
                 self.navigate_back();
             }
-            KeyCode::Char('j') | KeyCode::Down => {
+            Action::Down => {
                 self.navigate_down();
             }
-            KeyCode::Char('k') | KeyCode::Up => {
+            Action::Up => {
                 self.navigate_up();
             }
-            KeyCode::Char('l') | KeyCode::Right => {
+            Action::First => {
+                self.navigate_to(0);
+            }
+            Action::Last => match self.current_tab {
+                AppTab::Topic(_) if self.suggested_topics().is_empty() => {
+                    if let Some(row) = self.topic_rows().last() {
+                        self.navigate_to(row.index);
+                    }
+                }
+                _ => self.navigate_to(self.current_list_len().saturating_sub(1)),
+            },
+            Action::Open => {
                 self.navigate_next().await?;
             }
-            KeyCode::Enter => {
+            Action::Select => {
                 // 如果在设置页面且选择了 "生成 API 密钥" 选项
                 if let AppTab::Settings = self.current_tab {
-                    if self.selected_index == 0 {  // 第一个选项是 "生成 API 密钥"
-                        self.run_api_key_generator().await?;
-                        return Ok(());
+                    match self.selected_index {
+                        0 => self.run_api_key_generator().await?,  // 第一个选项是 "生成 API 密钥"
+                        1 => self.cycle_theme(),
+                        _ => {}
                     }
+                    return Ok(());
                 } else if let AppTab::Topic(_topic_id) = self.current_tab {
+                    // 被忽略的帖子先展开
+                    if let Some(post) = self.selected_post().filter(|post| self.is_post_collapsed(post)) {
+                        self.expanded_posts.insert(post.id);
+                        return Ok(());
+                    }
                     if let Some(posts) = self.posts.get(&self.get_current_topic_id()) {
                         if self.selected_index < posts.len() {
                             // 切换完整帖子查看状态
@@ -277,76 +964,497 @@ impl App {
                             return Ok(());
                         }
                     }
+                    // 帖子之后的推荐主题直接打开
+                    let suggested = self
+                        .suggested_topics()
+                        .get(self.selected_index - self.topic_posts_len())
+                        .map(|(_, topic)| (topic.id, topic.title.clone()));
+                    if let Some((id, title)) = suggested {
+                        self.open_topic(id, title).await?;
+                    }
                 } else {
                     // 默认的导航选择逻辑
                     self.navigate_select().await?;
                 }
             }
-            KeyCode::Char('t') => {
+            Action::ShowTopics => {
                 self.current_tab = AppTab::Topics;
                 self.selected_index = 0;
                 self.load_topics().await?;
             }
-            KeyCode::Char('c') => {
+            Action::ShowCategories => {
                 self.current_tab = AppTab::Categories;
                 self.selected_index = 0;
                 self.load_categories().await?;
             }
-            KeyCode::Char('i') => {
-                // 如果在主题中，首先确保进入完整帖子查看模式
-                if let AppTab::Topic(_topic_id) = self.current_tab {
-                    if let Some(posts) = self.posts.get(&self.get_current_topic_id()) {
-                        if self.selected_index < posts.len() {
-                            let post = &posts[self.selected_index];
-                            let image_urls: Vec<String> = crate::core::image::extract_image_urls(&post.cooked);
-                            
-                            // 创建可用图片映射
-                            let mut available_images = Vec::new();
-                            for (i, url) in image_urls.iter().enumerate() {
-                                if self.image_paths.lock().unwrap().get::<str>(url).is_some() {
-                                    available_images.push((i, url.clone()));
-                                }
-                            }
-                            
-                            // 如果有可用图片
-                            if !available_images.is_empty() {
-                                // 如果还不在完整查看模式，先进入该模式
-                                if !self.viewing_full_post {
-                                    self.viewing_full_post = true;
-                                    self.post_scroll = 0;
-                                    self.selected_image_button = None;
-                                    return Ok(());
-                                }
-                                
-                                // 选择第一个图片或切换到下一个图片
-                                if self.selected_image_button.is_none() {
-                                    self.selected_image_button = Some(0);
-                                } else {
-                                    let next_index = (self.selected_image_button.unwrap() + 1) % available_images.len();
-                                    self.selected_image_button = Some(next_index);
-                                }
-                            }
-                        }
+            // 如果在主题中且帖子有可用图片，先进入完整帖子查看模式
+            Action::ViewImages if !self.available_post_images().is_empty() => {
+                self.viewing_full_post = true;
+                self.post_scroll = 0;
+                self.selected_image_button = None;
+            }
+            Action::OpenGallery => {
+                self.open_gallery();
+            }
+            Action::ShowSettings => {
+                self.current_tab = AppTab::Settings;
+                self.selected_index = 0;
+            }
+            Action::Refresh => {
+                self.refresh_current_view().await?;
+            }
+            Action::NextPage => {
+                self.next_page().await?;
+            }
+            Action::PrevPage => {
+                self.prev_page().await?;
+            }
+            Action::FilterTopics if self.current_tab == AppTab::Topics => {
+                self.topic_filter.editing = true;
+            }
+            Action::IgnoreUser | Action::IgnoreTopic | Action::IgnoreCategory => {
+                if let Some(target) = self.ignore_target(action) {
+                    self.toggle_ignore(target).await;
+                }
+            }
+            Action::NotificationLevel => {
+                self.open_notification_picker();
+            }
+            Action::Reply => {
+                if let AppTab::Topic(id) = self.current_tab {
+                    let can_reply = self.topic_details.get(&id).is_none_or(|details| details.details.can_create_post);
+                    if can_reply {
+                        self.composer = Some(Composer::reply(id));
+                    } else {
+                        self.set_status("没有在这个主题中回复的权限", true);
                     }
                 }
             }
-            KeyCode::Char('s') => {
-                self.current_tab = AppTab::Settings;
+            Action::ToggleTopicHeader if matches!(self.current_tab, AppTab::Topic(_)) => {
+                self.topic_header_expanded = !self.topic_header_expanded;
+            }
+            Action::EditPost => {
+                self.start_edit_post().await;
+            }
+            Action::JumpToParent => {
+                self.jump_to_parent().await?;
+            }
+            Action::ToggleThreadView if matches!(self.current_tab, AppTab::Topic(_)) => {
+                self.thread_view = !self.thread_view;
+                let text = if self.thread_view { "按回复关系树形显示" } else { "按时间顺序平铺显示" };
+                self.set_status(text, false);
+            }
+            Action::ToggleSummary | Action::FilterByUser => {
+                self.toggle_post_filter(action).await?;
+            }
+            Action::ToggleFold | Action::NextSibling | Action::PrevSibling | Action::FirstChild => {
+                self.navigate_thread(action);
+            }
+            Action::ToggleReplies => {
+                self.toggle_replies().await;
+            }
+            Action::QuotePost => {
+                self.quote_post().await;
+            }
+            Action::DeletePost | Action::RecoverPost => {
+                self.delete_or_recover_post(action == Action::DeletePost).await;
+            }
+            Action::CycleSort if self.current_tab == AppTab::Topics => {
+                self.topic_sort = self.topic_sort.next();
                 self.selected_index = 0;
+                self.set_status(format!("排序: {}", self.topic_sort.title()), false);
+            }
+            _ => {}
+        }
+        
+        Ok(())
+    }
+    
+    /// 打开选中帖子最近一次修改的记录
+    async fn open_revisions(&mut self) {
+        let Some(post) = self.selected_post().filter(|_| matches!(self.current_tab, AppTab::Topic(_))) else {
+            return;
+        };
+        if post.version <= 1 {
+            self.set_status("这个帖子没有修改过", false);
+            return;
+        }
+        let (post_id, post_number, version) = (post.id, post.post_number, post.version);
+        self.load_revision(post_id, post_number, version).await;
+    }
+    
+    /// 加载某次修改并显示，失败时保留当前显示的修改
+    async fn load_revision(&mut self, post_id: u64, post_number: u64, number: u64) {
+        let side_by_side = self
+            .revisions
+            .as_ref()
+            .map_or(self.config.ui.revisions_side_by_side, |view| view.side_by_side);
+        match self.client.get_post_revision(post_id, number).await {
+            Ok(revision) => {
+                self.revisions = Some(RevisionView::new(post_number, revision, side_by_side));
+                self.need_redraw = true;
+            }
+            Err(e) => self.set_status(format!("获取修改记录失败: {}", e), true),
+        }
+    }
+    
+    /// 修改历史：h/l 切换上一次/下一次修改，Tab 切换对比方式，j/k 滚动，Esc 关闭
+    async fn handle_revisions_key(&mut self, key: KeyEvent) -> AppResult<()> {
+        let Some(view) = self.revisions.as_mut() else {
+            return Ok(());
+        };
+        let revision = &view.revision;
+        let target = match key.code {
+            KeyCode::Left | KeyCode::Char('h') => revision.previous_revision,
+            KeyCode::Right | KeyCode::Char('l') => revision.next_revision,
+            _ => None,
+        };
+        if let Some(number) = target {
+            let (post_id, post_number) = (revision.post_id, view.post_number);
+            self.load_revision(post_id, post_number, number).await;
+            return Ok(());
+        }
+        
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('v') => {
+                self.revisions = None;
+                self.need_redraw = true;
+            }
+            KeyCode::Tab | KeyCode::Char('s') => {
+                view.side_by_side = !view.side_by_side;
+                view.scroll = 0;
+            }
+            KeyCode::Down | KeyCode::Char('j') => view.scroll = view.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => view.scroll = view.scroll.saturating_sub(1),
+            KeyCode::PageDown | KeyCode::Char(' ') => view.scroll = view.scroll.saturating_add(10),
+            KeyCode::PageUp => view.scroll = view.scroll.saturating_sub(10),
+            KeyCode::Home | KeyCode::Char('g') => view.scroll = 0,
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// 显示或隐藏选中帖子中的剧透
+    fn toggle_spoilers(&mut self) {
+        let Some(post) = self.selected_post().filter(|_| matches!(self.current_tab, AppTab::Topic(_))) else {
+            return;
+        };
+        let post_id = post.id;
+        if !self.revealed_spoilers.remove(&post_id) {
+            self.revealed_spoilers.insert(post_id);
+        }
+        if let Some(search) = self.search.as_mut() {
+            search.set_revealed_spoilers(&self.revealed_spoilers);
+        }
+    }
+    
+    /// 展开或收起选中帖子中的一个 <details>
+    fn toggle_details(&mut self, section: usize) {
+        let Some(post_id) = self.selected_post().map(|post| post.id) else {
+            return;
+        };
+        if !self.expanded_details.remove(&(post_id, section)) {
+            self.expanded_details.insert((post_id, section));
+        }
+    }
+    
+    /// 打开选中帖子中的第 `index` 个投票
+    fn open_poll_voter(&mut self, index: usize) {
+        let Some(post) = self.selected_post().filter(|_| matches!(self.current_tab, AppTab::Topic(_))) else {
+            return;
+        };
+        match PollVoter::new(post, index) {
+            Some(voter) => {
+                self.poll_voter = Some(voter);
+                self.need_redraw = true;
+            }
+            None => self.set_status("这个帖子中没有投票", false),
+        }
+    }
+    
+    /// 投票窗口：j/k 选择，空格选择/取消选项，Enter 投票，x 撤回投票，Tab 切换投票，Esc 关闭
+    async fn handle_poll_key(&mut self, key: KeyEvent) -> AppResult<()> {
+        let Some(voter) = self.poll_voter.as_mut() else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('P') => {
+                self.poll_voter = None;
+                self.need_redraw = true;
+            }
+            KeyCode::Down | KeyCode::Char('j') => voter.next(),
+            KeyCode::Up | KeyCode::Char('k') => voter.prev(),
+            KeyCode::Char(' ') => {
+                if let Err(message) = voter.toggle() {
+                    self.set_status(message, true);
+                }
+            }
+            KeyCode::Tab if voter.poll_count > 1 => {
+                let index = (voter.poll_index + 1) % voter.poll_count;
+                self.open_poll_voter(index);
+            }
+            KeyCode::Enter => self.submit_poll_vote(false).await,
+            KeyCode::Char('x') | KeyCode::Backspace => self.submit_poll_vote(true).await,
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// 提交或撤回投票，成功后同时更新帖子中的投票结果
+    async fn submit_poll_vote(&mut self, unvote: bool) {
+        let Some(voter) = self.poll_voter.as_ref() else {
+            return;
+        };
+        let check = if unvote && !voter.has_voted() {
+            Err("还没有投票".to_string())
+        } else if unvote {
+            voter.check_open()
+        } else {
+            voter.check_vote()
+        };
+        if let Err(message) = check {
+            self.set_status(message, true);
+            return;
+        }
+        
+        let (post_id, name, chosen) = (voter.post_id, voter.poll.name.clone(), voter.chosen.clone());
+        let result = if unvote {
+            self.client.unvote_poll(post_id, &name).await
+        } else {
+            self.client.vote_poll(post_id, &name, &chosen).await
+        };
+        let poll = match result {
+            Ok(poll) => poll,
+            Err(e) => {
+                self.set_status(format!("{}失败: {}", if unvote { "撤回投票" } else { "投票" }, e), true);
+                return;
+            }
+        };
+        
+        let voted = if unvote { Vec::new() } else { chosen };
+        let post = self
+            .posts
+            .get_mut(&self.get_current_topic_id())
+            .and_then(|posts| posts.iter_mut().find(|post| post.id == post_id));
+        if let Some(post) = post {
+            if let Some(existing) = post.polls.iter_mut().find(|existing| existing.name == name) {
+                *existing = poll.clone();
+            }
+            if voted.is_empty() {
+                post.polls_votes.remove(&name);
+            } else {
+                post.polls_votes.insert(name.clone(), voted.clone());
+            }
+        }
+        if let Some(voter) = self.poll_voter.as_mut() {
+            voter.update(poll, voted);
+        }
+        self.set_status(if unvote { "已撤回投票" } else { "已投票" }, false);
+    }
+    
+    /// 为选中的主题或分类打开通知级别选择器
+    fn open_notification_picker(&mut self) {
+        let picker = if self.current_tab == AppTab::Categories {
+            self.categories.get(self.selected_index).map(|category| {
+                NotificationPicker::new(
+                    NotificationTarget::Category(category.id),
+                    category.name.clone(),
+                    category.notification_level,
+                )
+            })
+        } else {
+            let id = match self.current_tab {
+                AppTab::Topic(id) => Some(id),
+                _ => self.selected_topic().map(|topic| topic.id),
+            };
+            id.map(|id| {
+                let name = self
+                    .topics
+                    .iter()
+                    .find(|topic| topic.id == id)
+                    .map(|topic| topic.title.clone())
+                    .or_else(|| self.history.topic_title(id).map(str::to_string))
+                    .unwrap_or_else(|| format!("主题 #{}", id));
+                NotificationPicker::new(NotificationTarget::Topic(id), name, self.topic_levels.get(&id).copied())
+            })
+        };
+        self.notification_picker = picker;
+    }
+    
+    /// 通知级别选择器：j/k 选择，Enter 确定，Esc 关闭
+    async fn handle_notification_picker_key(&mut self, key: KeyEvent) -> AppResult<()> {
+        let Some(picker) = self.notification_picker.as_mut() else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.notification_picker = None;
+            }
+            KeyCode::Up | KeyCode::Char('k') => picker.prev(),
+            KeyCode::Down | KeyCode::Char('j') => picker.next(),
+            KeyCode::Enter => {
+                let picker = self.notification_picker.take().expect("选择器已打开");
+                self.set_notification_level(&picker).await;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// 在服务器上设置通知级别，成功后更新本地记录
+    async fn set_notification_level(&mut self, picker: &NotificationPicker) {
+        let level = picker.selected_level();
+        let result = match picker.target {
+            NotificationTarget::Topic(id) => self.client.set_topic_notification_level(id, level).await,
+            NotificationTarget::Category(id) => self.client.set_category_notification_level(id, level).await,
+        };
+        if let Err(e) = result {
+            self.set_status(format!("设置通知级别失败: {}", e), true);
+            return;
+        }
+        
+        match picker.target {
+            NotificationTarget::Topic(id) => {
+                self.topic_levels.insert(id, level);
+                for topic in self.topics.iter_mut().filter(|topic| topic.id == id) {
+                    topic.notification_level = Some(level);
+                }
+            }
+            NotificationTarget::Category(id) => {
+                for category in self.categories.iter_mut().filter(|category| category.id == id) {
+                    category.notification_level = Some(level);
+                }
+            }
+        }
+        self.set_status(format!("已将 \"{}\" 的通知级别设为{}", picker.name, level.title()), false);
+    }
+    
+    /// 输入主题筛选条件，Enter 完成输入，Esc 清除筛选
+    fn handle_filter_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.topic_filter = TopicFilter::default();
+            }
+            KeyCode::Enter => {
+                self.topic_filter.editing = false;
+            }
+            KeyCode::Backspace => {
+                self.topic_filter.query.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.topic_filter.query.push(c);
+            }
+            _ => return,
+        }
+        self.selected_index = 0;
+    }
+    
+    /// 经过筛选和排序后显示在主题列表中的主题
+    pub fn visible_topics(&self) -> Vec<&Topic> {
+        visible_topics(&self.topics, &self.categories, &self.ignore_rules, &self.topic_filter, self.topic_sort)
+    }
+    
+    /// 当前选中的主题：主题列表中选中的主题，或正在查看的主题
+    fn selected_topic(&self) -> Option<&Topic> {
+        match self.current_tab {
+            AppTab::Topics => self.visible_topics().get(self.selected_index).copied(),
+            AppTab::Topic(id) => self.topics.iter().find(|topic| topic.id == id),
+            _ => None,
+        }
+    }
+    
+    /// 当前选中的帖子
+    fn selected_post(&self) -> Option<&Post> {
+        self.posts.get(&self.get_current_topic_id())?.get(self.selected_index)
+    }
+    
+    /// 帖子是否被忽略且没有展开
+    pub fn is_post_collapsed(&self, post: &Post) -> bool {
+        self.ignore_rules.ignores_post(post) && !self.expanded_posts.contains(&post.id)
+    }
+    
+    /// 根据当前界面确定要忽略的用户、主题或分类
+    fn ignore_target(&self, action: Action) -> Option<IgnoreTarget> {
+        match (action, &self.current_tab) {
+            (Action::IgnoreUser, AppTab::User(username)) => Some(IgnoreTarget::User(username.clone())),
+            (Action::IgnoreUser, AppTab::Topic(_)) => {
+                self.selected_post().map(|post| IgnoreTarget::User(post.username.clone()))
             }
-            KeyCode::Char('r') => {
-                self.refresh_current_view().await?;
+            // 主题列表中忽略主题的发起人
+            (Action::IgnoreUser, AppTab::Topics) => self
+                .selected_topic()
+                .and_then(|topic| topic.posters.first())
+                .and_then(|poster| poster.username.clone())
+                .map(IgnoreTarget::User),
+            (Action::IgnoreTopic, AppTab::Topic(id)) => Some(IgnoreTarget::Topic(*id)),
+            (Action::IgnoreTopic, AppTab::Topics) => {
+                self.selected_topic().map(|topic| IgnoreTarget::Topic(topic.id))
             }
-            KeyCode::Char('n') => {
-                self.next_page().await?;
+            (Action::IgnoreCategory, AppTab::Categories) => {
+                self.categories.get(self.selected_index).map(|category| IgnoreTarget::Category(category.id))
             }
-            KeyCode::Char('p') => {
-                self.prev_page().await?;
+            // 从最近访问打开的主题可能不在主题列表中，此时使用主题详情中的分类
+            (Action::IgnoreCategory, AppTab::Topic(id)) => self
+                .selected_topic()
+                .and_then(|topic| topic.category_id)
+                .or_else(|| self.topic_details.get(id).and_then(|details| details.category_id))
+                .map(IgnoreTarget::Category),
+            (Action::IgnoreCategory, AppTab::Topics) => self
+                .selected_topic()
+                .and_then(|topic| topic.category_id)
+                .map(IgnoreTarget::Category),
+            _ => None,
+        }
+    }
+    
+    /// 添加或移除忽略项并保存配置，开启同步时同时在服务器上静音或取消静音
+    async fn toggle_ignore(&mut self, target: IgnoreTarget) {
+        let ignored = self.config.ignore.toggle(&target);
+        match IgnoreRules::new(&self.config.ignore) {
+            Ok(rules) => self.ignore_rules = rules,
+            Err(e) => warn!("忽略规则无效: {}", e),
+        }
+        
+        let name = match &target {
+            IgnoreTarget::User(username) => format!("用户 {}", username),
+            IgnoreTarget::Topic(id) => match self.topics.iter().find(|topic| topic.id == *id) {
+                Some(topic) => format!("主题 \"{}\"", topic.title),
+                None => match self.topic_details.get(id) {
+                    Some(details) => format!("主题 \"{}\"", details.title),
+                    None => format!("主题 #{}", id),
+                },
+            },
+            IgnoreTarget::Category(id) => match self.categories.iter().find(|category| category.id == *id) {
+                Some(category) => format!("分类 {}", category.name),
+                None => format!("分类 #{}", id),
+            },
+        };
+        let mut message = format!("{}{}", if ignored { "已忽略" } else { "已取消忽略" }, name);
+        let mut is_error = false;
+        
+        if let Err(e) = self.config.save() {
+            message = format!("{}，但无法保存配置: {}", message, e);
+            is_error = true;
+        }
+        if self.config.ignore.sync_server {
+            let level = if ignored { NotificationLevel::Muted } else { NotificationLevel::Regular };
+            let result = match &target {
+                IgnoreTarget::User(username) => self.client.set_user_muted(username, ignored).await,
+                IgnoreTarget::Topic(id) => self.client.set_topic_notification_level(*id, level).await,
+                IgnoreTarget::Category(id) => self.client.set_category_notification_level(*id, level).await,
+            };
+            if let Err(e) = result {
+                message = format!("{}，但同步到服务器失败: {}", message, e);
+                is_error = true;
             }
-            _ => {}
         }
+        self.set_status(message, is_error);
         
-        Ok(())
+        // 被忽略的主题从列表中消失后，保持选中位置有效
+        let len = self.current_list_len();
+        if self.selected_index >= len {
+            self.selected_index = len.saturating_sub(1);
+        }
     }
     
     pub fn should_quit(&self) -> bool {
@@ -362,8 +1470,9 @@ impl App {
                 // 重置图片状态
                 self.selected_image_button = None;
                 self.showing_image = false;
-                self.current_image_url = None;
+                self.current_image = None;
                 self.viewing_full_post = false; // 重置完整帖子查看状态
+                self.gallery = None;
             }
             AppTab::User(_) => {
                 self.current_tab = AppTab::Home;
@@ -387,56 +1496,501 @@ impl App {
     
     fn navigate_down(&mut self) {
         match self.current_tab {
-            AppTab::Home => {
-                if self.selected_index < 2 {
-                    self.selected_index += 1;
-                }
+            AppTab::Home if self.selected_index < 2 => {
+                self.selected_index += 1;
             }
-            AppTab::Topics => {
-                if !self.topics.is_empty() && self.selected_index < self.topics.len() - 1 {
-                    self.selected_index += 1;
-                }
+            AppTab::Topics if self.selected_index + 1 < self.visible_topics().len() => {
+                self.selected_index += 1;
             }
-            AppTab::Categories => {
-                if !self.categories.is_empty() && self.selected_index < self.categories.len() - 1 {
-                    self.selected_index += 1;
-                }
+            AppTab::Categories if self.selected_index + 1 < self.categories.len() => {
+                self.selected_index += 1;
+            }
+            // 最后一个帖子之后进入推荐主题
+            AppTab::Topic(_) if self.selected_index >= self.topic_posts_len() => {
+                self.navigate_to(self.selected_index + 1);
             }
             AppTab::Topic(_) => {
-                if let Some(posts) = self.posts.get(&self.get_current_topic_id()) {
-                    if !posts.is_empty() && self.selected_index < posts.len() - 1 {
-                        self.selected_index += 1;
-                        // 切换帖子时重置图片按钮状态
-                        self.selected_image_button = None;
+                let rows = self.topic_rows();
+                let next = match rows.iter().position(|row| row.index == self.selected_index) {
+                    Some(position) => rows.get(position + 1).map_or(self.topic_posts_len(), |row| row.index),
+                    None => rows.first().map_or(0, |row| row.index),
+                };
+                self.navigate_to(next);
+            }
+            AppTab::Settings if self.selected_index + 1 < SETTINGS_OPTIONS => {
+                self.selected_index += 1;
+            }
+            _ => {}
+        }
+    }
+    
+    /// 当前列表的项数
+    fn current_list_len(&self) -> usize {
+        match self.current_tab {
+            AppTab::Home => 3,
+            AppTab::Topics => self.visible_topics().len(),
+            AppTab::Categories => self.categories.len(),
+            AppTab::Topic(_) => self.topic_posts_len() + self.suggested_topics().len(),
+            AppTab::Settings => SETTINGS_OPTIONS,
+            _ => 0,
+        }
+    }
+    
+    fn topic_posts_len(&self) -> usize {
+        self.posts.get(&self.get_current_topic_id()).map_or(0, Vec::len)
+    }
+    
+    /// 在最后一页的帖子之后显示的推荐主题和相关主题，附带分组名称
+    ///
+    /// 选中下标从帖子数开始依次对应这些主题。
+    pub fn suggested_topics(&self) -> Vec<(&'static str, &SuggestedTopic)> {
+        let id = self.get_current_topic_id();
+        let Some(details) = self.topic_details.get(&id) else {
+            return Vec::new();
+        };
+        // 筛选后的页数未知，按当前页是否填满判断
+        let last_page = if self.post_filter.is_active() {
+            (self.topic_posts_len() as u64) < details.chunk_size
+        } else {
+            self.page as u64 >= details.page_count()
+        };
+        if !last_page {
+            return Vec::new();
+        }
+        
+        let mut seen = HashSet::from([id]);
+        let suggested = details.suggested_topics.iter().map(|topic| ("推荐主题", topic));
+        let related = details.related_topics.iter().map(|topic| ("相关主题", topic));
+        suggested
+            .chain(related)
+            .filter(|(_, topic)| seen.insert(topic.id) && !self.ignore_rules.ignores_suggested(topic))
+            .collect()
+    }
+    
+    /// 当前主题的帖子列表中显示的行，树形显示时按回复关系排列
+    pub fn topic_rows(&self) -> Vec<ThreadRow> {
+        let Some((posts, tree)) = self.current_thread() else {
+            return Vec::new();
+        };
+        if self.thread_view {
+            tree.rows(posts, &self.folded_threads)
+        } else {
+            (0..posts.len()).map(ThreadRow::flat).collect()
+        }
+    }
+    
+    /// 在回复树中移动或折叠选中帖子的回复，平铺显示时同样可以按回复关系移动
+    fn navigate_thread(&mut self, action: Action) {
+        let Some((posts, tree)) = self.current_thread() else {
+            return;
+        };
+        let selected = self.selected_index;
+        if selected >= posts.len() {
+            return;
+        }
+        
+        let siblings = tree.siblings(selected);
+        let position = siblings.iter().position(|&index| index == selected).unwrap_or(0);
+        let target = match action {
+            Action::ToggleFold => {
+                if tree.children(selected).is_empty() {
+                    self.set_status("这个帖子没有回复", false);
+                } else {
+                    let id = posts[selected].id;
+                    if !self.folded_threads.remove(&id) {
+                        self.folded_threads.insert(id);
                     }
                 }
+                return;
             }
-            AppTab::Settings => {
-                // 设置页暂时没有内容
+            Action::NextSibling => siblings.get(position + 1).copied(),
+            Action::PrevSibling => position.checked_sub(1).map(|position| siblings[position]),
+            Action::FirstChild => tree.children(selected).first().copied(),
+            _ => None,
+        };
+        match target {
+            Some(index) => {
+                self.reveal_post(index);
+                self.navigate_to(index);
             }
-            _ => {}
+            None => {
+                let text = if action == Action::FirstChild { "这个帖子没有回复" } else { "没有更多同级帖子" };
+                self.set_status(text, false);
+            }
+        }
+    }
+    
+    /// 展开包含某个帖子的折叠回复，使其在树形显示中可见
+    fn reveal_post(&mut self, index: usize) {
+        let Some((posts, tree)) = self.current_thread() else {
+            return;
+        };
+        let ancestors: Vec<u64> = tree.ancestors(index).map(|ancestor| posts[ancestor].id).collect();
+        for id in ancestors {
+            self.folded_threads.remove(&id);
+        }
+    }
+    
+    /// 当前主题已加载的帖子及其回复树
+    fn current_thread(&self) -> Option<(&[Post], &ThreadTree)> {
+        let topic_id = self.get_current_topic_id();
+        Some((self.posts.get(&topic_id)?, self.thread_trees.get(&topic_id)?))
+    }
+    
+    /// 直接选中列表中的某一项
+    fn navigate_to(&mut self, index: usize) {
+        if index < self.current_list_len() && index != self.selected_index {
+            self.selected_index = index;
+            // 切换帖子时重置图片按钮状态
+            self.selected_image_button = None;
         }
     }
     
     fn navigate_up(&mut self) {
         match self.current_tab {
-            AppTab::Home | AppTab::Topics | AppTab::Categories => {
-                if self.selected_index > 0 {
-                    self.selected_index -= 1;
+            AppTab::Home | AppTab::Topics | AppTab::Categories | AppTab::Settings if self.selected_index > 0 => {
+                self.selected_index -= 1;
+            }
+            AppTab::Topic(_) if self.selected_index > self.topic_posts_len() => {
+                self.selected_index -= 1;
+            }
+            // 从第一个推荐主题回到最后一个帖子
+            AppTab::Topic(_) if self.selected_index == self.topic_posts_len() => {
+                if let Some(row) = self.topic_rows().last() {
+                    self.navigate_to(row.index);
                 }
             }
             AppTab::Topic(_) => {
-                if self.selected_index > 0 {
-                    self.selected_index -= 1;
-                    // 切换帖子时重置图片按钮状态
-                    self.selected_image_button = None;
+                let rows = self.topic_rows();
+                let previous = rows
+                    .iter()
+                    .position(|row| row.index == self.selected_index)
+                    .and_then(|position| position.checked_sub(1))
+                    .and_then(|position| rows.get(position));
+                if let Some(row) = previous {
+                    self.navigate_to(row.index);
                 }
             }
-            AppTab::Settings => {
-                // 设置页暂时没有内容
+            _ => {}
+        }
+    }
+    
+    /// 当前选中帖子中已下载、可以查看的图片，顺序与图片按钮一致
+    fn available_post_images(&self) -> Vec<PostImage> {
+        let Some(post) = self
+            .posts
+            .get(&self.get_current_topic_id())
+            .and_then(|posts| posts.get(self.selected_index))
+        else {
+            return Vec::new();
+        };
+        
+        let image_paths = self.image_paths.lock().unwrap();
+        extract_images(&post.cooked)
+            .into_iter()
+            .filter(|image| image_paths.contains_key(&image.url))
+            .collect()
+    }
+    
+    /// 选择第一个图片按钮，或切换到下一个图片按钮
+    fn select_next_image_button(&mut self) {
+        let count = self.available_post_images().len();
+        if count == 0 {
+            return;
+        }
+        self.selected_image_button = Some(match self.selected_image_button {
+            Some(index) => (index + 1) % count,
+            None => 0,
+        });
+    }
+    
+    /// 在查看器中显示图片，并按需下载原图
+    fn show_image(&mut self, image: PostImage) {
+        self.fetch_original_image(&image);
+        self.showing_image = true;
+        self.current_image = Some(image);
+        self.image_view.reset();
+    }
+    
+    /// 处理图片查看器的缩放和平移操作，返回操作是否被处理
+    fn handle_image_view_action(&mut self, action: Action, allow_pan: bool) -> bool {
+        let view = &mut self.image_view;
+        match action {
+            Action::ZoomIn => view.zoom_in(),
+            Action::ZoomOut => view.zoom_out(),
+            Action::ToggleActualSize => view.toggle_actual_size(),
+            Action::ResetZoom => view.reset(),
+            Action::PanLeft if allow_pan => view.pan(-1.0, 0.0),
+            Action::PanRight if allow_pan => view.pan(1.0, 0.0),
+            Action::PanUp if allow_pan => view.pan(0.0, -1.0),
+            Action::PanDown if allow_pan => view.pan(0.0, 1.0),
+            _ => return false,
+        }
+        true
+    }
+    
+    /// 后台下载灯箱原图，缩略图已预先下载
+    fn fetch_original_image(&self, image: &PostImage) {
+        let Some(original_url) = image.original_url.clone() else {
+            return;
+        };
+        if self.image_paths.lock().unwrap().contains_key(&original_url) {
+            return;
+        }
+        
+        let image_cache = self.image_cache.clone();
+        let image_paths = Arc::clone(&self.image_paths);
+        let base_url = self.config.discourse.url.clone();
+        tokio::spawn(async move {
+            fetch_image(&image_cache, &image_paths, &base_url, &original_url).await;
+        });
+    }
+    
+    /// 处理查看器中对图片的外部操作（保存、复制链接、外部打开），返回操作是否被处理
+    fn handle_image_action(&mut self, action: Action, image: &PostImage) -> bool {
+        match action {
+            Action::SaveImage | Action::OpenExternal => self.request_image_action(action, image),
+            Action::CopyImageUrl => self.copy_image_url(image),
+            _ => return false,
+        }
+        true
+    }
+    
+    /// 原图已下载时立即保存或打开，否则在后台下载，下载结束后在 tick 中执行
+    fn request_image_action(&mut self, action: Action, image: &PostImage) {
+        let full_url = image.full_url().to_string();
+        if self.image_paths.lock().unwrap().contains_key(&full_url) {
+            self.run_image_action(action, image);
+            return;
+        }
+        
+        let already_fetching = self.pending_image_actions.iter().any(|(_, pending)| pending.full_url() == full_url);
+        self.pending_image_actions.push((action, image.clone()));
+        self.set_status("正在下载原图，完成后继续…", false);
+        if already_fetching {
+            return;
+        }
+        
+        let image_cache = self.image_cache.clone();
+        let image_paths = Arc::clone(&self.image_paths);
+        let base_url = self.config.discourse.url.clone();
+        let downloaded = self.downloaded_images_tx.clone();
+        tokio::spawn(async move {
+            fetch_image(&image_cache, &image_paths, &base_url, &full_url).await;
+            let _ = downloaded.send(full_url);
+        });
+    }
+    
+    /// 执行下载已结束的图片上等待中的操作
+    fn finish_image_actions(&mut self) {
+        while let Ok(url) = self.downloaded_images_rx.try_recv() {
+            let (ready, pending) = std::mem::take(&mut self.pending_image_actions)
+                .into_iter()
+                .partition(|(_, image)| image.full_url() == url);
+            self.pending_image_actions = pending;
+            for (action, image) in ready {
+                self.run_image_action(action, &image);
+            }
+        }
+    }
+    
+    fn run_image_action(&mut self, action: Action, image: &PostImage) {
+        match action {
+            Action::SaveImage => self.save_image(image),
+            Action::OpenExternal => self.open_image_externally(image),
+            _ => {}
+        }
+    }
+    
+    /// 图片的本地文件，优先使用原图，原图下载失败时使用缩略图
+    fn local_image_file(&self, image: &PostImage) -> Option<PathBuf> {
+        let image_paths = self.image_paths.lock().unwrap();
+        image_paths.get(image.full_url()).or_else(|| image_paths.get(&image.url)).cloned()
+    }
+    
+    fn save_image(&mut self, image: &PostImage) {
+        let Some(source) = self.local_image_file(image) else {
+            self.set_status("图片下载失败，无法保存", true);
+            return;
+        };
+        
+        let directory = self.config.images.download_dir();
+        match external::save_to_directory(&source, &directory, &image.download_filename()) {
+            Ok(path) => self.set_status(format!("图片已保存到 {}", path.display()), false),
+            Err(e) => self.set_status(format!("保存图片失败: {}", e), true),
+        }
+    }
+    
+    fn copy_image_url(&mut self, image: &PostImage) {
+        let url = absolute_url(&self.config.discourse.url, image.full_url());
+        match external::copy_to_clipboard(&url) {
+            Ok(()) => self.set_status(format!("已复制链接: {}", url), false),
+            Err(e) => self.set_status(format!("复制链接失败: {}", e), true),
+        }
+    }
+    
+    fn open_image_externally(&mut self, image: &PostImage) {
+        let Some(path) = self.local_image_file(image) else {
+            self.set_status("图片下载失败，无法打开", true);
+            return;
+        };
+        
+        match external::open_external(&path, self.config.images.open_command.as_deref()) {
+            Ok(()) => self.set_status("已使用外部程序打开图片", false),
+            Err(e) => self.set_status(format!("打开图片失败: {}", e), true),
+        }
+    }
+    
+    /// 在状态栏显示一条临时消息
+    pub fn set_status(&mut self, text: impl Into<String>, is_error: bool) {
+        self.status_message = Some(StatusMessage {
+            text: text.into(),
+            is_error,
+            created_at: Instant::now(),
+        });
+    }
+    
+    /// 后台下载头像，已下载时跳过
+    fn fetch_avatar(&self, avatar_template: &str) {
+        let base_url = self.config.discourse.url.clone();
+        let url = avatar_url(&base_url, avatar_template);
+        if self.image_paths.lock().unwrap().contains_key(&url) {
+            return;
+        }
+        
+        let image_cache = self.image_cache.clone();
+        let image_paths = Arc::clone(&self.image_paths);
+        tokio::spawn(async move {
+            fetch_image(&image_cache, &image_paths, &base_url, &url).await;
+        });
+    }
+    
+    /// 切换到下一个主题并保存到配置文件
+    fn cycle_theme(&mut self) {
+        let names = self.config.theme.theme_names();
+        let current = names.iter().position(|name| *name == self.theme.name).unwrap_or(0);
+        let next = &names[(current + 1) % names.len()];
+        
+        match Theme::load(&self.config.theme, next) {
+            Ok(theme) => {
+                self.theme = theme;
+                self.config.theme.name = next.clone();
+                match self.config.save() {
+                    Ok(()) => self.set_status(format!("已切换主题: {}", next), false),
+                    Err(e) => self.set_status(format!("已切换主题，但无法保存配置: {}", e), true),
+                }
+            }
+            Err(e) => self.set_status(format!("无法切换主题: {}", e), true),
+        }
+    }
+    
+    /// 打开当前主题的图片画廊
+    fn open_gallery(&mut self) {
+        if let Some(posts) = self.posts.get(&self.get_current_topic_id()) {
+            let gallery = Gallery::from_posts(posts);
+            if !gallery.is_empty() {
+                self.gallery = Some(gallery);
+            }
+        }
+    }
+    
+    async fn handle_gallery_action(&mut self, action: Action) {
+        let Some(gallery) = self.gallery.as_ref() else {
+            return;
+        };
+        
+        // 保存、复制链接和外部打开对选中的图片生效
+        if let Some(current) = gallery.current().map(|current| current.image.clone()) {
+            if self.handle_image_action(action, &current) {
+                return;
+            }
+        }
+        
+        let Some(gallery) = self.gallery.as_ref() else {
+            return;
+        };
+        
+        // 大图预览时支持缩放，放大后用于平移，未放大时左右平移切换图片
+        if gallery.previewing {
+            let allow_pan = self.image_view.is_zoomed();
+            if self.handle_image_view_action(action, allow_pan) {
+                return;
+            }
+        }
+        
+        let Some(gallery) = self.gallery.as_mut() else {
+            return;
+        };
+        let previous = gallery.selected;
+        
+        match action {
+            Action::Left | Action::PanLeft => gallery.prev(),
+            Action::Right | Action::PanRight => gallery.next(),
+            Action::Down if !gallery.previewing => gallery.next_row(),
+            Action::Up if !gallery.previewing => gallery.prev_row(),
+            Action::First => gallery.selected = 0,
+            Action::Last => gallery.selected = gallery.images.len().saturating_sub(1),
+            Action::TogglePreview => {
+                gallery.previewing = !gallery.previewing;
+                self.image_view.reset();
+                // 退出大图预览时清除残留的图片
+                self.need_redraw = true;
+            }
+            Action::JumpToPost => {
+                self.jump_to_gallery_post();
+            }
+            Action::Back => {
+                if gallery.previewing {
+                    gallery.previewing = false;
+                    self.image_view.reset();
+                } else {
+                    self.gallery = None;
+                }
+                self.need_redraw = true;
             }
             _ => {}
         }
+        
+        // 切换图片后重置缩放
+        if self.gallery.as_ref().is_some_and(|gallery| gallery.selected != previous) {
+            self.image_view.reset();
+        }
+        
+        // 大图预览时按需下载当前图片的原图
+        let previewed = self
+            .gallery
+            .as_ref()
+            .filter(|gallery| gallery.previewing)
+            .and_then(|gallery| gallery.current())
+            .map(|current| current.image.clone());
+        if let Some(image) = previewed {
+            self.fetch_original_image(&image);
+        }
+    }
+    
+    /// 关闭画廊并跳转到当前图片所在的帖子
+    fn jump_to_gallery_post(&mut self) {
+        let Some(image) = self.gallery.take().and_then(|g| g.current().cloned()) else {
+            return;
+        };
+        
+        self.selected_index = image.post_index;
+        self.viewing_full_post = true;
+        self.post_scroll = 0;
+        self.need_redraw = true;
+        
+        // 选中该图片在帖子中对应的图片按钮
+        self.selected_image_button = self
+            .posts
+            .get(&self.get_current_topic_id())
+            .and_then(|posts| posts.get(image.post_index))
+            .and_then(|post| {
+                let image_paths = self.image_paths.lock().unwrap();
+                extract_images(&post.cooked)
+                    .into_iter()
+                    .filter(|post_image| image_paths.contains_key(&post_image.url))
+                    .position(|post_image| post_image.url == image.image.url)
+            });
     }
     
     // 辅助方法，获取当前主题ID
@@ -466,45 +2020,41 @@ impl App {
                 // 重置图片状态
                 self.selected_image_button = None;
                 self.showing_image = false;
-                self.current_image_url = None;
+                self.current_image = None;
             }
             AppTab::Topics => {
-                if !self.topics.is_empty() {
-                    let topic_id = self.topics[self.selected_index].id;
-                    self.current_tab = AppTab::Topic(topic_id);
-                    self.selected_index = 0;
-                    self.load_topic_posts(topic_id).await?;
-                    // 重置图片状态
-                    self.selected_image_button = None;
-                    self.showing_image = false;
-                    self.current_image_url = None;
+                let topic = self
+                    .visible_topics()
+                    .get(self.selected_index)
+                    .map(|topic| (topic.id, topic.title.clone()));
+                if let Some((id, title)) = topic {
+                    self.open_topic(id, title).await?;
                 }
             }
-            AppTab::Categories => {
-                // 根据选定的分类加载主题
-                if !self.categories.is_empty() && self.selected_index < self.categories.len() {
-                    self.current_tab = AppTab::Topics;
-                    self.selected_index = 0;
-                    // 这里应该加载特定分类的主题，但需要扩展API客户端
-                    self.load_topics().await?;
-                }
+            // 根据选定的分类加载主题
+            AppTab::Categories if self.selected_index < self.categories.len() => {
+                let category = &self.categories[self.selected_index];
+                self.history.visit_category(category.id, &category.name);
+                self.current_tab = AppTab::Topics;
+                self.selected_index = 0;
+                // 这里应该加载特定分类的主题，但需要扩展API客户端
+                self.load_topics().await?;
             }
             AppTab::Topic(id) => {
                 // 在主题中查看帖子时，选择一个用户
                 if let Some(posts) = self.posts.get(id) {
                     if !posts.is_empty() && self.selected_index < posts.len() {
                         let username = posts[self.selected_index].username.clone();
-                        self.current_tab = AppTab::User(username.clone());
-                        self.selected_index = 0;
-                        self.load_user(&username).await?;
+                        self.open_user(username).await?;
                     }
                 }
             }
-            AppTab::Settings => {
-                // 处理设置页面的选项
-                if self.selected_index == 0 { // 生成 API 密钥
-                    self.run_api_key_generator().await?;
-                }
+            // 处理设置页面的选项
+            AppTab::Settings if self.selected_index == 0 => { // 生成 API 密钥
+                self.run_api_key_generator().await?;
+            }
+            AppTab::Settings if self.selected_index == 1 => {
+                self.cycle_theme();
             }
             _ => {}
         }
@@ -574,25 +2124,313 @@ impl App {
         Ok(())
     }
     
-    async fn submit_input(&mut self) -> AppResult<()> {
-        if self.input.is_empty() {
+    /// 输入框：Enter 提交，Alt+Enter 换行，编辑帖子时 Tab 切换到修改原因，Esc 放弃
+    async fn handle_composer_key(&mut self, key: KeyEvent) -> AppResult<()> {
+        let Some(composer) = self.composer.as_mut() else {
+            return Ok(());
+        };
+        // 显示表情补全时，Tab/Enter 补全，↑/↓ 选择
+        let completing = !composer.emoji_suggestions().is_empty();
+        match key.code {
+            KeyCode::Tab | KeyCode::Enter if completing && !key.modifiers.contains(KeyModifiers::ALT) => {
+                composer.complete_emoji();
+            }
+            KeyCode::Down if completing => composer.next_emoji(),
+            KeyCode::Up if completing => composer.prev_emoji(),
+            KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => composer.new_line(),
+            KeyCode::Enter => self.submit_composer().await?,
+            KeyCode::Esc => {
+                self.composer = None;
+            }
+            KeyCode::Tab => composer.toggle_field(),
+            KeyCode::Backspace => composer.pop_char(),
+            KeyCode::Char(c) => composer.push_char(c),
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// 提交回复或修改，失败时保留输入框中的内容
+    async fn submit_composer(&mut self) -> AppResult<()> {
+        let Some(composer) = self.composer.take() else {
+            return Ok(());
+        };
+        if composer.text.trim().is_empty() {
+            return Ok(());
+        }
+        
+        match composer.target {
+            ComposeTarget::Reply { topic_id, reply_to } => match self.client.create_post(topic_id, &composer.text, reply_to).await {
+                Ok(_) => {
+                    self.set_status("已发布回复", false);
+                    self.load_topic_posts(topic_id).await?;
+                }
+                Err(e) => {
+                    self.set_status(format!("发布回复失败: {}", e), true);
+                    self.composer = Some(composer);
+                }
+            },
+            ComposeTarget::Edit { post_id, post_number } => {
+                match self.client.update_post(post_id, &composer.text, &composer.edit_reason).await {
+                    Ok(post) => {
+                        self.set_status(
+                            format!("已保存对 #{} 的修改，共修改 {} 次", post_number, post.edit_count()),
+                            false,
+                        );
+                        if let Some(existing) = self
+                            .posts
+                            .get_mut(&post.topic_id)
+                            .and_then(|posts| posts.iter_mut().find(|existing| existing.id == post.id))
+                        {
+                            *existing = post;
+                        }
+                    }
+                    Err(e) => {
+                        self.set_status(format!("保存修改失败: {}", e), true);
+                        self.composer = Some(composer);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// 切换只看热门帖子或只看某个用户的帖子，从第一页重新加载
+    async fn toggle_post_filter(&mut self, action: Action) -> AppResult<()> {
+        let AppTab::Topic(topic_id) = self.current_tab else {
+            return Ok(());
+        };
+        if action == Action::ToggleSummary {
+            self.post_filter.summary = !self.post_filter.summary;
+        } else if self.post_filter.username.is_some() {
+            self.post_filter.username = None;
+        } else {
+            let Some(username) = self.selected_post().map(|post| post.username.clone()) else {
+                return Ok(());
+            };
+            self.post_filter.username = Some(username);
+        }
+        
+        let text = match self.post_filter.describe() {
+            Some(filter) => format!("筛选: {}", filter),
+            None => "显示全部帖子".to_string(),
+        };
+        self.set_status(text, false);
+        // 搜索范围和折叠状态基于原来的帖子列表
+        self.clear_search();
+        self.post_replies.clear();
+        self.page = 1;
+        self.selected_index = 0;
+        self.post_scroll = 0;
+        self.selected_image_button = None;
+        self.load_topic_posts(topic_id).await
+    }
+    
+    /// 跳到选中帖子所回复的帖子
+    async fn jump_to_parent(&mut self) -> AppResult<()> {
+        let AppTab::Topic(topic_id) = self.current_tab else {
             return Ok(());
+        };
+        let Some(parent) = self.selected_post().and_then(|post| post.reply_to_post_number) else {
+            self.set_status("这个帖子没有回复其他帖子", false);
+            return Ok(());
+        };
+        self.jump_to_post_number(topic_id, parent).await
+    }
+    
+    /// 选中某一楼的帖子，不在当前页时按楼层估算页码并加载
+    async fn jump_to_post_number(&mut self, topic_id: u64, post_number: u64) -> AppResult<()> {
+        let find = |app: &Self| {
+            app.posts
+                .get(&topic_id)
+                .and_then(|posts| posts.iter().position(|post| post.post_number == post_number))
+        };
+        let index = match find(self) {
+            Some(index) => Some(index),
+            // 筛选后的页码与楼层无关，只在当前页中查找
+            None if self.post_filter.is_active() => None,
+            None => {
+                // 有帖子被删除时估算的页码可能有偏差
+                let per_page = self
+                    .topic_details
+                    .get(&topic_id)
+                    .map_or(POSTS_PER_PAGE, |details| details.chunk_size.max(1));
+                self.page = ((post_number.saturating_sub(1)) / per_page) as u32 + 1;
+                self.load_topic_posts(topic_id).await?;
+                find(self)
+            }
+        };
+        match index {
+            Some(index) => {
+                self.reveal_post(index);
+                self.selected_index = index;
+                self.post_scroll = 0;
+                self.selected_image_button = None;
+            }
+            None => self.set_status(format!("第 {} 页中没有 #{}", self.page, post_number), true),
         }
+        Ok(())
+    }
+    
+    /// 展开或收起选中帖子的回复列表
+    async fn toggle_replies(&mut self) {
+        let Some(post) = self.selected_post().filter(|_| matches!(self.current_tab, AppTab::Topic(_))) else {
+            return;
+        };
+        let (post_id, reply_count) = (post.id, post.reply_count);
+        if self.post_replies.remove(&post_id).is_some() {
+            return;
+        }
+        if reply_count == 0 {
+            self.set_status("这个帖子没有回复", false);
+            return;
+        }
+        match self.client.get_post_replies(post_id).await {
+            Ok(replies) => {
+                self.post_replies.insert(post_id, replies);
+            }
+            Err(e) => self.set_status(format!("获取回复失败: {}", e), true),
+        }
+    }
+    
+    /// 引用选中的帖子回复
+    ///
+    /// 当前搜索匹配在这个帖子中时只引用匹配所在的一段，否则引用帖子的全部原文。
+    async fn quote_post(&mut self) {
+        let AppTab::Topic(topic_id) = self.current_tab else {
+            return;
+        };
+        let Some(post) = self.selected_post() else {
+            return;
+        };
+        let (post_id, post_number, username) = (post.id, post.post_number, post.username.clone());
+        let matched_block = self
+            .search
+            .as_ref()
+            .and_then(|search| search.current_match())
+            .filter(|found| found.post_id == post_id)
+            .and_then(|found| match render_post(&post.cooked).blocks.into_iter().nth(found.block) {
+                Some(ContentBlock::Text(text) | ContentBlock::Quote(text) | ContentBlock::Code(text)) => Some(text),
+                _ => None,
+            });
+        
+        let text = match matched_block {
+            Some(text) => text,
+            None => match self.client.get_post_raw(post_id).await {
+                Ok(raw) => raw,
+                Err(e) => {
+                    self.set_status(format!("获取帖子原文失败: {}", e), true);
+                    return;
+                }
+            },
+        };
         
-        if let AppTab::Topic(id) = self.current_tab.clone() {
-            let content = self.input.clone();
-            self.client.create_post(id, &content).await?;
-            self.load_topic_posts(id).await?;
+        // 已经打开输入框时插入引用，保留未发送的内容
+        let same_topic = match self.composer.as_ref().map(|composer| &composer.target) {
+            None => {
+                self.composer = Some(Composer::quote(topic_id, post_number, &username, &text));
+                return;
+            }
+            Some(ComposeTarget::Reply { topic_id: target, .. }) => *target == topic_id,
+            Some(ComposeTarget::Edit { post_id, .. }) => self
+                .posts
+                .get(&topic_id)
+                .is_some_and(|posts| posts.iter().any(|post| post.id == *post_id)),
+        };
+        match self.composer.as_mut() {
+            Some(composer) if same_topic => {
+                composer.insert_quote(topic_id, post_number, &username, &text);
+                self.set_status(format!("已在输入框中插入 #{} 的引用", post_number), false);
+            }
+            _ => self.set_status("输入框中是其他主题的内容，无法插入引用", true),
+        }
+    }
+    
+    /// 加载选中帖子的原文到输入框中
+    async fn start_edit_post(&mut self) {
+        let Some(post) = self.selected_post().filter(|_| matches!(self.current_tab, AppTab::Topic(_))) else {
+            return;
+        };
+        if !post.can_edit {
+            self.set_status("没有编辑这个帖子的权限", true);
+            return;
         }
+        let (post_id, post_number) = (post.id, post.post_number);
+        match self.client.get_post_raw(post_id).await {
+            Ok(raw) => {
+                self.composer = Some(Composer::edit(post_id, post_number, raw));
+            }
+            Err(e) => self.set_status(format!("获取帖子原文失败: {}", e), true),
+        }
+    }
+    
+    /// 删除或恢复选中的帖子，完成后重新加载当前页
+    /// 删除前需要按 y 确认，恢复直接进行
+    async fn delete_or_recover_post(&mut self, delete: bool) {
+        if !matches!(self.current_tab, AppTab::Topic(_)) {
+            return;
+        }
+        let Some(post) = self.selected_post() else {
+            return;
+        };
+        let (post_id, post_number) = (post.id, post.post_number);
         
+        if delete {
+            if !post.can_delete {
+                self.set_status("没有删除这个帖子的权限", true);
+                return;
+            }
+            self.pending_delete = Some((post_id, post_number));
+            return;
+        }
+        if !post.can_recover {
+            self.set_status("这个帖子无法恢复", true);
+            return;
+        }
+        let result = self.client.recover_post(post_id).await;
+        self.finish_post_change("恢复", post_number, result).await;
+    }
+    
+    /// 删除确认：y 删除，其他键取消
+    async fn handle_delete_confirm_key(&mut self, key: KeyEvent) -> AppResult<()> {
+        let Some((post_id, post_number)) = self.pending_delete.take() else {
+            return Ok(());
+        };
+        if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+            let result = self.client.delete_post(post_id).await;
+            self.finish_post_change("删除", post_number, result).await;
+        } else {
+            self.set_status("已取消删除", false);
+        }
         Ok(())
     }
     
+    /// 删除或恢复帖子后显示结果，成功时重新加载帖子
+    async fn finish_post_change(&mut self, verb: &str, post_number: u64, result: AppResult<()>) {
+        let AppTab::Topic(topic_id) = self.current_tab else {
+            return;
+        };
+        match result {
+            Ok(()) => {
+                self.set_status(format!("已{}帖子 #{}", verb, post_number), false);
+                if let Err(e) = self.load_topic_posts(topic_id).await {
+                    warn!("重新加载帖子失败: {}", e);
+                }
+            }
+            Err(e) => self.set_status(format!("{}帖子失败: {}", verb, e), true),
+        }
+    }
+    
     async fn load_topics(&mut self) -> AppResult<()> {
         self.loading_state = LoadingState::Loading;
         
         match self.client.get_latest_topics(self.page).await {
             Ok(topics) => {
+                for topic in &topics {
+                    if let Some(level) = topic.notification_level {
+                        self.topic_levels.insert(topic.id, level);
+                    }
+                }
                 self.topics = topics;
                 self.loading_state = LoadingState::NotLoading;
             }
@@ -622,43 +2460,36 @@ impl App {
     
     async fn load_topic_posts(&mut self, topic_id: u64) -> AppResult<()> {
         self.loading_state = LoadingState::Loading;
-        match self.client.get_topic_posts(topic_id, self.page).await {
+        match self.client.get_topic_posts(topic_id, self.page, &self.post_filter).await {
             Ok(posts) => {
+                if let Some(search) = self.search.as_mut().filter(|search| search.topic_id == topic_id) {
+                    search.set_page(self.page, &posts);
+                }
+                self.thread_trees.insert(topic_id, ThreadTree::new(&posts));
                 self.posts.insert(topic_id, posts.clone());
                 self.loading_state = LoadingState::NotLoading;
                 
-                // 启动图片下载任务
+                // 启动图片下载任务，预先下载帖子中的缩略图
                 let image_cache = self.image_cache.clone();
                 let image_paths = Arc::clone(&self.image_paths);
+                let base_url = self.config.discourse.url.clone();
                 
                 tokio::spawn(async move {
+                    // 头像较小，先下载
+                    let mut avatar_urls: Vec<String> = posts
+                        .iter()
+                        .filter_map(|post| post.avatar_template.as_deref())
+                        .map(|template| avatar_url(&base_url, template))
+                        .collect();
+                    avatar_urls.sort();
+                    avatar_urls.dedup();
+                    for url in avatar_urls {
+                        fetch_image(&image_cache, &image_paths, &base_url, &url).await;
+                    }
+                    
                     for post in posts {
-                        let image_urls: Vec<String> = crate::core::image::extract_image_urls(&post.cooked);
-                        for url in image_urls {
-                            // 检查缓存中是否已存在
-                            if let Some(cached_path) = image_cache.get_cached_path(&url).await {
-                                // 如果已经缓存，则更新图片路径映射
-                                image_paths.lock().unwrap().insert(url, cached_path);
-                                continue;
-                            }
-                            
-                            // 下载图片
-                            match crate::core::image::download_image(&url).await {
-                                Ok(image_data) => {
-                                    match image_cache.add_to_cache(&url, &image_data).await {
-                                        Ok(path) => {
-                                            // 更新图片路径映射
-                                            image_paths.lock().unwrap().insert(url, path);
-                                        }
-                                        Err(e) => {
-                                            warn!("缓存图片失败: {}", e);
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    warn!("下载图片失败: {}", e);
-                                }
-                            }
+                        for url in extract_image_urls(&post.cooked) {
+                            fetch_image(&image_cache, &image_paths, &base_url, &url).await;
                         }
                     }
                 });
@@ -667,7 +2498,7 @@ impl App {
             }
             Err(e) => {
                 self.loading_state = LoadingState::Error(format!("加载帖子失败: {}", e));
-                Err(e.into())
+                Err(e)
             }
         }
     }
@@ -677,6 +2508,7 @@ impl App {
         
         match self.client.get_user(username).await {
             Ok(user) => {
+                self.fetch_avatar(&user.avatar_template);
                 self.users.insert(username.to_string(), user);
                 self.loading_state = LoadingState::NotLoading;
             }
@@ -748,4 +2580,44 @@ impl App {
         
         Ok(())
     }
-} 
\ No newline at end of file
+} 
+
+/// 依次加载主题的各页帖子，直到返回空页、重复的页面或出错，`current` 为已加载的当前页
+async fn fetch_search_pages(
+    client: Arc<dyn DiscourseClient + Send + Sync>,
+    topic_id: u64,
+    filter: PostFilter,
+    current: (u32, Vec<Post>),
+    sender: mpsc::UnboundedSender<SearchPageEvent>,
+) {
+    let mut seen = HashSet::new();
+    let mut error = None;
+    
+    for page in 1..=MAX_SEARCH_PAGES {
+        let posts = if page == current.0 {
+            current.1.clone()
+        } else {
+            match client.get_topic_posts(topic_id, page, &filter).await {
+                Ok(posts) => posts,
+                // 超出最后一页时服务器返回 404
+                Err(e) => {
+                    if page > 1 {
+                        debug!("停止加载搜索页面，第 {} 页: {}", page, e);
+                    } else {
+                        error = Some(format!("加载帖子失败: {}", e));
+                    }
+                    break;
+                }
+            }
+        };
+        // 超出最后一页时也可能返回空页或重复最后一页
+        let new_posts = posts.iter().filter(|post| seen.insert(post.id)).count();
+        if new_posts == 0 {
+            break;
+        }
+        if sender.send(SearchPageEvent::Page { topic_id, page, posts }).is_err() {
+            return;
+        }
+    }
+    let _ = sender.send(SearchPageEvent::Finished { topic_id, error });
+}
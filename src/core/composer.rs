@@ -0,0 +1,232 @@
+use crate::core::emoji;
+
+/// 输入框提交后的用途
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComposeTarget {
+    /// 回复主题，`reply_to` 为回复的帖子楼层
+    Reply { topic_id: u64, reply_to: Option<u64> },
+    /// 修改已有帖子
+    Edit { post_id: u64, post_number: u64 },
+}
+
+/// 回复和编辑帖子的输入框
+#[derive(Debug, Clone)]
+pub struct Composer {
+    pub target: ComposeTarget,
+    /// Markdown 内容
+    pub text: String,
+    /// 修改原因，只在编辑帖子时使用
+    pub edit_reason: String,
+    /// 正在输入修改原因
+    pub editing_reason: bool,
+    /// 表情补全列表中选中的一项
+    pub emoji_selected: usize,
+}
+
+/// 补全列表中最多显示的表情数
+const EMOJI_SUGGESTIONS: usize = 8;
+
+impl Composer {
+    pub fn reply(topic_id: u64) -> Self {
+        Composer {
+            target: ComposeTarget::Reply { topic_id, reply_to: None },
+            text: String::new(),
+            edit_reason: String::new(),
+            editing_reason: false,
+            emoji_selected: 0,
+        }
+    }
+
+    /// 引用帖子回复，内容以 Discourse 的引用块开头
+    pub fn quote(topic_id: u64, post_number: u64, username: &str, raw: &str) -> Self {
+        Composer {
+            target: ComposeTarget::Reply { topic_id, reply_to: Some(post_number) },
+            text: quote_block(topic_id, post_number, username, raw),
+            edit_reason: String::new(),
+            editing_reason: false,
+            emoji_selected: 0,
+        }
+    }
+
+    /// 在光标处（正文末尾）插入引用块，与已有内容之间空一行
+    pub fn insert_quote(&mut self, topic_id: u64, post_number: u64, username: &str, raw: &str) {
+        if !self.text.is_empty() && !self.text.ends_with("\n\n") {
+            self.text.push_str(if self.text.ends_with('\n') { "\n" } else { "\n\n" });
+        }
+        self.text.push_str(&quote_block(topic_id, post_number, username, raw));
+        self.editing_reason = false;
+        self.emoji_selected = 0;
+    }
+
+    /// 编辑帖子，内容为帖子的 Markdown 原文
+    pub fn edit(post_id: u64, post_number: u64, raw: String) -> Self {
+        Composer {
+            target: ComposeTarget::Edit { post_id, post_number },
+            text: raw,
+            edit_reason: String::new(),
+            editing_reason: false,
+            emoji_selected: 0,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self.target {
+            ComposeTarget::Reply { reply_to: None, .. } => "输入回复".to_string(),
+            ComposeTarget::Reply { reply_to: Some(post_number), .. } => format!("回复 #{}", post_number),
+            ComposeTarget::Edit { post_number, .. } => format!("编辑帖子 #{}", post_number),
+        }
+    }
+
+    pub fn is_edit(&self) -> bool {
+        matches!(self.target, ComposeTarget::Edit { .. })
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.field_mut().push(c);
+        self.emoji_selected = 0;
+    }
+
+    pub fn pop_char(&mut self) {
+        self.field_mut().pop();
+        self.emoji_selected = 0;
+    }
+
+    /// 正文末尾正在输入的表情短代码，如 `:smi`，冒号后至少两个字符
+    fn emoji_query(&self) -> Option<&str> {
+        if self.editing_reason {
+            return None;
+        }
+        let start = self.text.rfind(':')?;
+        let query = &self.text[start + 1..];
+        let at_word_start = self.text[..start].chars().next_back().is_none_or(char::is_whitespace);
+        let valid = query.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-'));
+        (at_word_start && valid && query.len() >= 2).then_some(query)
+    }
+
+    /// 当前可以补全的表情，为（短代码，表情）
+    pub fn emoji_suggestions(&self) -> Vec<(&'static str, &'static str)> {
+        self.emoji_query()
+            .map(|query| emoji::suggestions(query, EMOJI_SUGGESTIONS))
+            .unwrap_or_default()
+    }
+
+    pub fn next_emoji(&mut self) {
+        let count = self.emoji_suggestions().len();
+        if count > 0 {
+            self.emoji_selected = (self.emoji_selected + 1) % count;
+        }
+    }
+
+    pub fn prev_emoji(&mut self) {
+        let count = self.emoji_suggestions().len();
+        if count > 0 {
+            self.emoji_selected = (self.emoji_selected + count - 1) % count;
+        }
+    }
+
+    /// 用选中的短代码替换正在输入的部分，没有可补全的表情时返回 false
+    pub fn complete_emoji(&mut self) -> bool {
+        let Some(&(code, _)) = self.emoji_suggestions().get(self.emoji_selected) else {
+            return false;
+        };
+        let start = self.text.len() - self.emoji_query().map_or(0, str::len);
+        self.text.truncate(start);
+        self.text.push_str(code);
+        self.text.push_str(": ");
+        self.emoji_selected = 0;
+        true
+    }
+
+    /// 换行，修改原因只有一行
+    pub fn new_line(&mut self) {
+        if !self.editing_reason {
+            self.text.push('\n');
+        }
+    }
+
+    /// 编辑帖子时在内容和修改原因之间切换
+    pub fn toggle_field(&mut self) {
+        self.editing_reason = self.is_edit() && !self.editing_reason;
+    }
+
+    fn field_mut(&mut self) -> &mut String {
+        if self.editing_reason {
+            &mut self.edit_reason
+        } else {
+            &mut self.text
+        }
+    }
+}
+
+/// Discourse 的引用块，后面空一行以便继续输入
+fn quote_block(topic_id: u64, post_number: u64, username: &str, raw: &str) -> String {
+    format!(
+        "[quote=\"{}, post:{}, topic:{}\"]\n{}\n[/quote]\n\n",
+        username,
+        post_number,
+        topic_id,
+        raw.trim()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_starts_reply_with_quote_block() {
+        let composer = Composer::quote(7, 3, "alice", "  原文\n");
+        assert_eq!(composer.target, ComposeTarget::Reply { topic_id: 7, reply_to: Some(3) });
+        assert_eq!(composer.text, "[quote=\"alice, post:3, topic:7\"]\n原文\n[/quote]\n\n");
+    }
+
+    #[test]
+    fn insert_quote_keeps_draft() {
+        let mut composer = Composer::reply(7);
+        composer.text = "草稿".to_string();
+        composer.insert_quote(7, 3, "alice", "原文");
+        assert_eq!(composer.text, "草稿\n\n[quote=\"alice, post:3, topic:7\"]\n原文\n[/quote]\n\n");
+        // 上一个引用块后已经空了一行
+        composer.insert_quote(7, 4, "bob", "再引用");
+        assert!(composer.text.ends_with("[/quote]\n\n[quote=\"bob, post:4, topic:7\"]\n再引用\n[/quote]\n\n"));
+    }
+
+    #[test]
+    fn insert_quote_into_empty_composer() {
+        let mut composer = Composer::reply(7);
+        composer.insert_quote(7, 3, "alice", "原文");
+        assert_eq!(composer.text, Composer::quote(7, 3, "alice", "原文").text);
+    }
+
+    fn typed(text: &str) -> Composer {
+        let mut composer = Composer::reply(7);
+        text.chars().for_each(|c| composer.push_char(c));
+        composer
+    }
+
+    #[test]
+    fn emoji_completion_needs_two_characters_at_word_start() {
+        assert!(typed("好 :s").emoji_suggestions().is_empty());
+        assert!(!typed("好 :sm").emoji_suggestions().is_empty());
+        assert!(!typed(":sm").emoji_suggestions().is_empty());
+        // 时间和链接中的冒号不触发补全
+        assert!(typed("12:30").emoji_suggestions().is_empty());
+        assert!(typed("见 https://linux.do").emoji_suggestions().is_empty());
+        assert!(typed(":sm ile").emoji_suggestions().is_empty());
+    }
+
+    #[test]
+    fn complete_emoji_replaces_query_with_selected_code() {
+        let mut composer = typed("你好 :smi");
+        composer.next_emoji();
+        assert!(composer.complete_emoji());
+        assert_eq!(composer.text, "你好 :smirk: ");
+        assert_eq!(composer.emoji_selected, 0);
+        assert!(!composer.complete_emoji());
+
+        let mut composer = typed(":thumbs");
+        composer.prev_emoji();
+        assert!(composer.complete_emoji());
+        assert_eq!(composer.text, ":thumbsdown: ");
+    }
+}
@@ -2,10 +2,27 @@ use std::path::PathBuf;
 use std::fs;
 use serde::{Deserialize, Serialize};
 use crate::core::error::LdUiError;
+use crate::core::keymap::{Keymap, KeybindingsConfig};
+use crate::core::theme::{Theme, ThemeConfig};
+use crate::core::topic_list::TopicListConfig;
+use crate::core::ignore::{IgnoreConfig, IgnoreRules};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
     pub discourse: DiscourseConfig,
+    #[serde(default)]
+    pub images: ImageConfig,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    #[serde(default)]
+    pub topics: TopicListConfig,
+    #[serde(default)]
+    pub ignore: IgnoreConfig,
+    /// 覆盖默认快捷键，按模式分组：[keybindings.normal] quit = ["q", "<C-c>"]
+    #[serde(default, skip_serializing_if = "KeybindingsConfig::is_empty")]
+    pub keybindings: KeybindingsConfig,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -14,6 +31,53 @@ pub struct DiscourseConfig {
     pub api_key: String,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ImageConfig {
+    /// 在帖子正文中按实际位置内联显示图片（终端不支持图形协议时退回图片按钮）
+    pub inline: bool,
+    /// 内联图片的最大高度（行数）
+    pub max_height: u16,
+    /// 保存图片的目录，默认为系统下载目录
+    pub download_dir: Option<PathBuf>,
+    /// 用于打开图片的外部命令，默认为系统打开程序（如 xdg-open）
+    pub open_command: Option<String>,
+}
+
+impl Default for ImageConfig {
+    fn default() -> Self {
+        ImageConfig {
+            inline: true,
+            max_height: 15,
+            download_dir: None,
+            open_command: None,
+        }
+    }
+}
+
+impl ImageConfig {
+    /// 实际使用的图片保存目录
+    pub fn download_dir(&self) -> PathBuf {
+        self.download_dir
+            .clone()
+            .or_else(dirs::download_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct UiConfig {
+    /// 启用鼠标：滚轮滚动、点击选择、双击打开，启用后终端自身的文本选择需要按住 Shift
+    pub mouse: bool,
+    /// 在主题内搜索时在后台加载全部页面，使搜索覆盖整个主题
+    pub search_all_pages: bool,
+    /// 修改历史默认使用左右两列对比，否则上下交替显示
+    pub revisions_side_by_side: bool,
+    /// 主题默认按回复关系以树形显示
+    pub threaded: bool,
+}
+
 impl Config {
     pub fn default() -> Self {
         Config {
@@ -21,6 +85,12 @@ impl Config {
                 url: "https://linux.do".to_string(),
                 api_key: "".to_string(),
             },
+            images: ImageConfig::default(),
+            theme: ThemeConfig::default(),
+            ui: UiConfig::default(),
+            topics: TopicListConfig::default(),
+            ignore: IgnoreConfig::default(),
+            keybindings: KeybindingsConfig::new(),
         }
     }
 
@@ -43,6 +113,11 @@ impl Config {
             
         let config: Config = toml::from_str(&config_str)
             .map_err(|e| LdUiError::Config(format!("无法解析配置文件: {}", e)))?;
+        
+        // 启动时检查快捷键格式和冲突、主题是否可用，以及忽略规则中的正则表达式
+        Keymap::new(&config.keybindings)?;
+        Theme::load(&config.theme, &config.theme.name)?;
+        IgnoreRules::new(&config.ignore)?;
             
         Ok(config)
     }
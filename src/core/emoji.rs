@@ -0,0 +1,139 @@
+use emojis::{Emoji, SkinTone};
+use scraper::node::Element;
+
+/// Discourse 中与 GitHub 短代码不同的常用表情名称
+const ALIASES: &[(&str, &str)] = &[
+    ("slight_smile", "🙂"),
+    ("slight_frown", "🙁"),
+    ("upside_down", "🙃"),
+    ("white_frowning_face", "☹️"),
+    ("thumbsup", "👍"),
+    ("thumbsdown", "👎"),
+    ("hugging", "🤗"),
+    ("nerd", "🤓"),
+    ("rolling_eyes", "🙄"),
+    ("face_with_thermometer", "🤒"),
+    ("face_with_head_bandage", "🤕"),
+    ("lying_face", "🤥"),
+    ("sneezing_face", "🤧"),
+    ("zipper_mouth", "🤐"),
+    ("money_mouth", "🤑"),
+    ("robot", "🤖"),
+    ("heart_exclamation", "❣️"),
+];
+
+/// 帖子中的表情图片（img.emoji）
+pub fn is_emoji(element: &Element) -> bool {
+    element.classes().any(|c| c == "emoji")
+}
+
+/// 表情图片显示为的文字：标准表情转换为 Unicode 字符，站点自定义表情和未知表情显示为 `:name:`
+pub fn emoji_text(element: &Element) -> Option<String> {
+    let name = element
+        .attr("title")
+        .or_else(|| element.attr("alt"))?
+        .trim()
+        .trim_matches(':');
+    if name.is_empty() {
+        return None;
+    }
+    let custom = element.classes().any(|c| c == "emoji-custom")
+        || element.attr("src").is_some_and(|src| !src.contains("/images/emoji/"));
+    let unicode = (!custom).then(|| to_unicode(name)).flatten();
+    Some(unicode.unwrap_or_else(|| format!(":{}:", name)))
+}
+
+/// 按名称查找标准表情，支持 Discourse 的肤色后缀（如 `+1:t3`）
+pub fn to_unicode(name: &str) -> Option<String> {
+    let (name, tone) = match name.split_once(":t") {
+        Some((name, tone)) => (name, skin_tone(tone)),
+        None => (name, None),
+    };
+    if let Some(&(_, emoji)) = ALIASES.iter().find(|(alias, _)| *alias == name) {
+        return Some(emoji.to_string());
+    }
+    let emoji = emojis::get_by_shortcode(name)?;
+    let emoji = tone.and_then(|tone| emoji.with_skin_tone(tone)).unwrap_or(emoji);
+    Some(emoji.as_str().to_string())
+}
+
+/// Discourse 的肤色从 t1（默认）到 t6（最深）
+fn skin_tone(tone: &str) -> Option<SkinTone> {
+    match tone {
+        "2" => Some(SkinTone::Light),
+        "3" => Some(SkinTone::MediumLight),
+        "4" => Some(SkinTone::Medium),
+        "5" => Some(SkinTone::MediumDark),
+        "6" => Some(SkinTone::Dark),
+        _ => None,
+    }
+}
+
+/// 按短代码补全表情，前缀匹配的排在前面，同类中较短的排在前面
+pub fn suggestions(query: &str, limit: usize) -> Vec<(&'static str, &'static str)> {
+    let query = query.to_lowercase();
+    let standard = emojis::iter()
+        .filter(|emoji| emoji.skin_tone().is_none_or(|tone| tone == SkinTone::Default))
+        .flat_map(|emoji: &'static Emoji| emoji.shortcodes().map(move |code| (code, emoji.as_str())));
+    let mut matches: Vec<(bool, &'static str, &'static str)> = ALIASES
+        .iter()
+        .copied()
+        .chain(standard)
+        .filter(|(code, _)| code.contains(query.as_str()))
+        .map(|(code, emoji)| (!code.starts_with(query.as_str()), code, emoji))
+        .collect();
+    matches.sort_by_key(|&(later, code, _)| (later, code.len(), code));
+    matches.dedup_by_key(|&mut (_, code, _)| code);
+    matches.into_iter().take(limit).map(|(_, code, emoji)| (code, emoji)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(query: &str, limit: usize) -> Vec<&'static str> {
+        suggestions(query, limit)
+            .into_iter()
+            .map(|(code, _)| code)
+            .collect()
+    }
+
+    #[test]
+    fn suggests_prefix_matches_first_and_shorter_codes_first() {
+        assert_eq!(codes("smi", 3), vec!["smile", "smirk", "smiley"]);
+        // 忽略大小写，包含查询的排在前缀匹配之后
+        assert_eq!(
+            codes("SMILE", 6),
+            vec![
+                "smile",
+                "smiley",
+                "smile_cat",
+                "smiley_cat",
+                "sweat_smile",
+                "slight_smile"
+            ]
+        );
+        assert!(codes("zzzz", 8).is_empty());
+    }
+
+    #[test]
+    fn suggestions_include_discourse_aliases() {
+        assert_eq!(
+            suggestions("thumbs", 8),
+            vec![("thumbsup", "👍"), ("thumbsdown", "👎")]
+        );
+        assert!(codes("heart_e", 8).contains(&"heart_exclamation"));
+        // 肤色变体不单独列出
+        assert!(suggestions("+1", 8).iter().all(|(_, emoji)| *emoji == "👍"));
+    }
+
+    #[test]
+    fn converts_names_with_aliases_and_skin_tones() {
+        assert_eq!(to_unicode("smile").as_deref(), Some("😄"));
+        assert_eq!(to_unicode("slight_smile").as_deref(), Some("🙂"));
+        assert_eq!(to_unicode("+1:t3").as_deref(), Some("👍🏼"));
+        // t1 为默认肤色
+        assert_eq!(to_unicode("+1:t1").as_deref(), Some("👍"));
+        assert_eq!(to_unicode("not_an_emoji"), None);
+    }
+}
@@ -0,0 +1,111 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use tracing::debug;
+
+/// 依次尝试的剪贴板命令
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+    &["pbcopy"],
+];
+
+/// 将文件复制到下载目录，文件名已存在时自动添加序号，返回保存后的路径
+pub fn save_to_directory(source: &Path, directory: &Path, filename: &str) -> Result<PathBuf> {
+    std::fs::create_dir_all(directory)
+        .map_err(|e| anyhow!("无法创建下载目录 {:?}: {}", directory, e))?;
+
+    let target = unique_path(directory, filename);
+    std::fs::copy(source, &target).map_err(|e| anyhow!("无法保存文件: {}", e))?;
+    Ok(target)
+}
+
+/// 在目录中找到一个不会覆盖已有文件的路径，例如 "image (1).png"
+fn unique_path(directory: &Path, filename: &str) -> PathBuf {
+    let candidate = directory.join(filename);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let path = Path::new(filename);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(filename);
+    let extension = path.extension().and_then(|e| e.to_str());
+    (1..)
+        .map(|n| match extension {
+            Some(ext) => directory.join(format!("{} ({}).{}", stem, n, ext)),
+            None => directory.join(format!("{} ({})", stem, n)),
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap()
+}
+
+/// 复制文本到系统剪贴板
+///
+/// 优先使用系统剪贴板命令，都不可用时通过 OSC 52 转义序列交给终端处理。
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    for command in CLIPBOARD_COMMANDS {
+        if pipe_to_command(command, text).is_ok() {
+            debug!("使用 {} 复制到剪贴板", command[0]);
+            return Ok(());
+        }
+    }
+
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", BASE64.encode(text))?;
+    stdout.flush()?;
+    Ok(())
+}
+
+fn pipe_to_command(command: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("{} 退出状态: {}", command[0], status));
+    }
+    Ok(())
+}
+
+/// 使用配置的命令或系统默认程序打开文件
+pub fn open_external(path: &Path, command: Option<&str>) -> Result<()> {
+    let command = command.unwrap_or(default_open_command());
+    let mut parts = command.split_whitespace();
+    let program = parts.next().ok_or_else(|| anyhow!("打开命令为空"))?;
+
+    let mut child = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow!("无法运行 {}: {}", program, e))?;
+    // 不等待外部程序退出，在后台线程中回收，避免留下僵尸进程
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+fn default_open_command() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    }
+}
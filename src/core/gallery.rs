@@ -0,0 +1,84 @@
+use std::cell::Cell;
+
+use crate::api::Post;
+use crate::core::image::{extract_images, PostImage};
+
+/// 画廊中的一张图片，记录其所在的帖子
+#[derive(Debug, Clone)]
+pub struct GalleryImage {
+    pub image: PostImage,
+    /// 所在帖子在当前帖子列表中的索引
+    pub post_index: usize,
+    pub post_number: u64,
+    pub username: String,
+}
+
+/// 主题图片画廊状态
+#[derive(Debug, Clone, Default)]
+pub struct Gallery {
+    pub images: Vec<GalleryImage>,
+    pub selected: usize,
+    /// 是否处于大图预览模式
+    pub previewing: bool,
+    /// 缩略图网格的列数，由绘制时根据终端宽度更新
+    pub columns: Cell<usize>,
+}
+
+impl Gallery {
+    /// 收集已加载帖子中的全部图片
+    pub fn from_posts(posts: &[Post]) -> Self {
+        let images = posts
+            .iter()
+            .enumerate()
+            .flat_map(|(post_index, post)| {
+                extract_images(&post.cooked)
+                    .into_iter()
+                    .map(move |image| GalleryImage {
+                        image,
+                        post_index,
+                        post_number: post.post_number,
+                        username: post.username.clone(),
+                    })
+            })
+            .collect();
+
+        Self {
+            images,
+            selected: 0,
+            previewing: false,
+            columns: Cell::new(1),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    pub fn current(&self) -> Option<&GalleryImage> {
+        self.images.get(self.selected)
+    }
+
+    pub fn next(&mut self) {
+        if self.selected + 1 < self.images.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// 在缩略图网格中向下移动一行
+    pub fn next_row(&mut self) {
+        let columns = self.columns.get().max(1);
+        if self.selected + columns < self.images.len() {
+            self.selected += columns;
+        }
+    }
+
+    /// 在缩略图网格中向上移动一行
+    pub fn prev_row(&mut self) {
+        let columns = self.columns.get().max(1);
+        self.selected = self.selected.saturating_sub(columns);
+    }
+}
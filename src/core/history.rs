@@ -0,0 +1,43 @@
+use std::collections::VecDeque;
+
+/// 每类最多记住的条目数
+const MAX_RECENT: usize = 10;
+
+/// 最近访问过的主题、分类和用户，最近访问的在前
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    /// 主题 ID 和标题
+    pub topics: VecDeque<(u64, String)>,
+    /// 分类 ID 和名称
+    pub categories: VecDeque<(u64, String)>,
+    pub users: VecDeque<String>,
+}
+
+impl History {
+    pub fn visit_topic(&mut self, id: u64, title: &str) {
+        push_recent(&mut self.topics, (id, title.to_string()), |(existing, _)| *existing == id);
+    }
+
+    pub fn visit_category(&mut self, id: u64, name: &str) {
+        push_recent(&mut self.categories, (id, name.to_string()), |(existing, _)| *existing == id);
+    }
+
+    pub fn visit_user(&mut self, username: &str) {
+        push_recent(&mut self.users, username.to_string(), |existing| existing == username);
+    }
+
+    /// 已记录的主题标题
+    pub fn topic_title(&self, id: u64) -> Option<&str> {
+        self.topics
+            .iter()
+            .find(|(existing, _)| *existing == id)
+            .map(|(_, title)| title.as_str())
+    }
+}
+
+/// 移除重复的旧记录，把新记录放到最前面
+fn push_recent<T>(items: &mut VecDeque<T>, item: T, is_same: impl Fn(&T) -> bool) {
+    items.retain(|existing| !is_same(existing));
+    items.push_front(item);
+    items.truncate(MAX_RECENT);
+}
@@ -0,0 +1,439 @@
+use scraper::{ElementRef, Html, Node, Selector};
+
+use crate::core::emoji::{emoji_text, is_emoji};
+use crate::core::image::is_content_image;
+
+/// 帖子正文渲染后的内容块，按文档顺序排列
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentBlock {
+    /// 一行文本，显示时再按终端宽度折行
+    Text(String),
+    /// 引用中的一行文本
+    Quote(String),
+    /// 代码块中的一行，保留原始空白
+    Code(String),
+    /// 一张图片，位置与其在 HTML 中的位置一致
+    Image(String),
+    /// 投票的位置，内容为投票名称，选项和结果来自帖子的 polls 字段
+    Poll(String),
+    /// 链接预览卡片
+    Onebox(Onebox),
+}
+
+/// Discourse 为单独一行的链接生成的预览（aside.onebox）
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Onebox {
+    pub url: String,
+    pub domain: Option<String>,
+    pub title: Option<String>,
+    pub excerpt: Option<String>,
+}
+
+/// 剧透内容在所在文本行中的字符范围，显示前需要打开
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spoiler {
+    pub block: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 可折叠的 <details> 区域，内容为 `start..end` 的内容块
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetailsSection {
+    /// <summary> 所在的内容块，没有 <summary> 时为空
+    pub summary: Option<usize>,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl DetailsSection {
+    pub fn contains(&self, block: usize) -> bool {
+        (self.start..self.end).contains(&block)
+    }
+}
+
+/// 正文中的一个链接，位置为所在文本行中的字符范围
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    /// 所在内容块的序号
+    pub block: usize,
+    pub start: usize,
+    pub end: usize,
+    pub href: String,
+}
+
+/// 渲染后的帖子正文
+#[derive(Debug, Clone, Default)]
+pub struct RenderedPost {
+    pub blocks: Vec<ContentBlock>,
+    /// 位于同一行文本内的链接
+    pub links: Vec<Link>,
+    pub spoilers: Vec<Spoiler>,
+    /// 按开始位置排列，嵌套的区域在外层之后
+    pub details: Vec<DetailsSection>,
+}
+
+impl RenderedPost {
+    /// 把未打开的剧透内容替换为遮挡字符，保持字符数不变以免影响链接和搜索的位置
+    pub fn hide_spoilers(&mut self) {
+        for spoiler in &self.spoilers {
+            let (ContentBlock::Text(text) | ContentBlock::Quote(text) | ContentBlock::Code(text)) =
+                &mut self.blocks[spoiler.block]
+            else {
+                continue;
+            };
+            *text = text
+                .chars()
+                .enumerate()
+                .map(|(i, c)| if (spoiler.start..spoiler.end).contains(&i) && !c.is_whitespace() { '░' } else { c })
+                .collect();
+        }
+    }
+
+    /// 内容块是否位于收起的 <details> 中，`expanded` 判断某个区域是否展开
+    pub fn is_collapsed(&self, block: usize, expanded: impl Fn(usize) -> bool) -> bool {
+        self.details
+            .iter()
+            .enumerate()
+            .any(|(index, section)| section.contains(block) && !expanded(index))
+    }
+}
+
+/// 会产生换行的块级元素
+const BLOCK_TAGS: &[&str] = &[
+    "p", "div", "li", "ul", "ol", "blockquote", "pre", "h1", "h2", "h3", "h4", "h5", "h6",
+    "table", "tr", "aside", "details", "summary", "hr", "header", "article", "section", "figure",
+];
+
+/// 不需要输出任何内容的元素
+const SKIPPED_TAGS: &[&str] = &["script", "style", "svg", "noscript"];
+
+/// 将一小段 HTML（如投票选项）渲染为单行文本
+pub fn plain_text(html: &str) -> String {
+    render_post(html)
+        .blocks
+        .into_iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text(text) | ContentBlock::Quote(text) | ContentBlock::Code(text) => Some(text),
+            ContentBlock::Image(_) | ContentBlock::Poll(_) | ContentBlock::Onebox(_) => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 将 Discourse 的 cooked HTML 渲染为文本行和图片组成的内容块，同时记录链接的位置
+pub fn render_post(html: &str) -> RenderedPost {
+    let fragment = Html::parse_fragment(html);
+    let mut renderer = Renderer::default();
+    renderer.walk(fragment.root_element());
+    renderer.flush();
+    RenderedPost {
+        blocks: renderer.blocks,
+        links: renderer.links,
+        spoilers: renderer.spoilers,
+        details: renderer.details,
+    }
+}
+
+#[derive(Default)]
+struct Renderer {
+    blocks: Vec<ContentBlock>,
+    current: String,
+    /// 处于 <pre> 中时保留原始空白
+    pre_depth: usize,
+    /// 处于引用中的层数
+    quote_depth: usize,
+    links: Vec<Link>,
+    /// 当前行中的链接（起止字符位置和地址），结束当前行时确定所在内容块
+    line_links: Vec<(usize, usize, String)>,
+    spoilers: Vec<Spoiler>,
+    /// 处于剧透中的层数，以及最外层剧透在当前行中开始的位置
+    spoiler_depth: usize,
+    spoiler_start: usize,
+    /// 当前行中已经结束的剧透范围
+    line_spoilers: Vec<(usize, usize)>,
+    details: Vec<DetailsSection>,
+    /// 尚未结束的 <details>，为 details 中的序号
+    open_details: Vec<usize>,
+}
+
+impl Renderer {
+    fn walk(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.push_text(text),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        self.visit_element(child);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_element(&mut self, element: ElementRef) {
+        let value = element.value();
+        let name = value.name();
+
+        if SKIPPED_TAGS.contains(&name) {
+            return;
+        }
+
+        // 灯箱图片下方的文件名和尺寸信息不属于正文
+        if value.classes().any(|c| c == "meta") && is_in_lightbox(element) {
+            return;
+        }
+
+        // 投票的 HTML 只是服务器渲染的静态结果，改为按帖子的 polls 数据显示
+        if let Some(poll_name) = value
+            .attr("data-poll-name")
+            .filter(|_| name == "div" && value.classes().any(|c| c == "poll"))
+        {
+            self.flush();
+            self.blocks.push(ContentBlock::Poll(poll_name.to_string()));
+            return;
+        }
+
+        if name == "aside" && value.classes().any(|c| c == "onebox") {
+            self.flush();
+            self.blocks.push(ContentBlock::Onebox(parse_onebox(element)));
+            // 预览中的缩略图仍作为图片显示，与图片按钮的顺序保持一致
+            let images = Selector::parse("img").unwrap();
+            for image in element.select(&images) {
+                if let Some(src) = image.value().attr("src").filter(|_| is_content_image(image.value())) {
+                    self.blocks.push(ContentBlock::Image(src.to_string()));
+                }
+            }
+            return;
+        }
+
+        match name {
+            "br" => {
+                self.flush();
+                return;
+            }
+            "img" => {
+                // 表情与文字在同一行
+                if is_emoji(value) {
+                    if let Some(text) = emoji_text(value) {
+                        self.current.push_str(&text);
+                    }
+                    return;
+                }
+                if let Some(src) = value.attr("src").filter(|_| is_content_image(value)) {
+                    self.flush();
+                    self.blocks.push(ContentBlock::Image(src.to_string()));
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        let is_block = BLOCK_TAGS.contains(&name);
+        if is_block {
+            self.flush();
+        }
+        if name == "li" && !self.current.ends_with("• ") {
+            self.current.push_str("• ");
+        }
+        let is_quote = name == "blockquote" || (name == "aside" && value.classes().any(|c| c == "quote"));
+        if name == "pre" {
+            self.pre_depth += 1;
+        }
+        if is_quote {
+            self.quote_depth += 1;
+        }
+        let is_spoiler = value.classes().any(|c| c == "spoiler" || c == "spoiled");
+        if is_spoiler {
+            if self.spoiler_depth == 0 {
+                self.spoiler_start = self.current.chars().count();
+            }
+            self.spoiler_depth += 1;
+        }
+        if name == "details" {
+            let start = self.blocks.len();
+            self.open_details.push(self.details.len());
+            self.details.push(DetailsSection { summary: None, start, end: start });
+        }
+
+        // 只记录没有跨行的文字链接，图片链接由图片按钮处理
+        let link_start = (name == "a" && !value.classes().any(|c| c == "lightbox"))
+            .then(|| value.attr("href"))
+            .flatten()
+            .map(|href| (self.blocks.len(), self.current.chars().count(), href));
+
+        self.walk(element);
+
+        if let Some((block, start, href)) = link_start {
+            let end = self.current.trim_end().chars().count();
+            if block == self.blocks.len() && end > start {
+                self.line_links.push((start, end, href.to_string()));
+            }
+        }
+
+        // 先结束元素内的最后一行，再离开代码块或引用
+        if name == "pre" {
+            self.flush();
+            self.pre_depth -= 1;
+        }
+        if is_quote {
+            self.flush();
+            self.quote_depth -= 1;
+        }
+        if name == "li" && self.current.trim_end() == "•" {
+            self.current.clear();
+        }
+        if is_spoiler {
+            self.spoiler_depth -= 1;
+            if self.spoiler_depth == 0 {
+                let end = self.current.trim_end().chars().count();
+                if end > self.spoiler_start {
+                    self.line_spoilers.push((self.spoiler_start, end));
+                }
+            }
+        }
+        if is_block {
+            self.flush();
+        }
+        // <summary> 之后的内容块才会被折叠
+        if name == "summary" {
+            if let Some(section) = self.open_details.last().map(|&index| &mut self.details[index]) {
+                if section.summary.is_none() && self.blocks.len() > section.start {
+                    section.summary = Some(self.blocks.len() - 1);
+                    section.start = self.blocks.len();
+                }
+            }
+        }
+        if name == "details" {
+            if let Some(index) = self.open_details.pop() {
+                self.details[index].end = self.blocks.len();
+            }
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if self.pre_depth > 0 {
+            // 代码块按原始换行拆分
+            let mut lines = text.split('\n');
+            if let Some(first) = lines.next() {
+                self.current.push_str(first);
+            }
+            for line in lines {
+                self.flush_raw();
+                self.current.push_str(line);
+            }
+            return;
+        }
+
+        // 普通文本合并连续空白
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if text.starts_with(char::is_whitespace) {
+            self.push_space();
+        }
+        if words.is_empty() {
+            return;
+        }
+        self.current.push_str(&words.join(" "));
+        if text.ends_with(char::is_whitespace) {
+            self.push_space();
+        }
+    }
+
+    fn push_space(&mut self) {
+        if !self.current.is_empty() && !self.current.ends_with(' ') {
+            self.current.push(' ');
+        }
+    }
+
+    /// 结束当前行，忽略空白行
+    fn flush(&mut self) {
+        let line = self.current.trim_end();
+        // 只有列表符号时保留，让列表项中的段落接在符号后面
+        if line == "•" {
+            return;
+        }
+        if !line.is_empty() {
+            let line = line.to_string();
+            self.push_line_links();
+            self.blocks.push(self.line_block(line));
+        }
+        self.line_links.clear();
+        self.line_spoilers.clear();
+        self.current.clear();
+        self.spoiler_start = 0;
+    }
+
+    /// 将当前行中的链接和剧透关联到即将加入的内容块，剧透跨行时当前行到行尾都属于剧透
+    fn push_line_links(&mut self) {
+        let block = self.blocks.len();
+        self.links.extend(self.line_links.drain(..).map(|(start, end, href)| Link {
+            block,
+            start,
+            end,
+            href,
+        }));
+        if self.spoiler_depth > 0 {
+            let end = self.current.trim_end().chars().count();
+            if end > self.spoiler_start {
+                self.line_spoilers.push((self.spoiler_start, end));
+            }
+        }
+        self.spoilers
+            .extend(self.line_spoilers.drain(..).map(|(start, end)| Spoiler { block, start, end }));
+    }
+
+    /// 按当前所处的元素决定文本行的类型
+    fn line_block(&self, line: String) -> ContentBlock {
+        if self.pre_depth > 0 {
+            ContentBlock::Code(line)
+        } else if self.quote_depth > 0 {
+            ContentBlock::Quote(line)
+        } else {
+            ContentBlock::Text(line)
+        }
+    }
+
+    /// 结束当前行，保留空行（用于代码块）
+    fn flush_raw(&mut self) {
+        let line = self.current.trim_end().to_string();
+        self.push_line_links();
+        self.blocks.push(self.line_block(line));
+        self.current.clear();
+        self.spoiler_start = 0;
+    }
+}
+
+/// 从预览卡片中取出地址、网站、标题和摘要
+fn parse_onebox(element: ElementRef) -> Onebox {
+    let text_of = |selector: &str| {
+        let selector = Selector::parse(selector).unwrap();
+        element
+            .select(&selector)
+            .map(|found| found.text().collect::<Vec<_>>().join(" ").split_whitespace().collect::<Vec<_>>().join(" "))
+            .find(|text| !text.is_empty())
+    };
+    let title_link = Selector::parse("h3 a, h4 a, header a").unwrap();
+    let url = element
+        .value()
+        .attr("data-onebox-src")
+        .or_else(|| element.select(&title_link).find_map(|link| link.value().attr("href")))
+        .unwrap_or_default()
+        .to_string();
+    Onebox {
+        domain: text_of("header.source").or_else(|| {
+            let host = url.split("://").nth(1)?.split('/').next()?;
+            Some(host.to_string())
+        }),
+        title: text_of("h3").or_else(|| text_of("h4")),
+        excerpt: text_of("article p").or_else(|| text_of("p")),
+        url,
+    }
+}
+
+fn is_in_lightbox(element: ElementRef) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|a| a.value().name() == "a" && a.value().classes().any(|c| c == "lightbox"))
+}
@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Post, SuggestedTopic, Topic};
+use crate::core::error::LdUiError;
+
+/// 配置文件中 [ignore] 的内容
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct IgnoreConfig {
+    /// 忽略的用户名，其帖子折叠显示，发起的主题不显示
+    pub users: Vec<String>,
+    /// 忽略的主题 ID
+    pub topics: Vec<u64>,
+    /// 忽略的分类 ID
+    pub categories: Vec<u64>,
+    /// 忽略的标签
+    pub tags: Vec<String>,
+    /// 标题匹配这些正则表达式的主题不显示
+    pub title_patterns: Vec<String>,
+    /// 在应用中添加忽略项时，同时在服务器上静音对应的用户、主题或分类
+    pub sync_server: bool,
+}
+
+/// 由配置生成的忽略规则，用户名和标签忽略大小写
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    users: HashSet<String>,
+    topics: HashSet<u64>,
+    categories: HashSet<u64>,
+    tags: HashSet<String>,
+    title_patterns: Vec<Regex>,
+}
+
+impl IgnoreRules {
+    pub fn new(config: &IgnoreConfig) -> Result<Self, LdUiError> {
+        let title_patterns = config
+            .title_patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| {
+                    LdUiError::Config(format!("忽略规则中的标题正则表达式 \"{}\" 无效: {}", pattern, e))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(IgnoreRules {
+            users: config.users.iter().map(|user| user.to_lowercase()).collect(),
+            topics: config.topics.iter().copied().collect(),
+            categories: config.categories.iter().copied().collect(),
+            tags: config.tags.iter().map(|tag| tag.to_lowercase()).collect(),
+            title_patterns,
+        })
+    }
+
+    pub fn ignores_user(&self, username: &str) -> bool {
+        self.users.contains(&username.to_lowercase())
+    }
+
+    /// 主题是否应从列表中隐藏
+    ///
+    /// 参与者列表中的第一位为发起人，发起人被忽略时隐藏主题。
+    pub fn ignores_topic(&self, topic: &Topic) -> bool {
+        self.ignores_fields(topic.id, topic.category_id, &topic.tags, &topic.title)
+            || topic
+                .posters
+                .first()
+                .and_then(|poster| poster.username.as_deref())
+                .is_some_and(|username| self.ignores_user(username))
+    }
+
+    /// 主题末尾的推荐主题是否应隐藏，推荐主题中没有发起人
+    pub fn ignores_suggested(&self, topic: &SuggestedTopic) -> bool {
+        self.ignores_fields(topic.id, topic.category_id, &topic.tags, &topic.title)
+    }
+
+    fn ignores_fields<'a>(
+        &self,
+        id: u64,
+        category_id: Option<u64>,
+        tags: impl IntoIterator<Item = &'a String>,
+        title: &str,
+    ) -> bool {
+        self.topics.contains(&id)
+            || category_id.is_some_and(|id| self.categories.contains(&id))
+            || tags.into_iter().any(|tag| self.tags.contains(&tag.to_lowercase()))
+            || self.title_patterns.iter().any(|pattern| pattern.is_match(title))
+    }
+
+    /// 帖子是否应折叠显示
+    pub fn ignores_post(&self, post: &Post) -> bool {
+        self.ignores_user(&post.username)
+    }
+}
+
+/// 可以从界面中添加到忽略列表的项目
+#[derive(Debug, Clone, PartialEq)]
+pub enum IgnoreTarget {
+    User(String),
+    Topic(u64),
+    Category(u64),
+}
+
+impl IgnoreConfig {
+    /// 添加或移除忽略项，返回添加后是否处于忽略状态
+    pub fn toggle(&mut self, target: &IgnoreTarget) -> bool {
+        match target {
+            IgnoreTarget::User(username) => toggle_item(&mut self.users, username.clone(), |existing| {
+                existing.to_lowercase() == username.to_lowercase()
+            }),
+            IgnoreTarget::Topic(id) => toggle_item(&mut self.topics, *id, |existing| existing == id),
+            IgnoreTarget::Category(id) => toggle_item(&mut self.categories, *id, |existing| existing == id),
+        }
+    }
+}
+
+fn toggle_item<T>(items: &mut Vec<T>, item: T, is_same: impl Fn(&T) -> bool) -> bool {
+    let before = items.len();
+    items.retain(|existing| !is_same(existing));
+    if items.len() == before {
+        items.push(item);
+        true
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topic(id: u64, title: &str, category_id: u64, tags: &[&str], starter: &str) -> Topic {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": title,
+            "category_id": category_id,
+            "posts_count": 1,
+            "views": 0,
+            "created_at": "2024-01-01T00:00:00Z",
+            "last_posted_at": null,
+            "last_poster_username": "other",
+            "posters": [
+                { "user_id": 1, "primary_group_id": null, "description": null, "username": starter },
+                { "user_id": 2, "primary_group_id": null, "description": null, "username": "other" },
+            ],
+            "tags": tags,
+        }))
+        .unwrap()
+    }
+
+    fn rules() -> IgnoreRules {
+        IgnoreRules::new(&IgnoreConfig {
+            users: vec!["Spammer".to_string()],
+            topics: vec![10],
+            categories: vec![5],
+            tags: vec!["抽奖".to_string(), "NSFW".to_string()],
+            title_patterns: vec!["^【广告】".to_string(), r"(?i)\bcrypto\b".to_string()],
+            sync_server: false,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn matches_each_kind_of_rule() {
+        let rules = rules();
+        assert!(!rules.ignores_topic(&topic(1, "正常主题", 1, &["rust"], "alice")));
+        assert!(rules.ignores_topic(&topic(10, "正常主题", 1, &[], "alice")));
+        assert!(rules.ignores_topic(&topic(1, "正常主题", 5, &[], "alice")));
+        assert!(rules.ignores_topic(&topic(1, "正常主题", 1, &["nsfw"], "alice")));
+        assert!(rules.ignores_topic(&topic(1, "正常主题", 1, &["抽奖"], "alice")));
+        assert!(rules.ignores_topic(&topic(1, "【广告】低价出售", 1, &[], "alice")));
+        assert!(!rules.ignores_topic(&topic(1, "转卖【广告】位", 1, &[], "alice")));
+        assert!(rules.ignores_topic(&topic(1, "聊聊 Crypto 行情", 1, &[], "alice")));
+        assert!(!rules.ignores_topic(&topic(1, "cryptography 入门", 1, &[], "alice")));
+    }
+
+    #[test]
+    fn ignores_topics_started_by_ignored_users() {
+        let rules = rules();
+        assert!(rules.ignores_user("spammer"));
+        assert!(rules.ignores_user("SPAMMER"));
+        assert!(!rules.ignores_user("spam"));
+        assert!(rules.ignores_topic(&topic(1, "正常主题", 1, &[], "spammer")));
+        // 只看发起人，参与回复不影响主题
+        let mut topic = topic(1, "正常主题", 1, &[], "alice");
+        topic.posters[1].username = Some("Spammer".to_string());
+        assert!(!rules.ignores_topic(&topic));
+    }
+
+    #[test]
+    fn rejects_invalid_title_patterns() {
+        let result = IgnoreRules::new(&IgnoreConfig {
+            title_patterns: vec!["正常".to_string(), "(未闭合".to_string()],
+            ..Default::default()
+        });
+        match result {
+            Err(LdUiError::Config(message)) => assert!(message.contains("(未闭合")),
+            other => panic!("expected config error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn toggle_adds_and_removes_items() {
+        let mut config = IgnoreConfig::default();
+        assert!(config.toggle(&IgnoreTarget::User("Alice".to_string())));
+        assert!(config.toggle(&IgnoreTarget::Topic(3)));
+        assert_eq!(config.users, vec!["Alice"]);
+        // 用户名忽略大小写
+        assert!(!config.toggle(&IgnoreTarget::User("alice".to_string())));
+        assert!(config.users.is_empty());
+        assert!(!config.toggle(&IgnoreTarget::Topic(3)));
+        assert!(config.topics.is_empty());
+        assert!(config.toggle(&IgnoreTarget::Category(7)));
+        assert_eq!(config.categories, vec![7]);
+    }
+}
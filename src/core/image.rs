@@ -1,9 +1,10 @@
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
 use std::collections::HashMap;
 use tokio::sync::Mutex;
 use anyhow::Result;
-use scraper::{Html, Selector};
+use scraper::{node::Element, ElementRef, Html, Selector};
 use tracing::{debug, warn};
 
 /// 图片缓存，用于存储已下载的图片
@@ -36,7 +37,7 @@ impl ImageCache {
     pub async fn add_to_cache(&self, url: &str, image_data: &[u8]) -> Result<PathBuf> {
         // 计算文件名（使用URL的哈希）
         let url_hash = format!("{:x}", md5::compute(url.as_bytes()));
-        let ext = url.split('.').last().unwrap_or("jpg");
+        let ext = url.split('.').next_back().unwrap_or("jpg");
         let filename = format!("{}.{}", url_hash, ext);
         let file_path = self.cache_dir.join(&filename);
         
@@ -51,29 +52,164 @@ impl ImageCache {
     }
 }
 
-/// 从HTML中提取图片URL
-pub fn extract_image_urls(html_content: &str) -> Vec<String> {
-    let document = Html::parse_document(html_content);
-    
-    // 优化：使用更具体的选择器，只选择需要的图片元素
-    // 例如，避免选择小图标或头像等
-    let selector = Selector::parse("img:not(.avatar):not(.icon)").unwrap_or_else(|_| {
-        // 如果选择器无效，回退到基本选择器
-        Selector::parse("img").unwrap()
-    });
+/// 帖子中的一张图片及其灯箱信息
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PostImage {
+    /// 帖子中显示的缩略图（优化后的图片）地址
+    pub url: String,
+    /// 灯箱链接指向的原图地址
+    pub original_url: Option<String>,
+    pub filename: Option<String>,
+    /// 原图尺寸，例如 "1920×1080"
+    pub dimensions: Option<String>,
+    /// 原图文件大小，例如 "123 KB"
+    pub file_size: Option<String>,
+}
+
+impl PostImage {
+    /// 查看器中应显示的地址：有原图时为原图，否则为缩略图
+    pub fn full_url(&self) -> &str {
+        self.original_url.as_deref().unwrap_or(&self.url)
+    }
     
-    let mut urls = Vec::new();
+    /// 保存到本地时使用的文件名：优先使用灯箱中的原始文件名，缺少扩展名时从地址中补全
+    pub fn download_filename(&self) -> String {
+        let url_name = self
+            .full_url()
+            .split(['?', '#'])
+            .next()
+            .and_then(|path| path.rsplit('/').next())
+            .filter(|name| !name.is_empty())
+            .unwrap_or("image");
+        
+        let filename = match self.filename.as_deref().map(str::trim).filter(|name| !name.is_empty()) {
+            Some(name) if name.contains('.') => name.to_string(),
+            Some(name) => match url_name.rsplit_once('.') {
+                Some((_, ext)) => format!("{}.{}", name, ext),
+                None => name.to_string(),
+            },
+            None => url_name.to_string(),
+        };
+        
+        // 避免文件名中出现路径分隔符
+        filename.replace(['/', '\\'], "_")
+    }
+}
+
+/// 判断一个img元素是否为正文图片（排除头像、图标、表情和data:URL）
+pub fn is_content_image(element: &Element) -> bool {
+    let is_decoration = element.classes().any(|c| c == "avatar" || c == "icon" || c == "site-icon" || c == "emoji");
+    let is_data_url = element.attr("src").is_some_and(|src| src.starts_with("data:"));
+    !is_decoration && !is_data_url
+}
+
+/// 从HTML中提取图片及其灯箱信息
+pub fn extract_images(html_content: &str) -> Vec<PostImage> {
+    let document = Html::parse_document(html_content);
+    let selector = Selector::parse("img").unwrap();
     
-    for element in document.select(&selector) {
-        if let Some(src) = element.value().attr("src") {
-            // 跳过非图片URL（如data:URL)
-            if !src.starts_with("data:") {
-                urls.push(src.to_string());
+    document
+        .select(&selector)
+        .filter(|element| is_content_image(element.value()))
+        .filter_map(|element| {
+            let url = element.value().attr("src")?.to_string();
+            let mut image = PostImage { url, ..Default::default() };
+            
+            // Discourse 将大图包裹在 a.lightbox 中，链接指向原图
+            let lightbox = element
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .find(|a| a.value().name() == "a" && a.value().classes().any(|c| c == "lightbox"));
+            
+            if let Some(lightbox) = lightbox {
+                image.original_url = lightbox.value().attr("href").map(|href| href.to_string());
+                image.filename = lightbox.value().attr("title").map(|title| title.to_string());
+                
+                let filename_selector = Selector::parse(".meta .filename").unwrap();
+                let informations_selector = Selector::parse(".meta .informations").unwrap();
+                
+                if let Some(filename) = lightbox.select(&filename_selector).next() {
+                    image.filename = Some(filename.text().collect::<String>().trim().to_string());
+                }
+                if let Some(informations) = lightbox.select(&informations_selector).next() {
+                    let informations = informations.text().collect::<String>();
+                    let (dimensions, file_size) = parse_image_informations(&informations);
+                    image.dimensions = dimensions;
+                    image.file_size = file_size;
+                }
             }
+            
+            Some(image)
+        })
+        .collect()
+}
+
+/// 解析灯箱中的尺寸信息，例如 "1920×1080 123 KB"
+fn parse_image_informations(informations: &str) -> (Option<String>, Option<String>) {
+    let informations = informations.trim();
+    match informations.split_once(char::is_whitespace) {
+        Some((dimensions, size)) if dimensions.contains('×') => {
+            (Some(dimensions.to_string()), Some(size.trim().to_string()))
         }
+        _ if informations.contains('×') => (Some(informations.to_string()), None),
+        _ if informations.is_empty() => (None, None),
+        _ => (None, Some(informations.to_string())),
+    }
+}
+
+/// 从HTML中提取图片URL
+pub fn extract_image_urls(html_content: &str) -> Vec<String> {
+    extract_images(html_content)
+        .into_iter()
+        .map(|image| image.url)
+        .collect()
+}
+
+/// 请求的头像尺寸（像素），帖子列表和用户资料共用同一尺寸以复用缓存
+pub const AVATAR_SIZE: u32 = 120;
+
+/// 将头像模板中的 {size} 替换为实际尺寸，并转换为完整地址
+pub fn avatar_url(base_url: &str, avatar_template: &str) -> String {
+    absolute_url(base_url, &avatar_template.replace("{size}", &AVATAR_SIZE.to_string()))
+}
+
+/// 将帖子中的相对地址转换为完整地址
+pub fn absolute_url(base_url: &str, url: &str) -> String {
+    if url.starts_with("//") {
+        format!("https:{}", url)
+    } else if url.starts_with('/') {
+        format!("{}{}", base_url.trim_end_matches('/'), url)
+    } else {
+        url.to_string()
+    }
+}
+
+/// 下载图片到缓存，并以帖子中的原始地址为键更新图片路径映射
+pub async fn fetch_image(
+    image_cache: &ImageCache,
+    image_paths: &StdMutex<HashMap<String, PathBuf>>,
+    base_url: &str,
+    url: &str,
+) {
+    // 检查缓存中是否已存在
+    if let Some(cached_path) = image_cache.get_cached_path(url).await {
+        image_paths.lock().unwrap().insert(url.to_string(), cached_path);
+        return;
     }
     
-    urls
+    match download_image(&absolute_url(base_url, url)).await {
+        Ok(image_data) => match image_cache.add_to_cache(url, &image_data).await {
+            Ok(path) => {
+                image_paths.lock().unwrap().insert(url.to_string(), path);
+            }
+            Err(e) => {
+                warn!("缓存图片失败: {}", e);
+            }
+        },
+        Err(e) => {
+            warn!("下载图片失败: {}", e);
+        }
+    }
 }
 
 /// 异步下载图片
@@ -91,3 +227,54 @@ pub async fn download_image(url: &str) -> Result<Vec<u8>> {
     let image_data = response.bytes().await?;
     Ok(image_data.to_vec())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dimensions_and_size() {
+        assert_eq!(
+            parse_image_informations("1920×1080 123 KB"),
+            (Some("1920×1080".to_string()), Some("123 KB".to_string()))
+        );
+        // 灯箱中的文字前后带有换行和缩进
+        assert_eq!(
+            parse_image_informations("\n  800×600  1.5 MB\n"),
+            (Some("800×600".to_string()), Some("1.5 MB".to_string()))
+        );
+    }
+
+    #[test]
+    fn parses_size_or_dimensions_alone() {
+        assert_eq!(
+            parse_image_informations("123 KB"),
+            (None, Some("123 KB".to_string()))
+        );
+        assert_eq!(
+            parse_image_informations("640×480"),
+            (Some("640×480".to_string()), None)
+        );
+        assert_eq!(parse_image_informations("  "), (None, None));
+    }
+
+    #[test]
+    fn reads_lightbox_metadata() {
+        let html = r#"<div class="lightbox-wrapper"><a class="lightbox" href="/uploads/original/photo.png" title="photo">
+            <img src="/uploads/optimized/photo_690x388.png">
+            <div class="meta"><span class="filename">截图</span><span class="informations">1920×1080 123 KB</span></div>
+        </a></div><p><img src="/images/emoji/smile.png" class="emoji"><img src="/uploads/inline.jpg"></p>"#;
+        let images = extract_images(html);
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].url, "/uploads/optimized/photo_690x388.png");
+        assert_eq!(
+            images[0].original_url.as_deref(),
+            Some("/uploads/original/photo.png")
+        );
+        assert_eq!(images[0].filename.as_deref(), Some("截图"));
+        assert_eq!(images[0].dimensions.as_deref(), Some("1920×1080"));
+        assert_eq!(images[0].file_size.as_deref(), Some("123 KB"));
+        assert_eq!(images[1].url, "/uploads/inline.jpg");
+        assert_eq!(images[1].original_url, None);
+    }
+}
@@ -0,0 +1,114 @@
+use std::cell::Cell;
+
+/// 最大缩放倍数（相对于适应窗口）
+const MAX_ZOOM: f32 = 16.0;
+/// 每次缩放的倍率
+const ZOOM_STEP: f32 = 1.5;
+/// 每次平移可见区域的比例
+const PAN_STEP: f32 = 0.25;
+
+/// 图片中的一块矩形区域，坐标为相对于图片宽高的比例（0.0-1.0）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Region {
+    pub const FULL: Region = Region { x: 0.0, y: 0.0, width: 1.0, height: 1.0 };
+
+    pub fn is_full(&self) -> bool {
+        self.width >= 1.0 && self.height >= 1.0
+    }
+}
+
+/// 图片查看器的缩放与平移状态
+#[derive(Debug, Clone)]
+pub struct ImageView {
+    /// 相对于适应窗口大小的缩放倍数，1.0 表示完整显示整张图片
+    pub zoom: f32,
+    /// 是否按原始像素大小显示（一个图片像素对应一个终端像素）
+    pub actual_size: bool,
+    /// 可见区域中心在图片中的位置
+    pub center: (f32, f32),
+    /// 上一次绘制时可见区域的大小，由绘制时更新，用于计算平移步长
+    pub visible_size: Cell<(f32, f32)>,
+}
+
+impl Default for ImageView {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            actual_size: false,
+            center: (0.5, 0.5),
+            visible_size: Cell::new((1.0, 1.0)),
+        }
+    }
+}
+
+impl ImageView {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 是否只显示了图片的一部分
+    pub fn is_zoomed(&self) -> bool {
+        let (width, height) = self.visible_size.get();
+        width < 1.0 || height < 1.0
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.actual_size = false;
+        self.zoom = (self.zoom * ZOOM_STEP).min(MAX_ZOOM);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.actual_size = false;
+        self.zoom = (self.zoom / ZOOM_STEP).max(1.0);
+    }
+
+    /// 在适应窗口和原始大小之间切换
+    pub fn toggle_actual_size(&mut self) {
+        self.actual_size = !self.actual_size;
+        self.zoom = 1.0;
+    }
+
+    /// 按可见区域大小的比例平移，dx/dy 为 -1、0 或 1
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        let (width, height) = self.visible_size.get();
+        self.center.0 = clamp_center(self.center.0 + dx * width * PAN_STEP, width);
+        self.center.1 = clamp_center(self.center.1 + dy * height * PAN_STEP, height);
+    }
+
+    /// 计算当前可见的图片区域
+    ///
+    /// `image_size` 为图片像素尺寸，`viewport` 为显示区域的终端像素尺寸。
+    pub fn visible_region(&self, image_size: (u32, u32), viewport: (u32, u32)) -> Region {
+        let (image_width, image_height) = (image_size.0.max(1) as f32, image_size.1.max(1) as f32);
+        let (viewport_width, viewport_height) = (viewport.0.max(1) as f32, viewport.1.max(1) as f32);
+
+        // 适应窗口时每个图片像素对应的终端像素数
+        let fit_scale = (viewport_width / image_width).min(viewport_height / image_height);
+        let scale = if self.actual_size { 1.0 } else { fit_scale * self.zoom };
+        if scale <= fit_scale {
+            return Region::FULL;
+        }
+
+        let width = (viewport_width / scale / image_width).min(1.0);
+        let height = (viewport_height / scale / image_height).min(1.0);
+        Region {
+            x: clamp_center(self.center.0, width) - width / 2.0,
+            y: clamp_center(self.center.1, height) - height / 2.0,
+            width,
+            height,
+        }
+    }
+}
+
+/// 限制中心位置，使可见区域不超出图片边界
+fn clamp_center(center: f32, size: f32) -> f32 {
+    let half = size / 2.0;
+    center.clamp(half, (1.0 - half).max(half))
+}
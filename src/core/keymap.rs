@@ -0,0 +1,744 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::core::error::LdUiError;
+
+/// 按键所处的界面模式，每个模式有独立的快捷键表
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyMode {
+    /// 列表浏览
+    Normal,
+    /// 完整帖子查看
+    Post,
+    /// 图片查看器
+    Image,
+    /// 图片画廊缩略图网格
+    Gallery,
+    /// 图片画廊大图预览
+    Preview,
+}
+
+impl KeyMode {
+    pub const ALL: [KeyMode; 5] = [
+        KeyMode::Normal,
+        KeyMode::Post,
+        KeyMode::Image,
+        KeyMode::Gallery,
+        KeyMode::Preview,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            KeyMode::Normal => "浏览",
+            KeyMode::Post => "查看帖子时",
+            KeyMode::Image => "查看图片时",
+            KeyMode::Gallery => "图片画廊",
+            KeyMode::Preview => "画廊大图预览",
+        }
+    }
+}
+
+/// 可以绑定到按键的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    ToggleHelp,
+    OpenPalette,
+    Back,
+    Open,
+    Select,
+    Up,
+    Down,
+    Left,
+    Right,
+    First,
+    Last,
+    ShowTopics,
+    ShowCategories,
+    ShowSettings,
+    Refresh,
+    NextPage,
+    PrevPage,
+    ViewImages,
+    NextImage,
+    OpenImage,
+    OpenGallery,
+    TogglePreview,
+    JumpToPost,
+    ZoomIn,
+    ZoomOut,
+    ToggleActualSize,
+    ResetZoom,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    SaveImage,
+    CopyImageUrl,
+    OpenExternal,
+    Search,
+    SearchNext,
+    SearchPrev,
+    SearchAllPages,
+    FilterTopics,
+    CycleSort,
+    IgnoreUser,
+    IgnoreTopic,
+    IgnoreCategory,
+    NotificationLevel,
+    Reply,
+    EditPost,
+    DeletePost,
+    RecoverPost,
+    ShowRevisions,
+    JumpToParent,
+    ToggleReplies,
+    QuotePost,
+    ToggleThreadView,
+    ToggleFold,
+    NextSibling,
+    PrevSibling,
+    FirstChild,
+    ToggleSummary,
+    FilterByUser,
+    ToggleTopicHeader,
+    ShowPoll,
+    ToggleSpoiler,
+    ToggleDetails,
+}
+
+impl Action {
+    /// 帮助界面中显示的说明
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "退出",
+            Action::ToggleHelp => "显示/关闭帮助",
+            Action::OpenPalette => "命令面板",
+            Action::Back => "返回",
+            Action::Open => "打开选中项",
+            Action::Select => "选择/查看详情",
+            Action::Up => "向上移动",
+            Action::Down => "向下移动",
+            Action::Left => "上一张",
+            Action::Right => "下一张",
+            Action::First => "跳到开头",
+            Action::Last => "跳到末尾",
+            Action::ShowTopics => "查看主题",
+            Action::ShowCategories => "查看分类",
+            Action::ShowSettings => "设置",
+            Action::Refresh => "刷新",
+            Action::NextPage => "下一页（搜索时为下一个匹配）",
+            Action::PrevPage => "上一页（搜索时为上一个匹配）",
+            Action::ViewImages => "查看帖子中的图片",
+            Action::NextImage => "选择下一张图片",
+            Action::OpenImage => "查看选中的图片",
+            Action::OpenGallery => "打开本主题的图片画廊",
+            Action::TogglePreview => "大图预览/返回网格",
+            Action::JumpToPost => "跳转到图片所在帖子",
+            Action::ZoomIn => "放大",
+            Action::ZoomOut => "缩小",
+            Action::ToggleActualSize => "适应窗口/原始大小",
+            Action::ResetZoom => "重置缩放",
+            Action::PanLeft => "向左平移",
+            Action::PanRight => "向右平移",
+            Action::PanUp => "向上平移",
+            Action::PanDown => "向下平移",
+            Action::SaveImage => "保存到下载目录",
+            Action::CopyImageUrl => "复制图片链接",
+            Action::OpenExternal => "使用外部程序打开",
+            Action::Search => "在主题内搜索",
+            Action::SearchNext => "下一个搜索匹配",
+            Action::SearchPrev => "上一个搜索匹配",
+            Action::SearchAllPages => "加载全部页面并搜索",
+            Action::FilterTopics => "筛选主题列表",
+            Action::CycleSort => "切换主题排序方式",
+            Action::IgnoreUser => "忽略/取消忽略选中的用户",
+            Action::IgnoreTopic => "忽略/取消忽略选中的主题",
+            Action::IgnoreCategory => "忽略/取消忽略选中的分类",
+            Action::NotificationLevel => "设置主题/分类的通知级别",
+            Action::Reply => "回复主题",
+            Action::EditPost => "编辑选中的帖子",
+            Action::DeletePost => "删除选中的帖子",
+            Action::RecoverPost => "恢复已删除的帖子",
+            Action::ShowRevisions => "查看帖子的修改历史",
+            Action::JumpToParent => "跳到所回复的帖子",
+            Action::ToggleReplies => "展开/收起帖子的回复",
+            Action::QuotePost => "引用选中的帖子回复",
+            Action::ToggleThreadView => "切换树形/平铺显示",
+            Action::ToggleFold => "折叠/展开帖子下的回复",
+            Action::NextSibling => "下一个同级帖子",
+            Action::PrevSibling => "上一个同级帖子",
+            Action::FirstChild => "跳到第一条回复",
+            Action::ToggleSummary => "只看热门帖子/查看全部",
+            Action::FilterByUser => "只看选中帖子作者的帖子/查看全部",
+            Action::ToggleTopicHeader => "展开/收起主题信息",
+            Action::ShowPoll => "查看帖子中的投票并投票",
+            Action::ToggleSpoiler => "显示/隐藏帖子中的剧透",
+            Action::ToggleDetails => "展开/收起当前位置的折叠内容",
+        }
+    }
+}
+
+/// 单个按键，包括修饰键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        // 字符本身已经体现了 Shift（如 'G'、'?'），忽略 Shift 以便与配置中的写法一致
+        let modifiers = match event.code {
+            KeyCode::Char(_) | KeyCode::BackTab => event.modifiers - KeyModifiers::SHIFT,
+            _ => event.modifiers,
+        };
+        Key { code: event.code, modifiers }
+    }
+}
+
+/// 特殊按键在配置中的名称
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("Enter", KeyCode::Enter),
+    ("Esc", KeyCode::Esc),
+    ("Tab", KeyCode::Tab),
+    ("BackTab", KeyCode::BackTab),
+    ("Backspace", KeyCode::Backspace),
+    ("Space", KeyCode::Char(' ')),
+    ("lt", KeyCode::Char('<')),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+];
+
+impl Key {
+    fn plain(code: KeyCode) -> Self {
+        Key { code, modifiers: KeyModifiers::NONE }
+    }
+
+    /// 解析尖括号中的按键，如 "C-p"、"Enter"、"F5"
+    fn parse_special(text: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        while let Some((prefix, tail)) = rest.split_once('-').filter(|(_, tail)| !tail.is_empty()) {
+            modifiers |= match prefix {
+                "C" | "c" => KeyModifiers::CONTROL,
+                "A" | "a" | "M" | "m" => KeyModifiers::ALT,
+                "S" | "s" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+            rest = tail;
+        }
+
+        let code = if let Some((_, code)) = KEY_NAMES.iter().find(|(name, _)| name.eq_ignore_ascii_case(rest)) {
+            *code
+        } else if let Some(number) = rest.strip_prefix(['F', 'f']).and_then(|n| n.parse::<u8>().ok()) {
+            KeyCode::F(number)
+        } else {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return None,
+            }
+        };
+
+        // 终端把 Shift+Tab 报告为 BackTab
+        let code = match code {
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            code => code,
+        };
+        Some(Key::from(KeyEvent::new(code, modifiers)))
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = KEY_NAMES
+            .iter()
+            .find(|(_, code)| *code == self.code)
+            .map(|(name, _)| name.to_string())
+            .or_else(|| match self.code {
+                KeyCode::F(number) => Some(format!("F{}", number)),
+                KeyCode::Char(_) => None,
+                _ => Some(format!("{:?}", self.code)),
+            });
+
+        if name.is_none() && self.modifiers.is_empty() {
+            if let KeyCode::Char(c) = self.code {
+                return write!(f, "{}", c);
+            }
+        }
+
+        let mut prefix = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            prefix.push_str("C-");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            prefix.push_str("A-");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            prefix.push_str("S-");
+        }
+        let name = name.unwrap_or_else(|| match self.code {
+            KeyCode::Char(c) => c.to_string(),
+            _ => String::new(),
+        });
+        write!(f, "<{}{}>", prefix, name)
+    }
+}
+
+/// 按键序列，如 "gg"、"<C-p>"、"<Enter>"
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeySequence(pub Vec<Key>);
+
+impl FromStr for KeySequence {
+    type Err = LdUiError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || LdUiError::Config(format!("无效的快捷键: \"{}\"", text));
+        let mut keys = Vec::new();
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            if c == '<' {
+                let end = rest.find('>').ok_or_else(invalid)?;
+                keys.push(Key::parse_special(&rest[1..end]).ok_or_else(invalid)?);
+                rest = &rest[end + 1..];
+            } else {
+                keys.push(Key::plain(KeyCode::Char(c)));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+
+        if keys.is_empty() {
+            return Err(invalid());
+        }
+        Ok(KeySequence(keys))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for key in &self.0 {
+            write!(f, "{}", key)?;
+        }
+        Ok(())
+    }
+}
+
+/// 配置文件中一个操作的按键，可以是单个按键或按键列表
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum KeyBinding {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyBinding {
+    fn keys(&self) -> Vec<&str> {
+        match self {
+            KeyBinding::One(key) => vec![key.as_str()],
+            KeyBinding::Many(keys) => keys.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// 配置文件中 [keybindings] 的内容：模式 → 操作 → 按键
+pub type KeybindingsConfig = HashMap<KeyMode, HashMap<Action, KeyBinding>>;
+
+/// 各模式的默认快捷键，顺序即帮助界面中的显示顺序
+fn default_bindings(mode: KeyMode) -> &'static [(Action, &'static [&'static str])] {
+    const IMAGE_ACTIONS: [(Action, &[&str]); 7] = [
+        (Action::ZoomIn, &["+", "="]),
+        (Action::ZoomOut, &["-"]),
+        (Action::ToggleActualSize, &["f"]),
+        (Action::ResetZoom, &["0"]),
+        (Action::SaveImage, &["s"]),
+        (Action::CopyImageUrl, &["y"]),
+        (Action::OpenExternal, &["O"]),
+    ];
+
+    match mode {
+        KeyMode::Normal => &[
+            (Action::Down, &["j", "<Down>"]),
+            (Action::Up, &["k", "<Up>"]),
+            (Action::First, &["gg"]),
+            (Action::Last, &["G"]),
+            (Action::Back, &["h", "<Left>"]),
+            (Action::Open, &["l", "<Right>"]),
+            (Action::Select, &["<Enter>"]),
+            (Action::ViewImages, &["i"]),
+            (Action::OpenGallery, &["a"]),
+            (Action::ShowTopics, &["t"]),
+            (Action::ShowCategories, &["c"]),
+            (Action::ShowSettings, &["s"]),
+            (Action::Refresh, &["r"]),
+            (Action::NextPage, &["n"]),
+            (Action::PrevPage, &["p"]),
+            (Action::Search, &["/"]),
+            (Action::SearchNext, &["]"]),
+            (Action::SearchPrev, &["N", "["]),
+            (Action::SearchAllPages, &["A"]),
+            (Action::FilterTopics, &["f"]),
+            (Action::CycleSort, &["o"]),
+            (Action::IgnoreUser, &["xu"]),
+            (Action::IgnoreTopic, &["xt"]),
+            (Action::IgnoreCategory, &["xc"]),
+            (Action::NotificationLevel, &["w"]),
+            (Action::Reply, &["R"]),
+            (Action::EditPost, &["e"]),
+            (Action::DeletePost, &["dd"]),
+            (Action::RecoverPost, &["u"]),
+            (Action::ShowRevisions, &["v"]),
+            (Action::ShowPoll, &["P"]),
+            (Action::ToggleSpoiler, &["b"]),
+            (Action::JumpToParent, &["gp"]),
+            (Action::ToggleReplies, &["gr"]),
+            (Action::QuotePost, &["Q"]),
+            (Action::ToggleThreadView, &["T"]),
+            (Action::ToggleFold, &["za"]),
+            (Action::NextSibling, &["}"]),
+            (Action::PrevSibling, &["{"]),
+            (Action::FirstChild, &["gc"]),
+            (Action::ToggleSummary, &["S"]),
+            (Action::FilterByUser, &["U"]),
+            (Action::ToggleTopicHeader, &["H"]),
+            (Action::OpenPalette, &[":", "<C-p>"]),
+            (Action::ToggleHelp, &["?"]),
+            (Action::Quit, &["q"]),
+        ],
+        KeyMode::Post => &[
+            (Action::Down, &["j", "<Down>"]),
+            (Action::Up, &["k", "<Up>"]),
+            (Action::First, &["gg"]),
+            (Action::NextImage, &["<Tab>", "i"]),
+            (Action::OpenImage, &["o"]),
+            (Action::OpenGallery, &["a"]),
+            (Action::Search, &["/"]),
+            (Action::SearchNext, &["n", "]"]),
+            (Action::SearchPrev, &["N", "["]),
+            (Action::SearchAllPages, &["A"]),
+            (Action::ShowRevisions, &["v"]),
+            (Action::ShowPoll, &["P"]),
+            (Action::ToggleSpoiler, &["b"]),
+            (Action::ToggleDetails, &["za"]),
+            (Action::Back, &["<Enter>", "<Esc>"]),
+            (Action::OpenPalette, &[":", "<C-p>"]),
+            (Action::ToggleHelp, &["?"]),
+        ],
+        KeyMode::Image => &[
+            IMAGE_ACTIONS[0],
+            IMAGE_ACTIONS[1],
+            IMAGE_ACTIONS[2],
+            IMAGE_ACTIONS[3],
+            (Action::PanLeft, &["h", "<Left>"]),
+            (Action::PanRight, &["l", "<Right>"]),
+            (Action::PanUp, &["k", "<Up>"]),
+            (Action::PanDown, &["j", "<Down>"]),
+            IMAGE_ACTIONS[4],
+            IMAGE_ACTIONS[5],
+            IMAGE_ACTIONS[6],
+            (Action::Back, &["<Enter>", "<Esc>", "o"]),
+            (Action::OpenPalette, &[":", "<C-p>"]),
+            (Action::ToggleHelp, &["?"]),
+        ],
+        KeyMode::Gallery => &[
+            (Action::Left, &["h", "<Left>"]),
+            (Action::Right, &["l", "<Right>"]),
+            (Action::Down, &["j", "<Down>"]),
+            (Action::Up, &["k", "<Up>"]),
+            (Action::First, &["gg"]),
+            (Action::Last, &["G"]),
+            (Action::TogglePreview, &["<Enter>", "o"]),
+            (Action::JumpToPost, &["p"]),
+            IMAGE_ACTIONS[4],
+            IMAGE_ACTIONS[5],
+            IMAGE_ACTIONS[6],
+            (Action::Back, &["<Esc>", "a"]),
+            (Action::OpenPalette, &[":", "<C-p>"]),
+            (Action::ToggleHelp, &["?"]),
+        ],
+        // 未放大时 h/l 平移会切换上一张/下一张
+        KeyMode::Preview => &[
+            (Action::Left, &["<Left>"]),
+            (Action::Right, &["<Right>"]),
+            IMAGE_ACTIONS[0],
+            IMAGE_ACTIONS[1],
+            IMAGE_ACTIONS[2],
+            IMAGE_ACTIONS[3],
+            (Action::PanLeft, &["h"]),
+            (Action::PanRight, &["l"]),
+            (Action::PanUp, &["k", "<Up>"]),
+            (Action::PanDown, &["j", "<Down>"]),
+            (Action::TogglePreview, &["<Enter>", "o"]),
+            (Action::JumpToPost, &["p"]),
+            IMAGE_ACTIONS[4],
+            IMAGE_ACTIONS[5],
+            IMAGE_ACTIONS[6],
+            (Action::Back, &["<Esc>", "a"]),
+            (Action::OpenPalette, &[":", "<C-p>"]),
+            (Action::ToggleHelp, &["?"]),
+        ],
+    }
+}
+
+/// 按键序列的匹配结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyMatch {
+    Action(Action),
+    /// 是某个组合键的前缀，需要等待后续按键
+    Pending,
+    None,
+}
+
+/// 当前生效的快捷键表
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<KeyMode, Vec<(Action, Vec<KeySequence>)>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&KeybindingsConfig::new()).expect("默认快捷键不应冲突")
+    }
+}
+
+impl Keymap {
+    /// 在默认快捷键上应用用户配置，并检查冲突
+    ///
+    /// 配置中的操作会替换该操作的全部默认按键，设为空列表即取消绑定。
+    pub fn new(config: &KeybindingsConfig) -> Result<Self, LdUiError> {
+        let mut bindings = HashMap::new();
+        let mut conflicts = Vec::new();
+
+        for mode in KeyMode::ALL {
+            let mut mode_bindings: Vec<(Action, Vec<KeySequence>)> = default_bindings(mode)
+                .iter()
+                .map(|(action, keys)| {
+                    let keys = keys.iter().map(|key| key.parse().expect("默认快捷键格式错误")).collect();
+                    (*action, keys)
+                })
+                .collect();
+
+            if let Some(overrides) = config.get(&mode) {
+                for (action, binding) in overrides {
+                    let entry = mode_bindings
+                        .iter_mut()
+                        .find(|(existing, _)| existing == action)
+                        .ok_or_else(|| {
+                            LdUiError::Config(format!(
+                                "快捷键模式 {} 不支持操作 {}",
+                                config_name(&mode),
+                                config_name(action)
+                            ))
+                        })?;
+                    entry.1 = binding
+                        .keys()
+                        .into_iter()
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()?;
+                }
+            }
+
+            conflicts.extend(find_conflicts(mode, &mode_bindings));
+            bindings.insert(mode, mode_bindings);
+        }
+
+        if !conflicts.is_empty() {
+            return Err(LdUiError::Config(format!("快捷键冲突: {}", conflicts.join("; "))));
+        }
+        Ok(Keymap { bindings })
+    }
+
+    /// 匹配已按下的按键序列
+    pub fn resolve(&self, mode: KeyMode, keys: &[Key]) -> KeyMatch {
+        let mut result = KeyMatch::None;
+        for (action, sequences) in &self.bindings[&mode] {
+            for sequence in sequences {
+                if sequence.0 == keys {
+                    return KeyMatch::Action(*action);
+                }
+                if sequence.0.starts_with(keys) {
+                    result = KeyMatch::Pending;
+                }
+            }
+        }
+        result
+    }
+
+    /// 某个模式下的全部绑定，按帮助界面的显示顺序排列
+    pub fn bindings(&self, mode: KeyMode) -> &[(Action, Vec<KeySequence>)] {
+        &self.bindings[&mode]
+    }
+
+    /// 操作在某个模式下绑定的按键，用 "/" 分隔，未绑定时为 None
+    pub fn describe(&self, mode: KeyMode, action: Action) -> Option<String> {
+        self.bindings(mode)
+            .iter()
+            .find(|(existing, sequences)| *existing == action && !sequences.is_empty())
+            .map(|(_, sequences)| {
+                sequences.iter().map(ToString::to_string).collect::<Vec<_>>().join("/")
+            })
+    }
+}
+
+/// 同一模式下相同或互为前缀的按键序列绑定到不同操作时视为冲突
+fn find_conflicts(mode: KeyMode, bindings: &[(Action, Vec<KeySequence>)]) -> Vec<String> {
+    let all: Vec<(Action, &KeySequence)> = bindings
+        .iter()
+        .flat_map(|(action, sequences)| sequences.iter().map(move |sequence| (*action, sequence)))
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for (i, (action, sequence)) in all.iter().enumerate() {
+        for (other_action, other) in &all[i + 1..] {
+            if action != other_action
+                && (sequence.0.starts_with(&other.0) || other.0.starts_with(&sequence.0))
+            {
+                conflicts.push(format!(
+                    "{} 模式中 \"{}\" ({}) 与 \"{}\" ({})",
+                    config_name(&mode),
+                    sequence,
+                    config_name(action),
+                    other,
+                    config_name(other_action)
+                ));
+            }
+        }
+    }
+    conflicts
+}
+
+/// 模式或操作在配置文件中的名称
+fn config_name<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        Key { code, modifiers }
+    }
+
+    fn sequence(text: &str) -> KeySequence {
+        text.parse().unwrap()
+    }
+
+    fn config(mode: KeyMode, overrides: &[(Action, &[&str])]) -> KeybindingsConfig {
+        let actions = overrides
+            .iter()
+            .map(|(action, keys)| (*action, KeyBinding::Many(keys.iter().map(|key| key.to_string()).collect())))
+            .collect();
+        HashMap::from([(mode, actions)])
+    }
+
+    #[test]
+    fn parses_plain_keys_and_chords() {
+        assert_eq!(sequence("j").0, vec![Key::plain(KeyCode::Char('j'))]);
+        assert_eq!(sequence("G").0, vec![Key::plain(KeyCode::Char('G'))]);
+        assert_eq!(sequence("gg").0, vec![Key::plain(KeyCode::Char('g')); 2]);
+        assert_eq!(sequence("dd").to_string(), "dd");
+    }
+
+    #[test]
+    fn parses_modifiers_and_special_keys() {
+        assert_eq!(sequence("<C-c>").0, vec![key(KeyCode::Char('c'), KeyModifiers::CONTROL)]);
+        assert_eq!(
+            sequence("<C-A-x>").0,
+            vec![key(KeyCode::Char('x'), KeyModifiers::CONTROL | KeyModifiers::ALT)]
+        );
+        assert_eq!(sequence("<Enter>").0, vec![Key::plain(KeyCode::Enter)]);
+        assert_eq!(sequence("<esc>").0, vec![Key::plain(KeyCode::Esc)]);
+        assert_eq!(sequence("<F5>").0, vec![Key::plain(KeyCode::F(5))]);
+        assert_eq!(sequence("<S-Tab>").0, vec![Key::plain(KeyCode::BackTab)]);
+        assert_eq!(
+            sequence("g<Down>").0,
+            vec![Key::plain(KeyCode::Char('g')), Key::plain(KeyCode::Down)]
+        );
+        assert_eq!(sequence("<C-p>").to_string(), "<C-p>");
+    }
+
+    #[test]
+    fn rejects_malformed_sequences() {
+        for text in ["", "<C-p", "<>", "<X-p>", "<Nope>", "<C->"] {
+            assert!(text.parse::<KeySequence>().is_err(), "{:?} 应该无效", text);
+        }
+    }
+
+    #[test]
+    fn overrides_replace_default_keys() {
+        let keymap = Keymap::new(&config(KeyMode::Normal, &[(Action::Quit, &["<C-c>"])])).unwrap();
+        let ctrl_c = [key(KeyCode::Char('c'), KeyModifiers::CONTROL)];
+        assert_eq!(keymap.resolve(KeyMode::Normal, &ctrl_c), KeyMatch::Action(Action::Quit));
+        assert_eq!(keymap.resolve(KeyMode::Normal, &[Key::plain(KeyCode::Char('q'))]), KeyMatch::None);
+        assert_eq!(keymap.describe(KeyMode::Normal, Action::Quit).as_deref(), Some("<C-c>"));
+        // 默认快捷键表不受影响
+        assert_eq!(Keymap::default().describe(KeyMode::Normal, Action::Quit).as_deref(), Some("q"));
+    }
+
+    #[test]
+    fn empty_override_unbinds_action() {
+        let keymap = Keymap::new(&config(KeyMode::Normal, &[(Action::Quit, &[])])).unwrap();
+        assert_eq!(keymap.describe(KeyMode::Normal, Action::Quit), None);
+    }
+
+    #[test]
+    fn chords_wait_for_next_key() {
+        let keymap = Keymap::default();
+        let g = Key::plain(KeyCode::Char('g'));
+        assert_eq!(keymap.resolve(KeyMode::Normal, &[g]), KeyMatch::Pending);
+        assert_eq!(keymap.resolve(KeyMode::Normal, &[g, g]), KeyMatch::Action(Action::First));
+    }
+
+    #[test]
+    fn conflicting_overrides_are_rejected() {
+        let duplicate = config(KeyMode::Normal, &[(Action::Quit, &["j"])]);
+        assert!(Keymap::new(&duplicate).is_err());
+        let prefix = config(KeyMode::Normal, &[(Action::Refresh, &["g"])]);
+        assert!(Keymap::new(&prefix).is_err());
+        let unsupported = config(KeyMode::Image, &[(Action::Reply, &["R"])]);
+        assert!(Keymap::new(&unsupported).is_err());
+    }
+
+    #[test]
+    fn finds_duplicate_and_prefix_conflicts() {
+        let bindings = vec![
+            (Action::Down, vec![sequence("j")]),
+            (Action::Up, vec![sequence("j")]),
+            (Action::Refresh, vec![sequence("g")]),
+            (Action::First, vec![sequence("gg")]),
+            // 同一操作的多个按键互为前缀不算冲突
+            (Action::Last, vec![sequence("G"), sequence("GG")]),
+        ];
+        let conflicts = find_conflicts(KeyMode::Normal, &bindings);
+        assert_eq!(conflicts.len(), 2, "{:?}", conflicts);
+        assert!(conflicts[0].contains("\"j\" (down)") && conflicts[0].contains("(up)"));
+        assert!(conflicts[1].contains("\"g\" (refresh)") && conflicts[1].contains("\"gg\" (first)"));
+    }
+
+    #[test]
+    fn same_key_in_different_modes_is_allowed() {
+        // 图片模式中的 d 与浏览模式中的 dd 互不冲突
+        let keymap = Keymap::new(&config(KeyMode::Image, &[(Action::PanDown, &["d"])])).unwrap();
+        let d = [Key::plain(KeyCode::Char('d'))];
+        assert_eq!(keymap.resolve(KeyMode::Image, &d), KeyMatch::Action(Action::PanDown));
+        assert_eq!(keymap.resolve(KeyMode::Normal, &d), KeyMatch::Pending);
+    }
+}
@@ -3,9 +3,27 @@ pub mod config;
 pub mod error;
 mod log;
 pub mod image;
+pub mod gallery;
+pub mod image_view;
+pub mod external;
+pub mod keymap;
+pub mod theme;
+pub mod mouse;
+pub mod history;
+pub mod palette;
+pub mod search;
+pub mod topic_list;
+pub mod ignore;
+pub mod notification;
+pub mod composer;
+pub mod revision;
+pub mod thread;
+pub mod poll;
+pub mod emoji;
+pub mod html;
 pub mod api_key_generator;
 
-pub use app::{App, AppTab, AppResult, LoadingState};
+pub use app::{App, AppTab, AppResult, LoadingState, SETTINGS_OPTIONS};
 pub use config::Config;
 pub use log::initialize_logging;
 pub use api_key_generator::run_key_generator; 
\ No newline at end of file
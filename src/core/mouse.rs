@@ -0,0 +1,72 @@
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use ratatui::layout::Rect;
+
+/// 两次点击间隔小于该时长时视为双击
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// 可以用鼠标点击的目标
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClickTarget {
+    /// 顶部标签页，序号与标签栏顺序一致
+    Tab(usize),
+    /// 列表中的一项
+    ListItem(usize),
+    /// 完整帖子中的图片按钮或内联图片
+    ImageButton(usize),
+    /// 正文中的链接
+    Link(String),
+    /// 完整帖子中 <details> 的标题行，序号为帖子中的区域序号
+    Details(usize),
+}
+
+/// 上一帧绘制的可点击区域，由绘制时更新
+#[derive(Debug, Default)]
+pub struct HitRegions {
+    regions: RefCell<Vec<(Rect, ClickTarget)>>,
+}
+
+impl HitRegions {
+    pub fn clear(&self) {
+        self.regions.borrow_mut().clear();
+    }
+
+    pub fn push(&self, area: Rect, target: ClickTarget) {
+        if area.width > 0 && area.height > 0 {
+            self.regions.borrow_mut().push((area, target));
+        }
+    }
+
+    /// 找到位于指定位置的目标，后绘制的区域优先
+    pub fn target_at(&self, column: u16, row: u16) -> Option<ClickTarget> {
+        self.regions
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(area, _)| {
+                (area.x..area.x + area.width).contains(&column) && (area.y..area.y + area.height).contains(&row)
+            })
+            .map(|(_, target)| target.clone())
+    }
+}
+
+/// 记录上一次点击，用于识别双击
+#[derive(Debug, Default)]
+pub struct ClickTracker {
+    last: Option<(Instant, ClickTarget)>,
+}
+
+impl ClickTracker {
+    /// 记录一次点击，返回是否与上一次点击构成双击
+    pub fn click(&mut self, target: &ClickTarget) -> bool {
+        let now = Instant::now();
+        let is_double = self
+            .last
+            .as_ref()
+            .is_some_and(|(time, last)| last == target && now.duration_since(*time) < DOUBLE_CLICK_INTERVAL);
+        // 双击后重置，避免连续三次点击被识别为两次双击
+        self.last = if is_double { None } else { Some((now, target.clone())) };
+        is_double
+    }
+}
@@ -0,0 +1,49 @@
+use crate::api::NotificationLevel;
+
+/// 要设置通知级别的主题或分类
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationTarget {
+    Topic(u64),
+    Category(u64),
+}
+
+/// 通知级别选择器
+#[derive(Debug, Clone)]
+pub struct NotificationPicker {
+    pub target: NotificationTarget,
+    /// 主题标题或分类名称
+    pub name: String,
+    pub levels: &'static [NotificationLevel],
+    pub current: Option<NotificationLevel>,
+    pub selected: usize,
+}
+
+impl NotificationPicker {
+    /// 打开选择器，默认选中当前级别，未知时选中常规
+    pub fn new(target: NotificationTarget, name: String, current: Option<NotificationLevel>) -> Self {
+        let levels: &'static [NotificationLevel] = match target {
+            NotificationTarget::Topic(_) => &NotificationLevel::TOPIC_LEVELS,
+            NotificationTarget::Category(_) => &NotificationLevel::CATEGORY_LEVELS,
+        };
+        let selected = levels
+            .iter()
+            .position(|level| Some(*level) == current)
+            .or_else(|| levels.iter().position(|level| *level == NotificationLevel::Regular))
+            .unwrap_or(0);
+        NotificationPicker { target, name, levels, current, selected }
+    }
+
+    pub fn next(&mut self) {
+        if self.selected + 1 < self.levels.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn selected_level(&self) -> NotificationLevel {
+        self.levels[self.selected]
+    }
+}
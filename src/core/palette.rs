@@ -0,0 +1,238 @@
+use crate::core::history::History;
+use crate::core::keymap::{Action, KeyMode, Keymap};
+
+/// 命令面板中的一项
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteItem {
+    Action(Action),
+    Topic(u64),
+    Category(u64),
+    User(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct PaletteEntry {
+    pub item: PaletteItem,
+    pub label: String,
+    /// 操作绑定的按键
+    pub key: Option<String>,
+}
+
+impl PaletteEntry {
+    /// 条目类型，显示在标签前面
+    pub fn kind(&self) -> &'static str {
+        match self.item {
+            PaletteItem::Action(_) => "操作",
+            PaletteItem::Topic(_) => "主题",
+            PaletteItem::Category(_) => "分类",
+            PaletteItem::User(_) => "用户",
+        }
+    }
+}
+
+/// 一条匹配结果：条目序号和匹配到的字符位置
+#[derive(Debug, Clone)]
+pub struct PaletteMatch {
+    pub entry: usize,
+    pub positions: Vec<usize>,
+}
+
+/// 命令面板状态
+#[derive(Debug, Clone)]
+pub struct Palette {
+    /// 打开面板时所处的模式，操作在这个模式下执行
+    pub mode: KeyMode,
+    pub query: String,
+    pub entries: Vec<PaletteEntry>,
+    pub matches: Vec<PaletteMatch>,
+    pub selected: usize,
+}
+
+impl Palette {
+    /// 列出当前模式下的全部操作以及最近访问的主题、分类和用户
+    pub fn new(mode: KeyMode, keymap: &Keymap, history: &History) -> Self {
+        let actions = keymap
+            .bindings(mode)
+            .iter()
+            .filter(|(action, _)| *action != Action::OpenPalette)
+            .map(|(action, _)| PaletteEntry {
+                item: PaletteItem::Action(*action),
+                label: action.description().to_string(),
+                key: keymap.describe(mode, *action),
+            });
+        let topics = history.topics.iter().map(|(id, title)| PaletteEntry {
+            item: PaletteItem::Topic(*id),
+            label: title.clone(),
+            key: None,
+        });
+        let categories = history.categories.iter().map(|(id, name)| PaletteEntry {
+            item: PaletteItem::Category(*id),
+            label: name.clone(),
+            key: None,
+        });
+        let users = history.users.iter().map(|username| PaletteEntry {
+            item: PaletteItem::User(username.clone()),
+            label: username.clone(),
+            key: None,
+        });
+
+        let mut palette = Palette {
+            mode,
+            query: String::new(),
+            entries: actions.chain(topics).chain(categories).chain(users).collect(),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        palette.update_matches();
+        palette
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        self.update_matches();
+    }
+
+    pub fn pop_char(&mut self) {
+        self.query.pop();
+        self.update_matches();
+    }
+
+    pub fn next(&mut self) {
+        if self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// 当前选中的条目
+    pub fn current(&self) -> Option<&PaletteEntry> {
+        self.matches
+            .get(self.selected)
+            .map(|matched| &self.entries[matched.entry])
+    }
+
+    /// 按查询重新筛选，分数高的在前，同分时保持原有顺序
+    fn update_matches(&mut self) {
+        let mut scored: Vec<(i64, PaletteMatch)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(entry, item)| {
+                fuzzy_match(&self.query, &item.label)
+                    .map(|(score, positions)| (score, PaletteMatch { entry, positions }))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.matches = scored.into_iter().map(|(_, matched)| matched).collect();
+        self.selected = 0;
+    }
+}
+
+/// 模糊匹配：查询中的字符按顺序出现在文本中即为匹配（忽略大小写和空格）
+///
+/// 连续匹配和单词开头的匹配得分更高，返回得分和匹配到的字符位置。
+pub fn fuzzy_match(query: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut positions = Vec::new();
+    let mut score = 0i64;
+    let mut next = 0;
+
+    for q in query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase) {
+        let found = (next..chars.len()).find(|&i| chars[i].to_lowercase().eq(std::iter::once(q)))?;
+        score += 1;
+        if found == 0 || !chars[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        if positions.last().is_some_and(|&last| last + 1 == found) {
+            score += 5;
+        }
+        // 跳过的字符越多得分越低
+        score -= (found - next) as i64;
+        positions.push(found);
+        next = found + 1;
+    }
+
+    Some((score, positions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette(labels: &[&str]) -> Palette {
+        let entries = labels
+            .iter()
+            .map(|label| PaletteEntry {
+                item: PaletteItem::User(label.to_string()),
+                label: label.to_string(),
+                key: None,
+            })
+            .collect();
+        Palette {
+            mode: KeyMode::Normal,
+            query: String::new(),
+            entries,
+            matches: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    fn labels(palette: &Palette) -> Vec<&str> {
+        palette
+            .matches
+            .iter()
+            .map(|matched| palette.entries[matched.entry].label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn matches_characters_in_order_ignoring_case_and_spaces() {
+        assert_eq!(fuzzy_match("OpT", "open topic"), Some((21, vec![0, 1, 5])));
+        assert_eq!(
+            fuzzy_match("o t", "open topic").map(|(_, p)| p),
+            Some(vec![0, 5])
+        );
+        assert_eq!(
+            fuzzy_match("回复", "回复主题").map(|(_, p)| p),
+            Some(vec![0, 1])
+        );
+        assert_eq!(fuzzy_match("to", "open"), None);
+        assert_eq!(fuzzy_match("", "open"), Some((0, vec![])));
+    }
+
+    #[test]
+    fn prefers_word_starts_and_consecutive_characters() {
+        let score = |query, text| fuzzy_match(query, text).unwrap().0;
+        // 单词开头
+        assert!(score("t", "topic") > score("t", "list"));
+        assert!(score("nt", "new topic") > score("nt", "content"));
+        // 连续字符
+        assert!(score("top", "topic") > score("top", "toxp"));
+        // 跳过的字符更少
+        assert!(score("p", "xp") > score("p", "xxxp"));
+    }
+
+    #[test]
+    fn sorts_by_score_and_keeps_order_on_ties() {
+        let mut palette = palette(&["stop", "to post", "topic", "Topaz"]);
+        for c in "top".chars() {
+            palette.push_char(c);
+        }
+        assert_eq!(labels(&palette), vec!["to post", "topic", "Topaz", "stop"]);
+
+        palette.next();
+        assert_eq!(
+            palette.current().map(|entry| entry.label.as_str()),
+            Some("topic")
+        );
+        // 修改查询后回到第一项
+        palette.push_char('i');
+        assert_eq!(labels(&palette), vec!["topic"]);
+        assert_eq!(palette.selected, 0);
+        palette.pop_char();
+        assert_eq!(labels(&palette).len(), 4);
+    }
+}
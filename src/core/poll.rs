@@ -0,0 +1,244 @@
+use crate::api::{Poll, PollKind, Post};
+
+const UNSUPPORTED: &str = "不支持这种投票，只能查看";
+
+/// 投票窗口，显示帖子中的一个投票并选择要投的选项
+#[derive(Debug, Clone)]
+pub struct PollVoter {
+    pub post_id: u64,
+    pub post_number: u64,
+    pub poll: Poll,
+    /// 帖子中投票的数量和当前投票的序号，多个投票时用 Tab 切换
+    pub poll_count: usize,
+    pub poll_index: usize,
+    /// 已经投出的选项
+    pub voted: Vec<String>,
+    /// 准备提交的选项
+    pub chosen: Vec<String>,
+    pub selected: usize,
+}
+
+impl PollVoter {
+    /// 打开帖子中的第 `index` 个投票，没有投票时返回 None
+    pub fn new(post: &Post, index: usize) -> Option<Self> {
+        let poll = post.polls.get(index)?.clone();
+        let voted = post.polls_votes.get(&poll.name).cloned().unwrap_or_default();
+        // 默认选中第一个已投的选项
+        let selected = poll
+            .options
+            .iter()
+            .position(|option| voted.contains(&option.id))
+            .unwrap_or(0);
+        Some(PollVoter {
+            post_id: post.id,
+            post_number: post.post_number,
+            poll,
+            poll_count: post.polls.len(),
+            poll_index: index,
+            chosen: voted.clone(),
+            voted,
+            selected,
+        })
+    }
+
+    pub fn next(&mut self) {
+        if self.selected + 1 < self.poll.options.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn has_voted(&self) -> bool {
+        !self.voted.is_empty()
+    }
+
+    /// 选择或取消选中的选项，单选投票直接替换，多选投票不超过最多可选的数量
+    pub fn toggle(&mut self) -> Result<(), String> {
+        let Some(option) = self.poll.options.get(self.selected) else {
+            return Ok(());
+        };
+        if !self.poll.is_supported() {
+            return Err(UNSUPPORTED.to_string());
+        }
+        let id = option.id.clone();
+        if let Some(position) = self.chosen.iter().position(|chosen| *chosen == id) {
+            self.chosen.remove(position);
+            return Ok(());
+        }
+        match self.poll.kind {
+            PollKind::Regular | PollKind::Number | PollKind::Unknown => self.chosen = vec![id],
+            PollKind::Multiple => {
+                let max = self.poll.max_choices();
+                if self.chosen.len() >= max {
+                    return Err(format!("最多只能选择 {} 项", max));
+                }
+                self.chosen.push(id);
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查投票是否还能修改，不能时返回原因
+    pub fn check_open(&self) -> Result<(), String> {
+        if !self.poll.is_supported() {
+            return Err(UNSUPPORTED.to_string());
+        }
+        if self.poll.is_closed() {
+            return Err("投票已结束".to_string());
+        }
+        Ok(())
+    }
+
+    /// 检查能否提交当前选择，不能时返回原因
+    pub fn check_vote(&self) -> Result<(), String> {
+        self.check_open()?;
+        let (min, max) = (self.poll.min_choices(), self.poll.max_choices());
+        if self.chosen.len() < min {
+            return Err(format!("至少需要选择 {} 项", min));
+        }
+        if self.chosen.len() > max {
+            return Err(format!("最多只能选择 {} 项", max));
+        }
+        Ok(())
+    }
+
+    /// 提交或撤回后更新投票和已投的选项
+    pub fn update(&mut self, poll: Poll, voted: Vec<String>) {
+        self.poll = poll;
+        self.chosen = voted.clone();
+        self.voted = voted;
+        self.selected = self.selected.min(self.poll.options.len().saturating_sub(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn poll(kind: &str, extra: Value) -> Value {
+        let mut poll = json!({
+            "name": "poll",
+            "type": kind,
+            "status": "open",
+            "min": null,
+            "max": null,
+            "options": [
+                { "id": "a", "html": "甲", "votes": 1 },
+                { "id": "b", "html": "乙", "votes": 2 },
+                { "id": "c", "html": "丙", "votes": 0 },
+            ],
+            "voters": 3,
+            "close": null,
+        });
+        poll.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        poll
+    }
+
+    fn voter(poll: Value, voted: &[&str]) -> PollVoter {
+        let post: Post = serde_json::from_value(json!({
+            "id": 7,
+            "topic_id": 1,
+            "user_id": 1,
+            "username": "user",
+            "post_number": 3,
+            "created_at": "2024-01-01T00:00:00Z",
+            "cooked": "",
+            "polls": [poll],
+            "polls_votes": { "poll": voted },
+        }))
+        .unwrap();
+        PollVoter::new(&post, 0).unwrap()
+    }
+
+    fn select(voter: &mut PollVoter, index: usize) -> Result<(), String> {
+        voter.selected = index;
+        voter.toggle()
+    }
+
+    #[test]
+    fn opens_with_previous_votes_selected() {
+        let voter = voter(poll("regular", json!({})), &["b"]);
+        assert!(voter.has_voted());
+        assert_eq!(voter.chosen, vec!["b"]);
+        assert_eq!(voter.selected, 1);
+        assert_eq!(
+            (voter.post_id, voter.post_number, voter.poll_count),
+            (7, 3, 1)
+        );
+    }
+
+    #[test]
+    fn single_choice_replaces_and_unselects() {
+        let mut voter = voter(poll("regular", json!({})), &[]);
+        assert!(!voter.has_voted());
+        assert_eq!(voter.check_vote(), Err("至少需要选择 1 项".to_string()));
+
+        select(&mut voter, 0).unwrap();
+        select(&mut voter, 2).unwrap();
+        assert_eq!(voter.chosen, vec!["c"]);
+        assert_eq!(voter.check_vote(), Ok(()));
+
+        select(&mut voter, 2).unwrap();
+        assert!(voter.chosen.is_empty());
+    }
+
+    #[test]
+    fn multiple_choice_respects_min_and_max() {
+        let mut voter = voter(poll("multiple", json!({ "min": 2, "max": 2 })), &[]);
+        select(&mut voter, 0).unwrap();
+        assert_eq!(voter.check_vote(), Err("至少需要选择 2 项".to_string()));
+        select(&mut voter, 1).unwrap();
+        assert_eq!(voter.check_vote(), Ok(()));
+        assert_eq!(select(&mut voter, 2), Err("最多只能选择 2 项".to_string()));
+        assert_eq!(voter.chosen, vec!["a", "b"]);
+
+        select(&mut voter, 0).unwrap();
+        select(&mut voter, 2).unwrap();
+        assert_eq!(voter.chosen, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn closed_and_unknown_polls_are_read_only() {
+        let closed = voter(poll("regular", json!({ "status": "closed" })), &["a"]);
+        assert_eq!(closed.check_vote(), Err("投票已结束".to_string()));
+        let expired = voter(
+            poll("regular", json!({ "close": "2000-01-01T00:00:00Z" })),
+            &[],
+        );
+        assert_eq!(expired.check_open(), Err("投票已结束".to_string()));
+
+        let mut ranked = voter(poll("ranked_choice", json!({})), &[]);
+        assert_eq!(ranked.poll.kind, PollKind::Unknown);
+        assert_eq!(ranked.toggle(), Err(UNSUPPORTED.to_string()));
+        assert_eq!(ranked.check_open(), Err(UNSUPPORTED.to_string()));
+        assert!(ranked.chosen.is_empty());
+    }
+
+    #[test]
+    fn update_records_vote_and_unvote() {
+        let mut voter = voter(poll("regular", json!({})), &[]);
+        voter.selected = 2;
+        voter.toggle().unwrap();
+
+        let voted: Poll = serde_json::from_value(poll("regular", json!({ "voters": 4 }))).unwrap();
+        voter.update(voted, vec!["c".to_string()]);
+        assert!(voter.has_voted());
+        assert_eq!(voter.voted, vec!["c"]);
+        assert_eq!(voter.chosen, vec!["c"]);
+        assert_eq!(voter.poll.voters, 4);
+
+        // 撤回后选项减少时选中位置不越界
+        let mut unvoted: Poll = serde_json::from_value(poll("regular", json!({}))).unwrap();
+        unvoted.options.truncate(2);
+        voter.update(unvoted, Vec::new());
+        assert!(!voter.has_voted());
+        assert!(voter.chosen.is_empty());
+        assert_eq!(voter.selected, 1);
+    }
+}
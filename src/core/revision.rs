@@ -0,0 +1,204 @@
+use scraper::{ElementRef, Html, Node, Selector};
+
+use crate::api::PostRevision;
+
+/// 差异中一段文字的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Same,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffSegment {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+/// 两列对比中的一行，`old` 为修改前，`new` 为修改后
+#[derive(Debug, Clone, Default)]
+pub struct DiffRow {
+    pub old: Vec<DiffSegment>,
+    pub new: Vec<DiffSegment>,
+}
+
+impl DiffRow {
+    pub fn is_changed(&self) -> bool {
+        self.old.iter().chain(&self.new).any(|segment| segment.kind != DiffKind::Same)
+            || segments_text(&self.old) != segments_text(&self.new)
+    }
+}
+
+pub fn segments_text(segments: &[DiffSegment]) -> String {
+    segments.iter().map(|segment| segment.text.as_str()).collect()
+}
+
+/// 修改历史查看器，每次显示一个修改
+#[derive(Debug, Clone)]
+pub struct RevisionView {
+    pub post_number: u64,
+    pub revision: PostRevision,
+    pub title: Option<Vec<DiffSegment>>,
+    pub body: Vec<DiffRow>,
+    /// 左右两列对比，否则上下交替显示
+    pub side_by_side: bool,
+    pub scroll: u16,
+}
+
+impl RevisionView {
+    pub fn new(post_number: u64, revision: PostRevision, side_by_side: bool) -> Self {
+        let title = revision
+            .title_changes
+            .as_ref()
+            .map(|changes| inline_segments(Html::parse_fragment(&changes.inline).root_element()));
+        let body = revision
+            .body_changes
+            .as_ref()
+            .map(|changes| parse_side_by_side(&changes.side_by_side_markdown))
+            .unwrap_or_default();
+        RevisionView {
+            post_number,
+            revision,
+            title,
+            body,
+            side_by_side,
+            scroll: 0,
+        }
+    }
+}
+
+/// 解析 Discourse 的 side_by_side_markdown 表格，每个 <tr> 为一行，两个 <td> 分别为修改前后
+fn parse_side_by_side(html: &str) -> Vec<DiffRow> {
+    let fragment = Html::parse_fragment(html);
+    let row_selector = Selector::parse("tr").unwrap();
+    let cell_selector = Selector::parse("td").unwrap();
+    fragment
+        .select(&row_selector)
+        .map(|row| {
+            let mut cells = row.select(&cell_selector);
+            DiffRow {
+                old: cells.next().map(|cell| cell_segments(cell, DiffKind::Removed)).unwrap_or_default(),
+                new: cells.next().map(|cell| cell_segments(cell, DiffKind::Added)).unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+/// 整行增删时单元格只有 diff-del/diff-ins 类名，没有 <del>/<ins>，此时整行标记为 `whole_line`
+fn cell_segments(cell: ElementRef, whole_line: DiffKind) -> Vec<DiffSegment> {
+    let mut segments = inline_segments(cell);
+    let class = cell.value().attr("class").unwrap_or_default();
+    let marked = class.contains("diff-del") || class.contains("diff-ins");
+    if marked && segments.iter().all(|segment| segment.kind == DiffKind::Same) {
+        for segment in &mut segments {
+            segment.kind = whole_line;
+        }
+    }
+    segments
+}
+
+/// 按 <ins>/<del> 把元素中的文字拆分为若干段
+fn inline_segments(element: ElementRef) -> Vec<DiffSegment> {
+    let mut segments: Vec<DiffSegment> = Vec::new();
+    for node in element.descendants() {
+        let Node::Text(text) = node.value() else { continue };
+        let kind = node
+            .ancestors()
+            .take_while(|ancestor| ancestor.id() != element.id())
+            .filter_map(ElementRef::wrap)
+            .find_map(|ancestor| match ancestor.value().name() {
+                "ins" => Some(DiffKind::Added),
+                "del" => Some(DiffKind::Removed),
+                _ => None,
+            })
+            .unwrap_or(DiffKind::Same);
+        match segments.last_mut() {
+            Some(last) if last.kind == kind => last.text.push_str(text),
+            _ => segments.push(DiffSegment { kind, text: text.to_string() }),
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(kind: DiffKind, text: &str) -> DiffSegment {
+        DiffSegment { kind, text: text.to_string() }
+    }
+
+    /// 取出表格中第一个 <td>，与 parse_side_by_side 一样按片段解析
+    fn first_cell(html: &str, check: impl FnOnce(ElementRef)) {
+        let fragment = Html::parse_fragment(html);
+        let selector = Selector::parse("td").unwrap();
+        check(fragment.select(&selector).next().unwrap());
+    }
+
+    #[test]
+    fn whole_line_cells_take_row_kind() {
+        let rows = parse_side_by_side(
+            r#"<table class="markdown"><tr><td class="diff-del">旧的一行</td><td></td></tr><tr><td></td><td class="diff-ins">新的一行</td></tr></table>"#,
+        );
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].old, vec![segment(DiffKind::Removed, "旧的一行")]);
+        assert!(rows[0].new.is_empty());
+        assert!(rows[1].old.is_empty());
+        assert_eq!(rows[1].new, vec![segment(DiffKind::Added, "新的一行")]);
+        assert!(rows.iter().all(DiffRow::is_changed));
+    }
+
+    #[test]
+    fn inline_changes_split_segments() {
+        let rows = parse_side_by_side(
+            r#"<table class="markdown"><tr><td class="diff-del">你好 <del>世界</del>！</td><td class="diff-ins">你好 <ins>朋友</ins>！</td></tr></table>"#,
+        );
+        assert_eq!(
+            rows[0].old,
+            vec![segment(DiffKind::Same, "你好 "), segment(DiffKind::Removed, "世界"), segment(DiffKind::Same, "！")]
+        );
+        assert_eq!(
+            rows[0].new,
+            vec![segment(DiffKind::Same, "你好 "), segment(DiffKind::Added, "朋友"), segment(DiffKind::Same, "！")]
+        );
+    }
+
+    #[test]
+    fn unchanged_rows_are_not_changed() {
+        let rows = parse_side_by_side(r#"<table class="markdown"><tr><td>不变</td><td>不变</td></tr></table>"#);
+        assert_eq!(rows[0].old, vec![segment(DiffKind::Same, "不变")]);
+        assert!(!rows[0].is_changed());
+    }
+
+    #[test]
+    fn nested_markup_uses_nearest_change() {
+        first_cell(
+            r#"<table><tr><td class="diff-ins">a <ins>b <strong>c</strong></ins> <del><em>d</em></del></td></tr></table>"#,
+            |cell| {
+                assert_eq!(
+                    cell_segments(cell, DiffKind::Added),
+                    vec![
+                        segment(DiffKind::Same, "a "),
+                        segment(DiffKind::Added, "b c"),
+                        segment(DiffKind::Same, " "),
+                        segment(DiffKind::Removed, "d"),
+                    ]
+                );
+            },
+        );
+        first_cell(r#"<table><tr><td><del>x <ins>y</ins></del></td></tr></table>"#, |cell| {
+            assert_eq!(
+                cell_segments(cell, DiffKind::Removed),
+                vec![segment(DiffKind::Removed, "x "), segment(DiffKind::Added, "y")]
+            );
+        });
+    }
+
+    #[test]
+    fn unmarked_cells_stay_unchanged() {
+        first_cell(r#"<table><tr><td>普通</td></tr></table>"#, |cell| {
+            assert_eq!(cell_segments(cell, DiffKind::Removed), vec![segment(DiffKind::Same, "普通")]);
+        });
+    }
+}
@@ -0,0 +1,328 @@
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::api::Post;
+use crate::core::html::{render_post, ContentBlock, Spoiler};
+
+/// 搜索结果中的一处匹配，位置为渲染后文本块中的字符偏移
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub page: u32,
+    pub post_id: u64,
+    pub block: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 已渲染的帖子文本，只保留文字块，图片块为 None 以保持块序号与渲染结果一致
+#[derive(Debug, Clone)]
+struct PostText {
+    post_id: u64,
+    blocks: Vec<Option<Vec<char>>>,
+    spoilers: Vec<Spoiler>,
+}
+
+impl PostText {
+    /// 匹配是否与剧透内容重叠
+    fn in_spoiler(&self, block: usize, start: usize, end: usize) -> bool {
+        self.spoilers
+            .iter()
+            .any(|spoiler| spoiler.block == block && spoiler.start < end && start < spoiler.end)
+    }
+}
+
+/// 主题内搜索：在已加载的各页帖子的渲染文本中查找，忽略大小写
+#[derive(Debug, Clone)]
+pub struct TopicSearch {
+    pub topic_id: u64,
+    pub query: String,
+    /// 正在输入搜索内容
+    pub editing: bool,
+    pages: BTreeMap<u32, Vec<PostText>>,
+    /// 已显示剧透的帖子，其余帖子中剧透内容里的文字不参与匹配
+    revealed_spoilers: HashSet<u64>,
+    pub matches: Vec<SearchMatch>,
+    pub current: Option<usize>,
+    /// 开始搜索时的位置（页码、帖子序号），输入时跳到这之后的第一处匹配
+    origin: (u32, usize),
+    /// 需要把当前匹配滚动到完整帖子视图的可见区域内
+    pub reveal: Cell<bool>,
+    /// 绘制时计算出的显示当前匹配所需的滚动位置
+    pub revealed_scroll: Cell<Option<u16>>,
+}
+
+impl TopicSearch {
+    pub fn new(topic_id: u64, page: u32, selected: usize) -> Self {
+        TopicSearch {
+            topic_id,
+            query: String::new(),
+            editing: true,
+            pages: BTreeMap::new(),
+            revealed_spoilers: HashSet::new(),
+            matches: Vec::new(),
+            current: None,
+            origin: (page, selected),
+            reveal: Cell::new(false),
+            revealed_scroll: Cell::new(None),
+        }
+    }
+
+    /// 重新输入搜索内容，保留已加载的页面
+    pub fn restart(&mut self, page: u32, selected: usize) {
+        self.query.clear();
+        self.editing = true;
+        self.origin = (page, selected);
+        self.update_matches();
+    }
+
+    /// 加入或更新一页帖子，尽量保持当前匹配不变
+    pub fn set_page(&mut self, page: u32, posts: &[Post]) {
+        let texts = posts
+            .iter()
+            .map(|post| {
+                let rendered = render_post(&post.cooked);
+                PostText {
+                    post_id: post.id,
+                    blocks: rendered
+                        .blocks
+                        .into_iter()
+                        .map(|block| match block {
+                            ContentBlock::Text(text) | ContentBlock::Quote(text) | ContentBlock::Code(text) => {
+                                Some(text.chars().collect())
+                            }
+                            ContentBlock::Image(_) | ContentBlock::Poll(_) | ContentBlock::Onebox(_) => None,
+                        })
+                        .collect(),
+                    spoilers: rendered.spoilers,
+                }
+            })
+            .collect();
+        self.pages.insert(page, texts);
+        self.refresh_matches();
+    }
+
+    /// 更新已显示剧透的帖子，尽量保持当前匹配不变
+    pub fn set_revealed_spoilers(&mut self, revealed: &HashSet<u64>) {
+        self.revealed_spoilers = revealed.clone();
+        self.refresh_matches();
+    }
+
+    /// 重新查找匹配，当前匹配仍然存在时保持不变
+    fn refresh_matches(&mut self) {
+        let current = self.current_match().cloned();
+        self.find_matches();
+        self.current = match current {
+            Some(current) => self.matches.iter().position(|m| *m == current).or(self.current),
+            None => self.first_after_origin(),
+        }
+        .filter(|&index| index < self.matches.len());
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        self.update_matches();
+    }
+
+    pub fn pop_char(&mut self) {
+        self.query.pop();
+        self.update_matches();
+    }
+
+    /// 移动到下一处匹配，到末尾后回到开头
+    pub fn next(&mut self) -> Option<&SearchMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        self.current = Some(self.current.map_or(0, |index| (index + 1) % self.matches.len()));
+        self.current_match()
+    }
+
+    /// 移动到上一处匹配，到开头后回到末尾
+    pub fn prev(&mut self) -> Option<&SearchMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let len = self.matches.len();
+        self.current = Some(self.current.map_or(len - 1, |index| (index + len - 1) % len));
+        self.current_match()
+    }
+
+    pub fn current_match(&self) -> Option<&SearchMatch> {
+        self.current.and_then(|index| self.matches.get(index))
+    }
+
+    /// 某个帖子中的全部匹配，以及其中是否包含当前匹配
+    pub fn matches_in(&self, post_id: u64) -> impl Iterator<Item = (&SearchMatch, bool)> {
+        let current = self.current_match();
+        self.matches
+            .iter()
+            .filter(move |m| m.post_id == post_id)
+            .map(move |m| (m, Some(m) == current))
+    }
+
+    /// 已加载的页数
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    fn update_matches(&mut self) {
+        self.find_matches();
+        self.current = self.first_after_origin();
+    }
+
+    fn first_after_origin(&self) -> Option<usize> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let (page, selected) = self.origin;
+        let position = self.matches.iter().position(|m| {
+            m.page > page
+                || (m.page == page
+                    && self.post_index(m.page, m.post_id).is_some_and(|index| index >= selected))
+        });
+        Some(position.unwrap_or(0))
+    }
+
+    fn post_index(&self, page: u32, post_id: u64) -> Option<usize> {
+        self.pages.get(&page)?.iter().position(|post| post.post_id == post_id)
+    }
+
+    fn find_matches(&mut self) {
+        self.matches.clear();
+        let query: Vec<char> = self.query.chars().flat_map(char::to_lowercase).collect();
+        if query.is_empty() {
+            return;
+        }
+
+        for (page, posts) in &self.pages {
+            for post in posts {
+                for (block, text) in post.blocks.iter().enumerate() {
+                    let Some(text) = text else { continue };
+                    let mut start = 0;
+                    while start + query.len() <= text.len() {
+                        let found = text[start..start + query.len()]
+                            .iter()
+                            .zip(&query)
+                            .all(|(c, q)| c.to_lowercase().eq(std::iter::once(*q)));
+                        let hidden = !self.revealed_spoilers.contains(&post.post_id)
+                            && post.in_spoiler(block, start, start + query.len());
+                        if found && !hidden {
+                            self.matches.push(SearchMatch {
+                                page: *page,
+                                post_id: post.post_id,
+                                block,
+                                start,
+                                end: start + query.len(),
+                            });
+                            start += query.len();
+                        } else {
+                            start += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(id: u64, cooked: &str) -> Post {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "topic_id": 1,
+            "user_id": 1,
+            "username": "user",
+            "post_number": id,
+            "created_at": "2024-01-01T00:00:00Z",
+            "cooked": cooked,
+        }))
+        .unwrap()
+    }
+
+    fn search(query: &str, pages: &[(u32, Vec<Post>)]) -> TopicSearch {
+        let mut search = TopicSearch::new(1, 1, 0);
+        for (page, posts) in pages {
+            search.set_page(*page, posts);
+        }
+        for c in query.chars() {
+            search.push_char(c);
+        }
+        search
+    }
+
+    fn offsets(search: &TopicSearch) -> Vec<(u64, usize, usize, usize)> {
+        search
+            .matches
+            .iter()
+            .map(|m| (m.post_id, m.block, m.start, m.end))
+            .collect()
+    }
+
+    #[test]
+    fn offsets_count_characters_in_cjk_text() {
+        // 整段文字是一个文本块，显示时折行不影响偏移
+        let long = format!("<p>{}中文搜索Rust</p>", "很长的一段话，".repeat(20));
+        let search = search("搜索rust", &[(1, vec![post(1, &long)])]);
+        assert_eq!(offsets(&search), vec![(1, 0, 142, 148)]);
+    }
+
+    #[test]
+    fn matches_do_not_span_line_breaks() {
+        let search = search("ab", &[(1, vec![post(1, "<p>a<br>b ab</p><p>AB</p>")])]);
+        assert_eq!(offsets(&search), vec![(1, 1, 2, 4), (1, 2, 0, 2)]);
+    }
+
+    #[test]
+    fn matches_follow_page_order_and_wrap_around() {
+        let mut search = search(
+            "词",
+            &[
+                (2, vec![post(3, "<p>词</p>")]),
+                (1, vec![post(1, "<p>词 词</p>"), post(2, "<p>无</p>")]),
+            ],
+        );
+        assert_eq!(
+            search
+                .matches
+                .iter()
+                .map(|m| (m.page, m.post_id))
+                .collect::<Vec<_>>(),
+            vec![(1, 1), (1, 1), (2, 3)]
+        );
+        assert_eq!(search.current, Some(0));
+        assert_eq!(search.prev().map(|m| m.post_id), Some(3));
+        assert_eq!(search.next().map(|m| m.start), Some(0));
+        assert_eq!(search.next().map(|m| m.start), Some(2));
+    }
+
+    #[test]
+    fn hidden_spoilers_are_not_searched() {
+        let cooked = r#"<p>答案 <span class="spoiled">答案是 42</span></p>"#;
+        let mut search = search("答案", &[(1, vec![post(1, cooked)])]);
+        assert_eq!(offsets(&search), vec![(1, 0, 0, 2)]);
+
+        search.set_revealed_spoilers(&HashSet::from([1]));
+        assert_eq!(offsets(&search), vec![(1, 0, 0, 2), (1, 0, 3, 5)]);
+        search.set_revealed_spoilers(&HashSet::new());
+        assert_eq!(search.matches.len(), 1);
+    }
+
+    #[test]
+    fn restart_keeps_loaded_pages() {
+        let mut search = search(
+            "x",
+            &[
+                (1, vec![post(1, "<p>x</p>")]),
+                (2, vec![post(2, "<p>x</p>")]),
+            ],
+        );
+        search.restart(2, 0);
+        assert!(search.matches.is_empty());
+        search.push_char('x');
+        assert_eq!(search.page_count(), 2);
+        assert_eq!(search.current_match().map(|m| m.post_id), Some(2));
+    }
+}
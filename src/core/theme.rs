@@ -0,0 +1,472 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+
+use crate::core::error::LdUiError;
+
+/// 内置主题名称，顺序即设置页中切换的顺序
+pub const BUILTIN_THEMES: &[&str] = &["dark", "light", "high-contrast"];
+
+/// 主题中可配置的样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeSlot {
+    /// 正文
+    Text,
+    /// 用户名、弹窗标题等醒目文字
+    Title,
+    /// 时间、计数等次要信息
+    Meta,
+    /// 分隔线、占位符等弱化内容
+    Muted,
+    /// 操作提示
+    Accent,
+    /// 列表选中项
+    Selection,
+    Error,
+    Success,
+    /// 帖子中的引用
+    Quote,
+    /// 帖子中的代码块
+    Code,
+    /// 链接、标签和图片按钮
+    Link,
+    /// 有未读帖子的主题
+    Unread,
+    /// 图片查看器等弹窗的边框
+    Border,
+    /// 搜索匹配的文字
+    Search,
+    /// 当前所在的搜索匹配
+    SearchCurrent,
+    /// 修改历史中新增的内容
+    DiffAdded,
+    /// 修改历史中删除的内容
+    DiffRemoved,
+}
+
+/// 终端支持的颜色数量
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum ColorDepth {
+    /// 根据 COLORTERM 和 TERM 环境变量检测
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Indexed,
+    #[serde(rename = "16")]
+    Basic,
+}
+
+impl ColorDepth {
+    /// 将 Auto 解析为实际的颜色深度
+    pub fn resolve(self) -> ColorDepth {
+        if self != ColorDepth::Auto {
+            return self;
+        }
+
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        if colorterm.contains("truecolor") || colorterm.contains("24bit") {
+            return ColorDepth::TrueColor;
+        }
+        let term = std::env::var("TERM").unwrap_or_default();
+        if term.contains("256color") {
+            ColorDepth::Indexed
+        } else {
+            ColorDepth::Basic
+        }
+    }
+}
+
+/// 配置文件中的一个样式，颜色可以是名称（如 "yellow"）、调色板序号或 "#rrggbb"
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StyleConfig {
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl StyleConfig {
+    fn to_style(&self) -> Result<Style, LdUiError> {
+        let parse = |color: &str| {
+            Color::from_str(color)
+                .map_err(|_| LdUiError::Config(format!("无效的主题颜色: \"{}\"", color)))
+        };
+
+        let mut style = Style::default();
+        if let Some(fg) = &self.fg {
+            style = style.fg(parse(fg)?);
+        }
+        if let Some(bg) = &self.bg {
+            style = style.bg(parse(bg)?);
+        }
+        if self.bold {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        if self.italic {
+            style = style.add_modifier(Modifier::ITALIC);
+        }
+        if self.underline {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        Ok(style)
+    }
+}
+
+/// 配置文件中的自定义主题，在内置主题的基础上覆盖部分样式
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct UserTheme {
+    /// 基于哪个内置主题，默认为 dark
+    pub base: Option<String>,
+    #[serde(flatten)]
+    pub styles: HashMap<ThemeSlot, StyleConfig>,
+}
+
+/// 配置文件中的 [theme] 部分
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// 当前使用的主题
+    pub name: String,
+    pub color_depth: ColorDepth,
+    /// 自定义主题：[theme.custom.<名称>]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub custom: HashMap<String, UserTheme>,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        ThemeConfig {
+            name: "dark".to_string(),
+            color_depth: ColorDepth::Auto,
+            custom: HashMap::new(),
+        }
+    }
+}
+
+impl ThemeConfig {
+    /// 全部可用的主题名称，内置主题在前
+    pub fn theme_names(&self) -> Vec<String> {
+        let mut custom: Vec<String> = self.custom.keys().cloned().collect();
+        custom.sort();
+        BUILTIN_THEMES.iter().map(|name| name.to_string()).chain(custom).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    pub text: Style,
+    pub title: Style,
+    pub meta: Style,
+    pub muted: Style,
+    pub accent: Style,
+    pub selection: Style,
+    pub error: Style,
+    pub success: Style,
+    pub quote: Style,
+    pub code: Style,
+    pub link: Style,
+    pub unread: Style,
+    pub border: Style,
+    pub search: Style,
+    pub search_current: Style,
+    pub diff_added: Style,
+    pub diff_removed: Style,
+    /// 绘制时使用的颜色深度，用于转换分类颜色等动态颜色
+    pub color_depth: ColorDepth,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            name: "dark".to_string(),
+            text: Style::default().fg(Color::White),
+            title: Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            meta: Style::default().fg(Color::Gray),
+            muted: Style::default().fg(Color::DarkGray),
+            accent: Style::default().fg(Color::Yellow),
+            selection: Style::default()
+                .bg(Color::Yellow)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            error: Style::default().fg(Color::Red),
+            success: Style::default().fg(Color::Green),
+            quote: Style::default().fg(Color::Gray).add_modifier(Modifier::ITALIC),
+            code: Style::default().fg(Color::LightGreen),
+            link: Style::default().fg(Color::Cyan),
+            unread: Style::default().fg(Color::LightCyan).add_modifier(Modifier::BOLD),
+            border: Style::default().fg(Color::Cyan),
+            search: Style::default().bg(Color::Blue).fg(Color::White),
+            search_current: Style::default()
+                .bg(Color::LightMagenta)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            diff_added: Style::default().fg(Color::LightGreen),
+            diff_removed: Style::default().fg(Color::LightRed).add_modifier(Modifier::CROSSED_OUT),
+            color_depth: ColorDepth::TrueColor,
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            name: "light".to_string(),
+            text: Style::default().fg(Color::Black),
+            title: Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            meta: Style::default().fg(Color::DarkGray),
+            muted: Style::default().fg(Color::Gray),
+            accent: Style::default().fg(Color::Magenta),
+            selection: Style::default()
+                .bg(Color::Blue)
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+            error: Style::default().fg(Color::Red),
+            success: Style::default().fg(Color::Green),
+            quote: Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+            code: Style::default().fg(Color::Magenta),
+            link: Style::default().fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+            unread: Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            border: Style::default().fg(Color::Blue),
+            search: Style::default().bg(Color::LightYellow).fg(Color::Black),
+            search_current: Style::default()
+                .bg(Color::Magenta)
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+            diff_added: Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+            diff_removed: Style::default().fg(Color::Red).add_modifier(Modifier::CROSSED_OUT),
+            color_depth: ColorDepth::TrueColor,
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Theme {
+            name: "high-contrast".to_string(),
+            text: Style::default().fg(Color::White),
+            title: Style::default().fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+            meta: Style::default().fg(Color::White),
+            muted: Style::default().fg(Color::Gray),
+            accent: Style::default().fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+            selection: Style::default()
+                .bg(Color::White)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            error: Style::default().fg(Color::LightRed).add_modifier(Modifier::BOLD),
+            success: Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD),
+            quote: Style::default().fg(Color::LightCyan).add_modifier(Modifier::ITALIC),
+            code: Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD),
+            link: Style::default().fg(Color::LightCyan).add_modifier(Modifier::UNDERLINED),
+            unread: Style::default()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            border: Style::default().fg(Color::White),
+            search: Style::default().bg(Color::Yellow).fg(Color::Black),
+            search_current: Style::default()
+                .bg(Color::LightRed)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            diff_added: Style::default().bg(Color::Green).fg(Color::Black),
+            diff_removed: Style::default().bg(Color::Red).fg(Color::White).add_modifier(Modifier::CROSSED_OUT),
+            color_depth: ColorDepth::TrueColor,
+        }
+    }
+
+    fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// 按名称加载内置或自定义主题，并转换为终端支持的颜色
+    pub fn load(config: &ThemeConfig, name: &str) -> Result<Self, LdUiError> {
+        let mut theme = match Self::builtin(name) {
+            Some(theme) => theme,
+            None => {
+                let user_theme = config
+                    .custom
+                    .get(name)
+                    .ok_or_else(|| LdUiError::Config(format!("未知的主题: \"{}\"", name)))?;
+                let base = user_theme.base.as_deref().unwrap_or("dark");
+                let mut theme = Self::builtin(base).ok_or_else(|| {
+                    LdUiError::Config(format!("主题 \"{}\" 的基础主题 \"{}\" 不是内置主题", name, base))
+                })?;
+                for (slot, style) in &user_theme.styles {
+                    *theme.slot_mut(*slot) = style.to_style()?;
+                }
+                theme.name = name.to_string();
+                theme
+            }
+        };
+
+        theme.color_depth = config.color_depth.resolve();
+        theme.adapt();
+        Ok(theme)
+    }
+
+    /// 将任意颜色转换为当前终端可显示的颜色
+    pub fn color(&self, color: Color) -> Color {
+        adapt_color(color, self.color_depth)
+    }
+
+    fn slot_mut(&mut self, slot: ThemeSlot) -> &mut Style {
+        match slot {
+            ThemeSlot::Text => &mut self.text,
+            ThemeSlot::Title => &mut self.title,
+            ThemeSlot::Meta => &mut self.meta,
+            ThemeSlot::Muted => &mut self.muted,
+            ThemeSlot::Accent => &mut self.accent,
+            ThemeSlot::Selection => &mut self.selection,
+            ThemeSlot::Error => &mut self.error,
+            ThemeSlot::Success => &mut self.success,
+            ThemeSlot::Quote => &mut self.quote,
+            ThemeSlot::Code => &mut self.code,
+            ThemeSlot::Link => &mut self.link,
+            ThemeSlot::Unread => &mut self.unread,
+            ThemeSlot::Border => &mut self.border,
+            ThemeSlot::Search => &mut self.search,
+            ThemeSlot::SearchCurrent => &mut self.search_current,
+            ThemeSlot::DiffAdded => &mut self.diff_added,
+            ThemeSlot::DiffRemoved => &mut self.diff_removed,
+        }
+    }
+
+    /// 将全部样式中的颜色转换为当前颜色深度下可显示的颜色
+    fn adapt(&mut self) {
+        let depth = self.color_depth;
+        for style in [
+            &mut self.text,
+            &mut self.title,
+            &mut self.meta,
+            &mut self.muted,
+            &mut self.accent,
+            &mut self.selection,
+            &mut self.error,
+            &mut self.success,
+            &mut self.quote,
+            &mut self.code,
+            &mut self.link,
+            &mut self.unread,
+            &mut self.border,
+            &mut self.search,
+            &mut self.search_current,
+            &mut self.diff_added,
+            &mut self.diff_removed,
+        ] {
+            style.fg = style.fg.map(|color| adapt_color(color, depth));
+            style.bg = style.bg.map(|color| adapt_color(color, depth));
+        }
+    }
+
+}
+
+/// 解析 Discourse 使用的十六进制颜色，支持 "#rrggbb"、"rrggbb" 和 "rgb" 简写
+pub fn parse_hex_color(hex: &str) -> Option<Color> {
+    let hex = hex.trim().trim_start_matches('#');
+    let expanded: String = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect(),
+        6 => hex.to_string(),
+        _ => return None,
+    };
+    let value = u32::from_str_radix(&expanded, 16).ok()?;
+    Some(Color::Rgb((value >> 16) as u8, (value >> 8) as u8, value as u8))
+}
+
+/// 16 色调色板中各颜色的近似 RGB 值
+const BASIC_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (128, 0, 0)),
+    (Color::Green, (0, 128, 0)),
+    (Color::Yellow, (128, 128, 0)),
+    (Color::Blue, (0, 0, 128)),
+    (Color::Magenta, (128, 0, 128)),
+    (Color::Cyan, (0, 128, 128)),
+    (Color::Gray, (192, 192, 192)),
+    (Color::DarkGray, (128, 128, 128)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (0, 0, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// 256 色调色板中 6x6x6 色块每个分量的取值
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// 将颜色转换为指定颜色深度下最接近的颜色，具名颜色保持不变
+pub fn adapt_color(color: Color, depth: ColorDepth) -> Color {
+    match (color, depth.resolve()) {
+        (Color::Rgb(r, g, b), ColorDepth::Indexed) => Color::Indexed(nearest_indexed((r, g, b))),
+        (Color::Rgb(r, g, b), ColorDepth::Basic) => nearest_basic((r, g, b)),
+        (Color::Indexed(index), ColorDepth::Basic) => nearest_basic(indexed_to_rgb(index)),
+        (color, _) => color,
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+fn nearest_basic(rgb: (u8, u8, u8)) -> Color {
+    BASIC_COLORS
+        .iter()
+        .min_by_key(|(_, candidate)| distance(rgb, *candidate))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+/// 在 256 色调色板的色块和灰阶中找到最接近的颜色
+fn nearest_indexed(rgb: (u8, u8, u8)) -> u8 {
+    let level = |value: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - value as i32).abs())
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube_index = (16 + 36 * r + 6 * g + b) as u8;
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray_step = ((average.saturating_sub(8) + 5) / 10).min(23) as u8;
+    let gray_index = 232 + gray_step;
+
+    if distance(rgb, indexed_to_rgb(gray_index)) < distance(rgb, indexed_to_rgb(cube_index)) {
+        gray_index
+    } else {
+        cube_index
+    }
+}
+
+fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASIC_COLORS[index as usize].1,
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[((i / 6) % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
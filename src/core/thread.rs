@@ -0,0 +1,231 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::api::Post;
+
+/// 按 reply_to_post_number 建立的回复树，节点为帖子在列表中的下标
+///
+/// 所回复的帖子不在已加载的帖子中时，作为顶层节点显示。
+#[derive(Debug, Clone, Default)]
+pub struct ThreadTree {
+    parents: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    roots: Vec<usize>,
+}
+
+/// 帖子列表中的一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreadRow {
+    /// 帖子在列表中的下标
+    pub index: usize,
+    pub depth: usize,
+    /// 第 1 到 depth-1 层的祖先之后是否还有同级帖子，用于绘制竖线
+    pub guides: Vec<bool>,
+    /// 是否为同级中的最后一个
+    pub is_last: bool,
+    /// 折叠时隐藏的帖子数，未折叠时为 0
+    pub folded: usize,
+}
+
+impl ThreadRow {
+    /// 平铺视图中的一行
+    pub fn flat(index: usize) -> Self {
+        ThreadRow { index, depth: 0, guides: Vec::new(), is_last: true, folded: 0 }
+    }
+}
+
+impl ThreadTree {
+    pub fn new(posts: &[Post]) -> Self {
+        // 回复总是在所回复的帖子之后，只接受前面的帖子作为父节点，避免出现环
+        let mut indices: HashMap<u64, usize> = HashMap::with_capacity(posts.len());
+        let parents: Vec<Option<usize>> = posts
+            .iter()
+            .enumerate()
+            .map(|(index, post)| {
+                let parent = post.reply_to_post_number.and_then(|number| indices.get(&number).copied());
+                indices.entry(post.post_number).or_insert(index);
+                parent
+            })
+            .collect();
+
+        let mut children = vec![Vec::new(); posts.len()];
+        let mut roots = Vec::new();
+        for (index, parent) in parents.iter().enumerate() {
+            match parent {
+                Some(parent) => children[*parent].push(index),
+                None => roots.push(index),
+            }
+        }
+        ThreadTree { parents, children, roots }
+    }
+
+    pub fn parent(&self, index: usize) -> Option<usize> {
+        self.parents.get(index).copied().flatten()
+    }
+
+    pub fn children(&self, index: usize) -> &[usize] {
+        self.children.get(index).map_or(&[], Vec::as_slice)
+    }
+
+    /// 同一个父节点下的帖子，包括自身
+    pub fn siblings(&self, index: usize) -> &[usize] {
+        match self.parent(index) {
+            Some(parent) => &self.children[parent],
+            None => &self.roots,
+        }
+    }
+
+    /// 帖子的全部祖先，从父节点开始
+    pub fn ancestors(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(self.parent(index), |&ancestor| self.parent(ancestor))
+    }
+
+    /// 按深度优先顺序列出可见的行，`folded` 中的帖子不显示其回复
+    pub fn rows(&self, posts: &[Post], folded: &HashSet<u64>) -> Vec<ThreadRow> {
+        let mut rows = Vec::new();
+        let mut guides = Vec::new();
+        for (i, &root) in self.roots.iter().enumerate() {
+            self.push_rows(root, i + 1 == self.roots.len(), posts, folded, &mut guides, &mut rows);
+        }
+        rows
+    }
+
+    fn push_rows(
+        &self,
+        index: usize,
+        is_last: bool,
+        posts: &[Post],
+        folded: &HashSet<u64>,
+        guides: &mut Vec<bool>,
+        rows: &mut Vec<ThreadRow>,
+    ) {
+        // 顶层帖子没有连接线，其余帖子的 guides 比深度少一层
+        let depth = if self.parent(index).is_none() { 0 } else { guides.len() + 1 };
+        let is_folded = folded.contains(&posts[index].id) && !self.children[index].is_empty();
+        rows.push(ThreadRow {
+            index,
+            depth,
+            guides: guides.clone(),
+            is_last,
+            folded: if is_folded { self.descendant_count(index) } else { 0 },
+        });
+        if is_folded {
+            return;
+        }
+
+        if depth > 0 {
+            guides.push(!is_last);
+        }
+        let children = &self.children[index];
+        for (i, &child) in children.iter().enumerate() {
+            self.push_rows(child, i + 1 == children.len(), posts, folded, guides, rows);
+        }
+        if depth > 0 {
+            guides.pop();
+        }
+    }
+
+    fn descendant_count(&self, index: usize) -> usize {
+        self.children[index]
+            .iter()
+            .map(|&child| 1 + self.descendant_count(child))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 帖子 ID 为楼层号乘以 10，与下标和楼层都不同
+    fn post(post_number: u64, reply_to_post_number: Option<u64>) -> Post {
+        serde_json::from_value(serde_json::json!({
+            "id": post_number * 10,
+            "topic_id": 1,
+            "user_id": 1,
+            "username": "user",
+            "post_number": post_number,
+            "created_at": "2024-01-01T00:00:00Z",
+            "cooked": "",
+            "reply_to_post_number": reply_to_post_number,
+        }))
+        .unwrap()
+    }
+
+    /// 1 ─┬─ 2 ── 3 ── 7
+    ///    └─ 4
+    /// 5
+    /// 6（回复未加载的 99 楼）
+    fn posts() -> Vec<Post> {
+        vec![
+            post(1, None),
+            post(2, Some(1)),
+            post(3, Some(2)),
+            post(4, Some(1)),
+            post(5, None),
+            post(6, Some(99)),
+            post(7, Some(3)),
+        ]
+    }
+
+    fn shape(rows: &[ThreadRow]) -> Vec<(usize, usize, bool, usize)> {
+        rows.iter().map(|row| (row.index, row.depth, row.is_last, row.folded)).collect()
+    }
+
+    #[test]
+    fn rows_follow_reply_tree() {
+        let posts = posts();
+        let tree = ThreadTree::new(&posts);
+        let rows = tree.rows(&posts, &HashSet::new());
+        assert_eq!(
+            shape(&rows),
+            vec![
+                (0, 0, false, 0),
+                (1, 1, false, 0),
+                (2, 2, true, 0),
+                (6, 3, true, 0),
+                (3, 1, true, 0),
+                (4, 0, false, 0),
+                (5, 0, true, 0),
+            ]
+        );
+        assert_eq!(rows[3].guides, vec![true, false]);
+        assert_eq!(rows[4].guides, Vec::<bool>::new());
+    }
+
+    #[test]
+    fn siblings_children_and_ancestors() {
+        let posts = posts();
+        let tree = ThreadTree::new(&posts);
+        assert_eq!(tree.siblings(1), &[1, 3]);
+        assert_eq!(tree.siblings(0), &[0, 4, 5]);
+        assert_eq!(tree.siblings(5), &[0, 4, 5]);
+        assert_eq!(tree.siblings(6), &[6]);
+        assert_eq!(tree.children(0), &[1, 3]);
+        assert!(tree.children(3).is_empty());
+        assert_eq!(tree.ancestors(6).collect::<Vec<_>>(), vec![2, 1, 0]);
+        assert_eq!(tree.parent(5), None);
+    }
+
+    #[test]
+    fn folded_posts_hide_descendants() {
+        let posts = posts();
+        let tree = ThreadTree::new(&posts);
+        let folded = HashSet::from([posts[1].id, posts[3].id]);
+        let rows = tree.rows(&posts, &folded);
+        // 没有回复的帖子即使在折叠集合中也不显示为折叠
+        assert_eq!(
+            shape(&rows),
+            vec![(0, 0, false, 0), (1, 1, false, 2), (3, 1, true, 0), (4, 0, false, 0), (5, 0, true, 0)]
+        );
+    }
+
+    #[test]
+    fn only_earlier_posts_become_parents() {
+        let posts = vec![post(1, None), post(3, Some(2)), post(2, Some(3))];
+        let tree = ThreadTree::new(&posts);
+        assert_eq!(tree.parent(1), None);
+        assert_eq!(tree.parent(2), Some(1));
+        let rows = tree.rows(&posts, &HashSet::new());
+        assert_eq!(shape(&rows), vec![(0, 0, false, 0), (1, 0, true, 0), (2, 1, true, 0)]);
+    }
+}
@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Category, Topic};
+use crate::core::ignore::IgnoreRules;
+
+/// 主题列表的排序方式，均为降序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopicSort {
+    /// 最近活动，即服务器返回的顺序
+    #[default]
+    Activity,
+    Created,
+    Replies,
+    Views,
+    Likes,
+}
+
+impl TopicSort {
+    pub const ALL: [TopicSort; 5] = [
+        TopicSort::Activity,
+        TopicSort::Created,
+        TopicSort::Replies,
+        TopicSort::Views,
+        TopicSort::Likes,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            TopicSort::Activity => "最近活动",
+            TopicSort::Created => "创建时间",
+            TopicSort::Replies => "回复数",
+            TopicSort::Views => "浏览数",
+            TopicSort::Likes => "点赞数",
+        }
+    }
+
+    /// 切换到下一种排序方式
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|sort| sort == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// 主题列表中标题之外可显示的列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopicColumn {
+    Replies,
+    Views,
+    Likes,
+    /// 最近活动时间
+    Activity,
+    Category,
+    Tags,
+}
+
+impl TopicColumn {
+    pub fn title(&self) -> &'static str {
+        match self {
+            TopicColumn::Replies => "回复",
+            TopicColumn::Views => "浏览",
+            TopicColumn::Likes => "点赞",
+            TopicColumn::Activity => "活动",
+            TopicColumn::Category => "分类",
+            TopicColumn::Tags => "标签",
+        }
+    }
+
+    /// 列的显示宽度
+    pub fn width(&self) -> u16 {
+        match self {
+            TopicColumn::Replies | TopicColumn::Likes => 6,
+            TopicColumn::Views => 7,
+            TopicColumn::Activity => 10,
+            TopicColumn::Category => 12,
+            TopicColumn::Tags => 16,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TopicListConfig {
+    /// 默认排序方式
+    pub sort: TopicSort,
+    /// 标题之外显示的列，终端宽度不够时从后往前隐藏
+    pub columns: Vec<TopicColumn>,
+}
+
+impl Default for TopicListConfig {
+    fn default() -> Self {
+        TopicListConfig {
+            sort: TopicSort::default(),
+            columns: vec![
+                TopicColumn::Category,
+                TopicColumn::Replies,
+                TopicColumn::Views,
+                TopicColumn::Activity,
+                TopicColumn::Likes,
+                TopicColumn::Tags,
+            ],
+        }
+    }
+}
+
+/// 主题列表的筛选条件
+///
+/// 用空格分隔多个条件，全部满足才显示：`#标签`、`@用户`、`分类:名称`，其他文字匹配标题，均忽略大小写。
+#[derive(Debug, Clone, Default)]
+pub struct TopicFilter {
+    pub query: String,
+    /// 正在输入筛选条件
+    pub editing: bool,
+}
+
+impl TopicFilter {
+    pub fn is_active(&self) -> bool {
+        !self.query.trim().is_empty()
+    }
+
+    pub fn matches(&self, topic: &Topic, categories: &[Category]) -> bool {
+        self.query.split_whitespace().all(|term| {
+            let term = term.to_lowercase();
+            if let Some(tag) = term.strip_prefix('#') {
+                topic
+                    .tags
+                    .iter()
+                    .any(|existing| existing.to_lowercase().contains(tag))
+            } else if let Some(username) = term.strip_prefix('@') {
+                topic
+                    .posters
+                    .iter()
+                    .filter_map(|poster| poster.username.as_deref())
+                    .chain(topic.last_poster_username.as_deref())
+                    .any(|existing| existing.to_lowercase().contains(username))
+            } else if let Some(name) = term.strip_prefix("分类:").or_else(|| term.strip_prefix("category:")) {
+                topic
+                    .category_id
+                    .and_then(|id| categories.iter().find(|category| category.id == id))
+                    .is_some_and(|category| {
+                        category.name.to_lowercase().contains(name) || category.slug.contains(name)
+                    })
+            } else {
+                topic.title.to_lowercase().contains(&term)
+            }
+        })
+    }
+}
+
+/// 去掉被忽略的主题，再按筛选条件和排序方式得到要显示的主题
+pub fn visible_topics<'a>(
+    topics: &'a [Topic],
+    categories: &[Category],
+    ignore: &IgnoreRules,
+    filter: &TopicFilter,
+    sort: TopicSort,
+) -> Vec<&'a Topic> {
+    let mut visible: Vec<&Topic> = topics
+        .iter()
+        .filter(|topic| !ignore.ignores_topic(topic) && filter.matches(topic, categories))
+        .collect();
+    match sort {
+        TopicSort::Activity => {}
+        TopicSort::Created => visible.sort_by_key(|topic| std::cmp::Reverse(topic.created_at)),
+        TopicSort::Replies => visible.sort_by_key(|topic| std::cmp::Reverse(topic.posts_count)),
+        TopicSort::Views => visible.sort_by_key(|topic| std::cmp::Reverse(topic.views)),
+        TopicSort::Likes => visible.sort_by_key(|topic| std::cmp::Reverse(topic.like_count)),
+    }
+    visible
+}
+
+/// 在可用宽度内按配置顺序选择能显示的列，标题至少保留 `min_title_width`
+pub fn fit_columns(columns: &[TopicColumn], width: u16, min_title_width: u16) -> Vec<TopicColumn> {
+    let mut used = min_title_width;
+    columns
+        .iter()
+        .copied()
+        .take_while(|column| {
+            // 每列前有一格间隔
+            used = used.saturating_add(column.width() + 1);
+            used <= width
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ignore::IgnoreConfig;
+
+    fn topic(id: u64, title: &str, counts: (u64, u64, u64), created_day: u32) -> Topic {
+        let (posts_count, views, like_count) = counts;
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": title,
+            "category_id": if id.is_multiple_of(2) { 2 } else { 1 },
+            "posts_count": posts_count,
+            "views": views,
+            "like_count": like_count,
+            "created_at": format!("2024-01-{:02}T00:00:00Z", created_day),
+            "last_posted_at": null,
+            "last_poster_username": format!("last{}", id),
+            "posters": [{ "user_id": id, "primary_group_id": null, "description": null, "username": format!("Poster{}", id) }],
+            "tags": if id <= 2 { vec!["Rust", "linux"] } else { vec!["go"] },
+        }))
+        .unwrap()
+    }
+
+    fn category(id: u64, name: &str, slug: &str) -> Category {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": name,
+            "slug": slug,
+            "topic_count": 0,
+            "post_count": 0,
+            "description": null,
+            "color": "000000",
+            "text_color": "FFFFFF",
+        }))
+        .unwrap()
+    }
+
+    fn topics() -> Vec<Topic> {
+        vec![
+            topic(1, "Rust 异步入门", (10, 500, 3), 3),
+            topic(2, "Linux 内核更新", (30, 200, 9), 1),
+            topic(3, "Go 泛型讨论", (20, 900, 1), 2),
+            topic(4, "闲聊 rust 与 go", (5, 100, 5), 4),
+        ]
+    }
+
+    fn categories() -> Vec<Category> {
+        vec![
+            category(1, "开发调优", "develop"),
+            category(2, "搞七捻三", "chat"),
+        ]
+    }
+
+    fn ids(query: &str, sort: TopicSort) -> Vec<u64> {
+        let topics = topics();
+        let filter = TopicFilter {
+            query: query.to_string(),
+            editing: false,
+        };
+        visible_topics(
+            &topics,
+            &categories(),
+            &IgnoreRules::default(),
+            &filter,
+            sort,
+        )
+        .iter()
+        .map(|topic| topic.id)
+        .collect()
+    }
+
+    #[test]
+    fn filter_terms_match_title_tags_users_and_categories() {
+        assert_eq!(ids("", TopicSort::Activity), vec![1, 2, 3, 4]);
+        assert_eq!(ids("RUST", TopicSort::Activity), vec![1, 4]);
+        assert_eq!(ids("#rust", TopicSort::Activity), vec![1, 2]);
+        assert_eq!(ids("@poster3", TopicSort::Activity), vec![3]);
+        assert_eq!(ids("@last4", TopicSort::Activity), vec![4]);
+        assert_eq!(ids("分类:搞七", TopicSort::Activity), vec![2, 4]);
+        assert_eq!(ids("category:develop", TopicSort::Activity), vec![1, 3]);
+        assert_eq!(ids("#nothing", TopicSort::Activity), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn all_filter_terms_must_match() {
+        assert_eq!(ids("rust 分类:chat", TopicSort::Activity), vec![4]);
+        assert_eq!(ids("#linux  内核", TopicSort::Activity), vec![2]);
+        assert_eq!(ids("#go rust", TopicSort::Activity), vec![4]);
+        assert_eq!(ids("#go 异步", TopicSort::Activity), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn sorts_filtered_topics_in_descending_order() {
+        assert_eq!(ids("", TopicSort::Created), vec![4, 1, 3, 2]);
+        assert_eq!(ids("", TopicSort::Replies), vec![2, 3, 1, 4]);
+        assert_eq!(ids("", TopicSort::Views), vec![3, 1, 2, 4]);
+        assert_eq!(ids("", TopicSort::Likes), vec![2, 4, 1, 3]);
+        assert_eq!(ids("#go", TopicSort::Views), vec![3, 4]);
+        assert_eq!(ids("rust", TopicSort::Likes), vec![4, 1]);
+    }
+
+    #[test]
+    fn ignored_topics_are_hidden_before_filtering() {
+        let topics = topics();
+        let ignore = IgnoreRules::new(&IgnoreConfig {
+            categories: vec![2],
+            ..Default::default()
+        })
+        .unwrap();
+        let filter = TopicFilter {
+            query: "rust".to_string(),
+            editing: false,
+        };
+        let visible = visible_topics(&topics, &categories(), &ignore, &filter, TopicSort::Likes);
+        assert_eq!(
+            visible.iter().map(|topic| topic.id).collect::<Vec<_>>(),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn sort_cycles_through_all_options() {
+        let mut sort = TopicSort::default();
+        for expected in TopicSort::ALL.iter().skip(1).chain(&TopicSort::ALL[..1]) {
+            sort = sort.next();
+            assert_eq!(sort, *expected);
+        }
+    }
+}
@@ -6,7 +6,7 @@ use std::io;
 use std::time::Duration;
 use std::env;
 use crossterm::{
-    event::{self, DisableMouseCapture, Event, KeyCode},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -66,6 +66,10 @@ async fn main() -> color_eyre::Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
+    if config.ui.mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    ui::init_picker();
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
@@ -109,13 +113,17 @@ async fn run_app<B: ratatui::backend::Backend>(
             .unwrap_or_else(|| Duration::from_secs(0));
 
         if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                if key.code == KeyCode::Char('q') && app.should_quit() {
-                    // 在退出前确保屏幕是干净的
-                    terminal.clear()?;
-                    return Ok(());
+            match event::read()? {
+                Event::Key(key) => {
+                    app.handle_key_event(key).await?;
+                    if app.should_quit() {
+                        // 在退出前确保屏幕是干净的
+                        terminal.clear()?;
+                        return Ok(());
+                    }
                 }
-                app.handle_key_event(key).await?;
+                Event::Mouse(mouse) => app.handle_mouse_event(mouse).await?,
+                _ => {}
             }
         }
 
@@ -1,27 +1,38 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Local, Utc};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Tabs, Wrap, Clear},
+    widgets::{Block, Borders, Cell, List, ListItem, ListState, Paragraph, Row, Table, TableState, Tabs, Wrap, Clear},
     Frame,
 };
-use crate::core::{App, AppTab, LoadingState};
-use crate::ui::image_widget::ImageWidget;
-use crate::core::image::extract_image_urls;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use crate::api::{Category, Poll, PollKind, PollResults, Post, SuggestedTopic, Topic, TopicDetails};
+use crate::core::{App, AppTab, LoadingState, SETTINGS_OPTIONS};
+use crate::core::keymap::{Action, KeyMode};
+use crate::core::theme::{parse_hex_color, Theme};
+use crate::core::mouse::ClickTarget;
+use crate::core::composer::Composer;
+use crate::core::notification::NotificationPicker;
+use crate::core::thread::ThreadRow;
+use crate::core::poll::PollVoter;
+use crate::core::revision::{segments_text, DiffKind, DiffSegment, RevisionView};
+use crate::core::palette::Palette;
+use crate::core::search::TopicSearch;
+use crate::core::topic_list::{fit_columns, TopicColumn};
+use crate::ui::image_widget::{font_size, supports_graphics, ImageWidget};
+use crate::core::image_view::{ImageView, Region};
+use crate::core::image::{avatar_url, extract_image_urls, PostImage};
+use crate::core::html::{plain_text, render_post, ContentBlock, Link, Onebox, RenderedPost};
+use crate::core::gallery::{Gallery, GalleryImage};
 use tracing::debug;
 
-/// 检查一行文本是否包含图片尺寸信息
-fn is_image_size_info(line: &str) -> bool {
-    // 检查是否包含乘号(×)和文件大小单位(KB/MB/GB)
-    let has_dimension = line.contains('×');
-    let has_size_unit = line.contains(" KB") || line.contains(" MB") || line.contains(" GB") || line.contains(" B ");
-    
-    // 如果同时包含乘号和文件大小单位，很可能是图片尺寸信息
-    has_dimension && has_size_unit
-}
-
 pub fn draw_ui(f: &mut Frame, app: &App) {
+    // 可点击区域每帧重新记录
+    app.hit_regions.clear();
+    
     // 创建主布局
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -40,6 +51,7 @@ pub fn draw_ui(f: &mut Frame, app: &App) {
     let _title = format!("LDUI - Linux Do 终端客户端 ({})", app.config.discourse.url);
     let tabs = render_tabs(app);
     f.render_widget(tabs, chunks[0]);
+    register_tab_regions(app, chunks[0]);
 
     // 主要内容区域
     match app.current_tab {
@@ -55,13 +67,8 @@ pub fn draw_ui(f: &mut Frame, app: &App) {
     draw_status_bar(f, app, chunks[2]);
     
     // 如果处于输入模式，绘制输入框
-    if app.input_mode {
-        draw_input(f, app);
-    }
-    
-    // 如果显示帮助，绘制帮助窗口
-    if app.show_help {
-        draw_help(f);
+    if let Some(ref composer) = app.composer {
+        draw_composer(f, app, composer);
     }
     
     // 如果正在显示图片，绘制图片
@@ -69,19 +76,52 @@ pub fn draw_ui(f: &mut Frame, app: &App) {
         draw_image(f, app);
     }
     
+    // 如果正在浏览图片画廊，绘制画廊
+    if let Some(ref gallery) = app.gallery {
+        draw_gallery(f, app, gallery);
+    }
+    
+    // 如果显示帮助，绘制帮助窗口，覆盖在图片和画廊之上
+    if app.show_help {
+        draw_help(f, app);
+    }
+    
+    if let Some(ref palette) = app.palette {
+        draw_palette(f, app, palette);
+    }
+    
+    if let Some(ref picker) = app.notification_picker {
+        draw_notification_picker(f, app, picker);
+    }
+    
+    if let Some(ref view) = app.revisions {
+        draw_revisions(f, app, view);
+    }
+    
+    if let Some(ref voter) = app.poll_voter {
+        draw_poll_voter(f, app, voter);
+    }
+    
+    if let Some((_, post_number)) = app.pending_delete {
+        draw_delete_confirm(f, app, post_number);
+    }
+    
     // 如果正在加载，显示加载指示器
     if let LoadingState::Loading = app.loading_state {
-        draw_loading(f);
+        draw_loading(f, &app.theme);
     }
     
     // 如果发生错误，显示错误消息
     if let LoadingState::Error(ref error) = app.loading_state {
-        draw_error(f, error);
+        draw_error(f, &app.theme, error);
     }
 }
 
-fn render_tabs(app: &App) -> Tabs {
-    let titles = vec!["主页", "主题", "分类", "设置"];
+/// 标签栏中的标签，顺序与 ClickTarget::Tab 的序号一致
+const TAB_TITLES: [&str; 4] = ["主页", "主题", "分类", "设置"];
+
+fn render_tabs(app: &App) -> Tabs<'_> {
+    let titles = TAB_TITLES;
     let selected_tab = match app.current_tab {
         AppTab::Home => 0,
         AppTab::Topics => 1,
@@ -92,20 +132,54 @@ fn render_tabs(app: &App) -> Tabs {
 
     let tabs: Vec<Line> = titles
         .iter()
-        .map(|t| Line::from(vec![Span::styled(*t, Style::default().fg(Color::White))]))
+        .map(|t| Line::from(vec![Span::styled(*t, app.theme.text)]))
         .collect();
 
     Tabs::new(tabs)
         .block(Block::default().borders(Borders::ALL).title("DisUI"))
         .select(selected_tab)
-        .style(Style::default().fg(Color::White))
+        .style(app.theme.text)
         .highlight_style(
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
+            app.theme.title,
         )
 }
 
+/// 记录每个标签的点击区域，与 Tabs 的布局一致：左右各一格内边距，标签之间一格分隔符
+fn register_tab_regions(app: &App, area: Rect) {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    let mut x = inner.x;
+    for (index, title) in TAB_TITLES.iter().enumerate() {
+        let width = title.width() as u16 + 2;
+        app.hit_regions.push(
+            Rect { x, y: inner.y, width: width.min(inner.right().saturating_sub(x)), height: inner.height },
+            ClickTarget::Tab(index),
+        );
+        x = x.saturating_add(width + 1);
+        if x >= inner.right() {
+            break;
+        }
+    }
+}
+
+/// 记录列表中可见项的点击区域，`area` 为列表边框内的区域
+fn register_list_items(app: &App, area: Rect, offset: usize, heights: &[usize]) {
+    let rows: Vec<(usize, usize)> = heights.iter().copied().enumerate().collect();
+    register_list_rows(app, area, offset, &rows);
+}
+
+/// 显示顺序与列表顺序不同时使用，`rows` 为按显示顺序排列的（列表下标，高度）
+fn register_list_rows(app: &App, area: Rect, offset: usize, rows: &[(usize, usize)]) {
+    let mut y = area.y;
+    for &(index, height) in rows.iter().skip(offset) {
+        if y >= area.bottom() {
+            break;
+        }
+        let height = (height as u16).min(area.bottom() - y);
+        app.hit_regions.push(Rect { x: area.x, y, width: area.width, height }, ClickTarget::ListItem(index));
+        y += height;
+    }
+}
+
 fn draw_home(f: &mut Frame, _app: &App, area: Rect) {
     let block = Block::default()
         .borders(Borders::ALL)
@@ -138,79 +212,114 @@ fn draw_home(f: &mut Frame, _app: &App, area: Rect) {
     f.render_widget(paragraph, inner_area);
 }
 
+/// 列表底部提示中的翻页按键，如 "，n/p 切换页面"，按键来自当前快捷键配置
+fn page_keys_hint(app: &App) -> String {
+    let next = app.keymap.describe(KeyMode::Normal, Action::NextPage);
+    let prev = app.keymap.describe(KeyMode::Normal, Action::PrevPage);
+    match (next, prev) {
+        (Some(next), Some(prev)) => format!("，{}/{} 切换页面", next, prev),
+        (Some(key), None) | (None, Some(key)) => format!("，{} 切换页面", key),
+        (None, None) => String::new(),
+    }
+}
+
 fn draw_topics(f: &mut Frame, app: &App, area: Rect) {
     // 检查是否有主题
     if app.topics.is_empty() {
         // 如果没有主题，显示提示信息
-        let message = Paragraph::new("没有可显示的主题。\n\n尝试按 'r' 刷新或 'n' 前往下一页。")
-            .style(Style::default().fg(Color::Gray))
+        let mut text = "没有可显示的主题。\n\n尝试按 'r' 刷新".to_string();
+        if let Some(key) = app.keymap.describe(KeyMode::Normal, Action::NextPage) {
+            text.push_str(&format!("或 '{}' 前往下一页", key));
+        }
+        text.push('。');
+        let message = Paragraph::new(text)
+            .style(app.theme.meta)
             .alignment(Alignment::Center);
         f.render_widget(message, area);
         return;
     }
-
-    let items: Vec<ListItem> = app
-        .topics
+    
+    let topics = app.visible_topics();
+    
+    // 有筛选条件时在列表上方显示筛选栏
+    let area = if app.topic_filter.editing || app.topic_filter.is_active() {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(1)].as_ref())
+            .split(area);
+        draw_topic_filter(f, app, topics.len(), chunks[0]);
+        chunks[1]
+    } else {
+        area
+    };
+    
+    let mut title = format!("主题 (第{}页) · 排序: {}", app.page, app.topic_sort.title());
+    let ignored = app.topics.iter().filter(|topic| app.ignore_rules.ignores_topic(topic)).count();
+    if ignored > 0 {
+        title += &format!(" · 已忽略 {} 个", ignored);
+    }
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner_area = block.inner(area);
+    if topics.is_empty() {
+        let message = Paragraph::new("没有符合筛选条件的主题，按 'f' 修改筛选条件")
+            .block(block)
+            .style(app.theme.meta)
+            .alignment(Alignment::Center);
+        f.render_widget(message, area);
+        return;
+    }
+    
+    // 按终端宽度决定显示哪些列，减去高亮符号的宽度
+    let columns = fit_columns(
+        &app.config.topics.columns,
+        inner_area.width.saturating_sub("> ".width() as u16),
+        MIN_TITLE_WIDTH,
+    );
+    let show_badge = !columns.contains(&TopicColumn::Category);
+    
+    let rows: Vec<Row> = topics
         .iter()
         .map(|topic| {
-            let title = Line::from(vec![
-                Span::styled(
-                    format!("{} ", topic.title),
-                    Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(
-                    format!("[回复: {}]", topic.posts_count),
-                    Style::default().fg(Color::Gray),
-                ),
-            ]);
-            
-            let created_at = format_datetime(&topic.created_at);
-            let info = Line::from(vec![
-                Span::styled(
-                    format!("创建于: {} ", created_at),
-                    Style::default().fg(Color::Gray),
-                ),
-                Span::styled(
-                    format!("浏览: {}", topic.views),
-                    Style::default().fg(Color::Gray),
-                ),
-            ]);
-            
-            let tags = if let Some(ref tags) = topic.tags {
-                if !tags.is_empty() {
-                    Line::from(vec![Span::styled(
-                        format!("标签: {}", tags.join(", ")),
-                        Style::default().fg(Color::Cyan),
-                    )])
-                } else {
-                    Line::default()
-                }
-            } else {
-                Line::default()
-            };
-            
-            ListItem::new(vec![title, info, tags])
+            let category = topic
+                .category_id
+                .and_then(|id| app.categories.iter().find(|category| category.id == id));
+            let mut cells = vec![Cell::from(topic_title_line(app, topic, category.filter(|_| show_badge)))];
+            cells.extend(columns.iter().map(|column| topic_cell(app, topic, category, *column)));
+            Row::new(cells)
         })
         .collect();
-
-    let topics_list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title(format!("主题 (第{}页)", app.page)))
-        .highlight_style(
-            Style::default()
-                .bg(Color::Yellow)
-                .fg(Color::Black)
-                .add_modifier(Modifier::BOLD),
-        )
+    
+    let widths: Vec<Constraint> = std::iter::once(Constraint::Fill(1))
+        .chain(columns.iter().map(|column| Constraint::Length(column.width())))
+        .collect();
+    let header = Row::new(
+        std::iter::once("主题").chain(columns.iter().map(TopicColumn::title)).map(Cell::from),
+    )
+    .style(app.theme.meta.add_modifier(Modifier::BOLD));
+    
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(block)
+        .column_spacing(1)
+        .row_highlight_style(app.theme.selection)
         .highlight_symbol("> ");
-
-    let mut state = ListState::default();
-    state.select(Some(app.selected_index));
-    f.render_stateful_widget(topics_list, area, &mut state);
+    
+    let mut state = TableState::default();
+    state.select(Some(app.selected_index.min(topics.len() - 1)));
+    f.render_stateful_widget(table, area, &mut state);
+    
+    // 表头占一行
+    let rows_area = Rect {
+        y: inner_area.y + 1,
+        height: inner_area.height.saturating_sub(1),
+        ..inner_area
+    };
+    register_list_items(app, rows_area, state.offset(), &vec![1; topics.len()]);
     
     // 添加提示信息
-    let hint_text = "按 Enter 查看帖子完整内容，j/k 或 ↓/↑ 选择帖子，n/p 切换页面";
+    let hint_text = format!("按 Enter 打开主题，j/k 或 ↓/↑ 选择，f 筛选，o 切换排序{}", page_keys_hint(app));
     let hint = Paragraph::new(hint_text)
-        .style(Style::default().fg(Color::Gray))
+        .style(app.theme.meta)
         .alignment(Alignment::Center);
         
     let hint_area = Rect {
@@ -223,35 +332,137 @@ fn draw_topics(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(hint, hint_area);
 }
 
+/// 主题列表中标题至少保留的宽度，剩余宽度不够时隐藏靠后的列
+const MIN_TITLE_WIDTH: u16 = 24;
+
+/// 筛选栏：筛选条件、匹配数量和语法提示
+fn draw_topic_filter(f: &mut Frame, app: &App, visible: usize, area: Rect) {
+    let prefix = "筛选: ";
+    let mut spans = vec![
+        Span::styled(prefix, app.theme.accent),
+        Span::styled(app.topic_filter.query.clone(), app.theme.text),
+        Span::styled(format!("  {}/{} 个主题", visible, app.topics.len()), app.theme.meta),
+    ];
+    if app.topic_filter.editing {
+        spans.push(Span::styled("  (#标签 @用户 分类:名称，Enter 确定，Esc 清除)", app.theme.muted));
+        let cursor = prefix.width() + app.topic_filter.query.width();
+        f.set_cursor_position((area.x + cursor as u16, area.y));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// 主题标题，新主题和有未读帖子的主题使用未读样式，不显示分类列时在标题前显示分类徽章
+fn topic_title_line(app: &App, topic: &Topic, category: Option<&Category>) -> Line<'static> {
+    let unread_posts = topic.unread_posts.unwrap_or(0);
+    let is_unread = topic.unseen.unwrap_or(false) || unread_posts > 0;
+    let title_style = if is_unread {
+        app.theme.unread
+    } else {
+        app.theme.text.add_modifier(Modifier::BOLD)
+    };
+    
+    let mut spans = Vec::new();
+    if let Some(category) = category {
+        spans.push(category_badge(&app.theme, category));
+        spans.push(Span::raw(" "));
+    }
+    spans.push(Span::styled(topic.title.clone(), title_style));
+    if unread_posts > 0 {
+        spans.push(Span::styled(format!(" {} 条未读", unread_posts), app.theme.unread));
+    }
+    Line::from(spans)
+}
+
+/// 主题末尾推荐的主题，显示分类、标题、未读数和回复、浏览数
+fn suggested_topic_line(app: &App, topic: &SuggestedTopic) -> Line<'static> {
+    let unread_posts = topic.unread_posts.unwrap_or(0);
+    let title_style = if topic.unseen || unread_posts > 0 {
+        app.theme.unread
+    } else {
+        app.theme.text
+    };
+    
+    let mut spans = Vec::new();
+    if let Some(category) = topic.category_id.and_then(|id| app.categories.iter().find(|c| c.id == id)) {
+        spans.push(category_badge(&app.theme, category));
+        spans.push(Span::raw(" "));
+    }
+    spans.push(Span::styled(topic.title.clone(), title_style));
+    if unread_posts > 0 {
+        spans.push(Span::styled(format!(" {} 条未读", unread_posts), app.theme.unread));
+    }
+    let mut meta = format!(
+        "  {} 回复 · {} 浏览",
+        compact_count(topic.posts_count.saturating_sub(1)),
+        compact_count(topic.views)
+    );
+    if let Some(last_posted_at) = &topic.last_posted_at {
+        meta.push_str(&format!(" · {}", format_age(last_posted_at)));
+    }
+    spans.push(Span::styled(meta, app.theme.meta));
+    Line::from(spans)
+}
+
+/// 主题列表中某一列的内容，数字右对齐
+fn topic_cell(app: &App, topic: &Topic, category: Option<&Category>, column: TopicColumn) -> Cell<'static> {
+    let number = |count: u64| {
+        Cell::from(Line::from(Span::styled(compact_count(count), app.theme.meta)).alignment(Alignment::Right))
+    };
+    match column {
+        TopicColumn::Replies => number(topic.posts_count.saturating_sub(1)),
+        TopicColumn::Views => number(topic.views),
+        TopicColumn::Likes => number(topic.like_count),
+        TopicColumn::Activity => {
+            let last_activity = topic.last_posted_at.unwrap_or(topic.created_at);
+            Cell::from(Span::styled(format_age(&last_activity), app.theme.meta))
+        }
+        // 分类列表中找不到时（如子分类）留空
+        TopicColumn::Category => match category {
+            Some(category) => Cell::from(category_badge(&app.theme, category)),
+            None => Cell::from(""),
+        },
+        TopicColumn::Tags => {
+            let tags = topic.tags.join(", ");
+            Cell::from(Span::styled(tags, app.theme.link))
+        }
+    }
+}
+
+/// 较大的数字缩写为 1.2k、35k、1.2m
+fn compact_count(count: u64) -> String {
+    match count {
+        0..=999 => count.to_string(),
+        1_000..=9_999 => format!("{:.1}k", count as f64 / 1_000.0),
+        10_000..=999_999 => format!("{}k", count / 1_000),
+        _ => format!("{:.1}m", count as f64 / 1_000_000.0),
+    }
+}
+
 fn draw_categories(f: &mut Frame, app: &App, area: Rect) {
     let items: Vec<ListItem> = app
         .categories
         .iter()
         .map(|category| {
-            let title = Line::from(vec![
-                Span::styled(
-                    format!("{} ", category.name),
-                    Style::default()
-                        .fg(parse_color(&category.color))
-                        .add_modifier(Modifier::BOLD),
-                ),
-            ]);
+            let mut title = Line::from(vec![category_badge(&app.theme, category)]);
+            if let Some(level) = category.notification_level {
+                title.push_span(Span::styled(format!(" [{}]", level.title()), app.theme.meta));
+            }
             
             let info = Line::from(vec![
                 Span::styled(
                     format!("主题: {} ", category.topic_count),
-                    Style::default().fg(Color::Gray),
+                    app.theme.meta,
                 ),
                 Span::styled(
                     format!("帖子: {}", category.post_count),
-                    Style::default().fg(Color::Gray),
+                    app.theme.meta,
                 ),
             ]);
             
             let description = if let Some(ref desc) = category.description {
                 Line::from(vec![Span::styled(
                     desc.clone(),
-                    Style::default().fg(Color::White),
+                    app.theme.text,
                 )])
             } else {
                 Line::default()
@@ -261,19 +472,19 @@ fn draw_categories(f: &mut Frame, app: &App, area: Rect) {
         })
         .collect();
 
+    let item_heights: Vec<usize> = items.iter().map(ListItem::height).collect();
     let categories_list = List::new(items)
         .block(Block::default().borders(Borders::ALL).title("分类"))
         .highlight_style(
-            Style::default()
-                .bg(Color::Yellow)
-                .fg(Color::Black)
-                .add_modifier(Modifier::BOLD),
+            app.theme.selection,
         )
         .highlight_symbol("> ");
 
     let mut state = ListState::default();
     state.select(Some(app.selected_index));
     f.render_stateful_widget(categories_list, area, &mut state);
+    let list_area = Block::default().borders(Borders::ALL).inner(area);
+    register_list_items(app, list_area, state.offset(), &item_heights);
 }
 
 fn draw_topic(f: &mut Frame, app: &App, id: u64, area: Rect) {
@@ -285,212 +496,13 @@ fn draw_topic(f: &mut Frame, app: &App, id: u64, area: Rect) {
     if let Some(posts) = app.posts.get(&id) {
         // 如果处于完整帖子查看模式
         if app.viewing_full_post && app.selected_index < posts.len() {
-            let post = &posts[app.selected_index];
-            
-            // 提取图片URL
-            let image_urls = extract_image_urls(&post.cooked);
-            
-            // 创建可用图片映射
-            let mut available_images = Vec::new();
-            for (i, url) in image_urls.iter().enumerate() {
-                if app.image_paths.lock().unwrap().get::<str>(url).is_some() {
-                    available_images.push((i, url.clone()));
-                }
-            }
-            
-            // 创建帖子头部信息
-            let title = format!("帖子 #{} - {}", post.id, post.username);
-            
-            // 简单清理HTML标签
-            let mut cleaned = post.cooked.clone();
-            
-            // 替换一些常见HTML标签为纯文本等价物
-            cleaned = cleaned.replace("<br>", "\n").replace("<br/>", "\n").replace("<br />", "\n");
-            cleaned = cleaned.replace("<p>", "").replace("</p>", "\n");
-            cleaned = cleaned.replace("<strong>", "").replace("</strong>", "");
-            cleaned = cleaned.replace("<em>", "").replace("</em>", "");
-            cleaned = cleaned.replace("&nbsp;", " ");
-            cleaned = cleaned.replace("&lt;", "<").replace("&gt;", ">");
-            cleaned = cleaned.replace("&quot;", "\"").replace("&apos;", "'");
-            cleaned = cleaned.replace("&amp;", "&");
-            
-            // 移除可能的剩余HTML标签 (简单实现，不使用regex)
-            let mut result = String::with_capacity(cleaned.len());
-            let mut in_tag = false;
-            
-            for c in cleaned.chars() {
-                if c == '<' {
-                    in_tag = true;
-                } else if c == '>' {
-                    in_tag = false;
-                } else if !in_tag {
-                    result.push(c);
-                }
-            }
-            
-            let content_text = result;
-            
-            // 处理连续换行符，将多个换行符替换为一个
-            let mut processed_text = String::new();
-            let mut last_char_was_newline = false;
-            
-            for c in content_text.chars() {
-                if c == '\n' {
-                    if !last_char_was_newline {
-                        processed_text.push(c);
-                    }
-                    last_char_was_newline = true;
-                } else {
-                    processed_text.push(c);
-                    last_char_was_newline = false;
-                }
-            }
-            
-            // 提取图片URL
-            let image_urls = extract_image_urls(&post.cooked);
-            let _has_images = !image_urls.is_empty() && image_urls.iter().any(|url| {
-                app.image_paths.lock().unwrap().get(url).is_some()
-            });
-            
-            // 将内容按行分割并过滤掉图片尺寸信息行
-            let lines_iter = processed_text.split('\n')
-                .filter(|line| !is_image_size_info(line));
-            let mut content_lines = Vec::new();
-            
-            // 创建可用图片映射
-            let mut available_images = Vec::new();
-            for (i, url) in image_urls.iter().enumerate() {
-                if app.image_paths.lock().unwrap().get::<str>(url).is_some() {
-                    available_images.push((i, url.clone()));
-                }
-            }
-            
-            // 创建一个简单的映射来找到图片可能在的行号
-            // 这只是一个近似，因为HTML处理后不容易精确定位
-            let mut img_positions = Vec::new();
-            
-            // 计算内容总行数
-            let total_lines = content_text.lines().count();
-            
-            // 为每个图片分配一个位置 - 采用更精确的定位方法
-            if !available_images.is_empty() && total_lines > 0 {
-                // 尝试查找原始HTML中的图片标签位置，并映射到处理后的文本
-                let raw_html = &post.cooked;
-                let _line_counter = 0;
-                let _html_pos = 0;
-                
-                // 创建一个简单的映射来将原始HTML位置转换为处理后的文本行号
-                let mut img_tag_positions = Vec::new();
-                
-                // 查找所有img标签位置
-                for (idx, url) in image_urls.iter().enumerate() {
-                    if let Some(pos) = raw_html.find(&format!("src=\"{}\"", url)) {
-                        img_tag_positions.push((idx, pos));
-                    }
-                }
-                
-                // 按HTML中的位置排序
-                img_tag_positions.sort_by_key(|&(_, pos)| pos);
-                
-                if img_tag_positions.is_empty() {
-                    // 如果无法找到精确位置，退回到均匀分布
-                    let spacing = total_lines / (available_images.len() + 1);
-                    let spacing = spacing.max(3); // 至少间隔3行
-                    
-                    for i in 0..available_images.len() {
-                        let pos = (i + 1) * spacing;
-                        if pos < total_lines {
-                            img_positions.push(pos);
-                        }
-                    }
-                } else {
-                    // 将HTML位置比例映射到文本行
-                    let html_length = raw_html.len();
-                    
-                    for (idx, html_pos) in img_tag_positions {
-                        // 确保这个URL是可用的
-                        if available_images.iter().any(|(i, _)| *i == idx) {
-                            // 计算相对位置并映射到行号
-                            let relative_pos = html_pos as f64 / html_length as f64;
-                            let line_pos = (relative_pos * total_lines as f64) as usize;
-                            let line_pos = line_pos.min(total_lines - 1);
-                            img_positions.push(line_pos);
-                        }
-                    }
-                }
-                
-                debug!("图片位置列表: {:?}", img_positions);
-                debug!("可用图片列表: {}", available_images.len());
-            }
-            
-            // 内容行计数器
-            let mut content_line_counter = 0;
-            let mut img_counter = 0;
-            
-            // 添加帖子内容
-            for line in lines_iter {
-                // 空行处理
-                if line.trim().is_empty() {
-                    content_lines.push(Line::from(Span::raw("")));
-                    content_line_counter += 1;
-                    continue;
-                }
-                
-                // 检查这一行是否应该放置图片按钮
-                if img_counter < img_positions.len() && content_line_counter >= img_positions[img_counter] {
-                    // 在合适的位置插入图片按钮
-                    let button_style = if Some(img_counter) == app.selected_image_button {
-                        Style::default()
-                            .fg(Color::Black)
-                            .bg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default()
-                            .fg(Color::Blue)
-                            .add_modifier(Modifier::ITALIC)
-                    };
-                    
-                    let button_text = format!("[{} 图片 #{} (按o键查看)]", 
-                        if Some(img_counter) == app.selected_image_button { "✓" } else { " " }, 
-                        img_counter + 1
-                    );
-                    content_lines.push(Line::from(Span::styled(button_text, button_style)));
-                    img_counter += 1;
-                }
-                
-                // 正常内容行
-                content_lines.push(Line::from(Span::raw(line)));
-                content_line_counter += 1;
-            }
-            
-            // 计算内容实际行数与可见区域行数的差值，用于限制滚动范围
-            let content_height = content_lines.len() as u16;
-            let visible_area_height = inner_area.height.saturating_sub(2); // 减去边框
-            
-            // 调整滚动位置，避免无效滚动
-            let max_scroll = if content_height > visible_area_height {
-                content_height - visible_area_height
-            } else {
-                0
-            };
-            
-            // 确保不会滚动超出内容
-            let adjusted_scroll = app.post_scroll.min(max_scroll as u16);
-            
-            // 创建并渲染帖子内容
-            let full_post_view = Paragraph::new(content_lines)
-                .block(Block::default().borders(Borders::ALL).title(title))
-                .wrap(Wrap { trim: true })
-                .style(Style::default().fg(Color::White))
-                .scroll((adjusted_scroll, 0));  // 使用调整后的滚动值
-                
-            f.render_widget(full_post_view, inner_area);
+            draw_full_post(f, app, &posts[app.selected_index], inner_area);
             
             // 在底部添加提示
-            let hint_text = "按 ↑/↓/j/k 键滚动内容，Tab/i 选择图片，o 查看图片，Enter/Esc 返回";
+            let hint_text = "按 ↑/↓/j/k 键滚动内容，Tab/i 选择图片，o 查看图片，a 图片画廊，/ 搜索，Enter/Esc 返回";
             
             let hint = Paragraph::new(hint_text)
-                .style(Style::default().fg(Color::Gray))
+                .style(app.theme.meta)
                 .alignment(Alignment::Center);
                 
             let hint_area = Rect {
@@ -505,148 +517,157 @@ fn draw_topic(f: &mut Frame, app: &App, id: u64, area: Rect) {
             return; // 完整帖子查看模式下，不渲染其他内容
         }
         
-        // 非完整帖子查看模式下的渲染逻辑
-        let items: Vec<ListItem> = posts
+        // 主题详情加载后，在帖子列表上方显示主题信息
+        let (area, inner_area) = match app.topic_details.get(&id) {
+            Some(details) => {
+                let lines = topic_header_lines(app, details, area.width.saturating_sub(2) as usize);
+                let height = if app.topic_header_expanded { lines.len() as u16 + 2 } else { 1 };
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(height.min(area.height / 2)), Constraint::Min(0)])
+                    .split(area);
+                if app.topic_header_expanded {
+                    let mut title = "主题信息".to_string();
+                    if let Some(key) = app.keymap.describe(KeyMode::Normal, Action::ToggleTopicHeader) {
+                        title.push_str(&format!(" ({} 收起)", key));
+                    }
+                    let header = Paragraph::new(lines)
+                        .block(Block::default().borders(Borders::ALL).title(title));
+                    f.render_widget(header, chunks[0]);
+                } else {
+                    // 收起时只有一行，与列表边框内的内容对齐
+                    let line_area = Rect { x: chunks[0].x + 1, width: chunks[0].width.saturating_sub(2), ..chunks[0] };
+                    f.render_widget(Paragraph::new(lines), line_area);
+                }
+                (chunks[1], Block::default().borders(Borders::ALL).inner(chunks[1]))
+            }
+            None => (area, inner_area),
+        };
+        
+        // 非完整帖子查看模式下的渲染逻辑，树形显示时按回复关系排列并缩进
+        let rows = app.topic_rows();
+        let mut items: Vec<ListItem> = rows
             .iter()
-            .map(|post| {
-                // 创建帖子头部信息
-                let header = Line::from(vec![
+            .map(|row| {
+                let post = &posts[row.index];
+                let indent = thread_indent_width(row);
+                let separator_width = (inner_area.width as usize).saturating_sub(indent);
+                
+                // 被忽略的帖子折叠为一行提示
+                if app.is_post_collapsed(post) {
+                    return indent_item(app, row, vec![
+                        Line::from(Span::styled(
+                            format!("已忽略 {} 的帖子 (按 Enter 展开)", post.username),
+                            app.theme.muted.add_modifier(Modifier::ITALIC),
+                        )),
+                        Line::from(Span::styled("─".repeat(separator_width), app.theme.muted)),
+                    ]);
+                }
+                
+                let marks = search_marks(app, post);
+                
+                // 创建帖子头部信息，头像位置先显示首字母徽章，头像下载后覆盖在上面
+                let mut header_spans = vec![
+                    initial_badge(&post.username),
+                    Span::raw(" "),
                     Span::styled(
                         format!("{} ", post.username),
-                        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                        app.theme.title,
                     ),
                     Span::styled(
                         format_datetime(&post.created_at),
-                        Style::default().fg(Color::Gray),
+                        app.theme.meta,
                     ),
-                ]);
-                
-                // 将HTML内容分割成多行，以便在终端中能够正确显示
-                let content_width = inner_area.width.saturating_sub(2) as usize; // 减去内边距
-                let mut content_lines = Vec::new();
-                
-                // 简单清理HTML标签
-                let mut cleaned = post.cooked.clone();
-                
-                // 替换一些常见HTML标签为纯文本等价物
-                cleaned = cleaned.replace("<br>", "\n").replace("<br/>", "\n").replace("<br />", "\n");
-                cleaned = cleaned.replace("<p>", "").replace("</p>", "\n");
-                cleaned = cleaned.replace("<strong>", "").replace("</strong>", "");
-                cleaned = cleaned.replace("<em>", "").replace("</em>", "");
-                cleaned = cleaned.replace("&nbsp;", " ");
-                cleaned = cleaned.replace("&lt;", "<").replace("&gt;", ">");
-                cleaned = cleaned.replace("&quot;", "\"").replace("&apos;", "'");
-                cleaned = cleaned.replace("&amp;", "&");
-                
-                // 移除可能的剩余HTML标签 (简单实现，不使用regex)
-                let mut result = String::with_capacity(cleaned.len());
-                let mut in_tag = false;
-                
-                for c in cleaned.chars() {
-                    if c == '<' {
-                        in_tag = true;
-                    } else if c == '>' {
-                        in_tag = false;
-                    } else if !in_tag {
-                        result.push(c);
-                    }
+                ];
+                header_spans.push(Span::styled(format!(" #{}", post.post_number), app.theme.meta));
+                if post.edit_count() > 0 {
+                    header_spans.push(Span::styled(format!(" · 已编辑 {} 次", post.edit_count()), app.theme.meta));
                 }
-                
-                let content_text = result;
-                
-                // 处理连续换行符，将多个换行符替换为一个
-                let mut processed_text = String::new();
-                let mut last_char_was_newline = false;
-                
-                for c in content_text.chars() {
-                    if c == '\n' {
-                        if !last_char_was_newline {
-                            processed_text.push(c);
-                        }
-                        last_char_was_newline = true;
-                    } else {
-                        processed_text.push(c);
-                        last_char_was_newline = false;
-                    }
+                if post.is_deleted() {
+                    header_spans.push(Span::styled(" [已删除]", app.theme.error));
                 }
+                let match_count: usize = marks.values().map(Vec::len).sum();
+                if match_count > 0 {
+                    header_spans.push(Span::styled(format!(" [{} 处匹配]", match_count), app.theme.accent));
+                }
+                let header = Line::from(header_spans);
                 
-                // 提取图片URL
-                let image_urls = extract_image_urls(&post.cooked);
-                let _has_images = !image_urls.is_empty() && image_urls.iter().any(|url| {
-                    app.image_paths.lock().unwrap().get(url).is_some()
-                });
-                
-                // 将内容按行分割并过滤掉图片尺寸信息行
-                let lines_iter = processed_text.split('\n')
-                    .filter(|line| !is_image_size_info(line));
-                let mut lines_count = 0;
+                // 将HTML内容渲染成文本行，并按终端宽度折行
+                let content_width = (inner_area.width.saturating_sub(2) as usize).saturating_sub(indent); // 减去内边距和缩进
                 let max_preview_lines = 5; // 设置预览时最多显示的行数
                 
-                for line in lines_iter {
-                    if line.trim().is_empty() {
-                        content_lines.push(Line::from(Span::raw("")));
-                        lines_count += 1;
-                        if lines_count >= max_preview_lines {
-                            break;
-                        }
-                        continue;
-                    }
-                    
-                    // 长行处理 - 按照终端宽度自动分割长行
-                    if line.len() > content_width {
-                        let chars: Vec<char> = line.chars().collect();
-                        let mut current_pos = 0;
-                        
-                        while current_pos < chars.len() {
-                            let end_pos = std::cmp::min(current_pos + content_width, chars.len());
-                            let segment: String = chars[current_pos..end_pos].iter().collect();
-                            content_lines.push(Line::from(Span::raw(segment)));
-                            
-                            lines_count += 1;
-                            if lines_count >= max_preview_lines {
-                                break;
-                            }
-                            
-                            current_pos = end_pos;
+                let rendered = rendered_post(app, post);
+                let mut wrapped_lines = rendered
+                    .blocks
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| {
+                        !rendered.is_collapsed(*index, |section| app.expanded_details.contains(&(post.id, section)))
+                    })
+                    .filter_map(|(index, block)| match block {
+                        ContentBlock::Text(text) if rendered.details.iter().any(|section| section.summary == Some(index)) => {
+                            Some((index, format!("▸ {}", text)))
                         }
-                        
-                        if lines_count >= max_preview_lines {
-                            break;
+                        ContentBlock::Text(text) | ContentBlock::Quote(text) | ContentBlock::Code(text) => {
+                            Some((index, text.clone()))
                         }
-                    } else {
-                        content_lines.push(Line::from(Span::raw(line.to_string())));
-                        lines_count += 1;
-                        if lines_count >= max_preview_lines {
-                            break;
+                        // 预览中投票和链接预览只显示一行摘要
+                        ContentBlock::Poll(name) => Some((index, poll_summary(app, post, name))),
+                        ContentBlock::Onebox(onebox) => {
+                            let title = onebox.title.as_deref().unwrap_or(&onebox.url);
+                            match &onebox.domain {
+                                Some(domain) => Some((index, format!("[链接] {} · {}", title, domain))),
+                                None => Some((index, format!("[链接] {}", title))),
+                            }
                         }
-                    }
-                }
+                        ContentBlock::Image(_) => None,
+                    })
+                    .flat_map(|(index, text)| {
+                        let block_marks = marks.get(&index).cloned().unwrap_or_default();
+                        let mut offset = 0;
+                        wrap_text(&text, content_width).into_iter().map(move |piece| {
+                            let line = Line::from(mark_spans(&piece, offset, &block_marks, Style::default()));
+                            offset += piece.chars().count();
+                            line
+                        })
+                    });
+                let mut content_lines: Vec<Line> = wrapped_lines
+                    .by_ref()
+                    .take(max_preview_lines)
+                    .collect();
+                let has_more_content = wrapped_lines.next().is_some();
                 
-                // 如果内容被截断了或者有图片，添加省略号提示
-                let has_more_content = processed_text.split('\n').count() > lines_count || 
-                                      (processed_text.len() > content_width * lines_count);
+                // 检查是否有已下载的图片
+                let has_images = extract_image_urls(&post.cooked).iter().any(|url| {
+                    app.image_paths.lock().unwrap().contains_key(url)
+                });
                 
-                if has_more_content || _has_images {
+                // 如果内容被截断了或者有图片，添加省略号提示
+                if has_more_content || has_images {
                     let mut prompt = "... 按 Enter 查看完整内容".to_string();
-                    if _has_images {
+                    if has_images {
                         prompt += " 和图片附件";
                     }
                     prompt += " ...";
                     
                     content_lines.push(Line::from(Span::styled(
                         prompt,
-                        Style::default().fg(Color::Yellow),
+                        app.theme.accent,
                     )));
                 }
                 
                 // 创建分割线
                 let separator = Line::from(Span::styled(
-                    "─".repeat(inner_area.width as usize), 
-                    Style::default().fg(Color::DarkGray),
+                    "─".repeat(separator_width),
+                    app.theme.muted,
                 ));
                 
                 // 组合成完整的帖子显示
-                let mut all_lines = vec![header, Line::default()];
+                let mut all_lines = vec![header];
+                if let Some(line) = reply_to_line(app, post) {
+                    all_lines.push(line);
+                }
+                all_lines.push(Line::default());
                 
                 // 只有在内容行不为空时才添加
                 if !content_lines.is_empty() {
@@ -655,50 +676,122 @@ fn draw_topic(f: &mut Frame, app: &App, id: u64, area: Rect) {
                     // 如果内容为空，添加一个提示
                     all_lines.push(Line::from(Span::styled(
                         "[无内容]",
-                        Style::default().fg(Color::Gray),
+                        app.theme.meta,
                     )));
                 }
                 
+                all_lines.extend(replies_lines(app, post, content_width));
+                if row.folded > 0 {
+                    let mut text = format!("▸ 已折叠 {} 条回复", row.folded);
+                    if let Some(key) = app.keymap.describe(KeyMode::Normal, Action::ToggleFold) {
+                        text.push_str(&format!(" ({} 展开)", key));
+                    }
+                    all_lines.push(Line::from(Span::styled(text, app.theme.accent)));
+                }
                 all_lines.push(separator);
                 
-                ListItem::new(all_lines)
+                indent_item(app, row, all_lines)
             })
             .collect();
 
+        let details = app.topic_details.get(&id);
         let topic_title = if let Some(topic) = app.topics.iter().find(|t| t.id == id) {
             topic.title.clone()
+        } else if let Some(details) = details {
+            details.title.clone()
         } else {
             format!("主题 #{}", id)
         };
 
+        // 筛选后的页数与帖子总数无关，只显示当前页
+        let mut title = match details.filter(|_| !app.post_filter.is_active()) {
+            Some(details) => format!("{} (第{}/{}页)", topic_title, app.page, details.page_count()),
+            None => format!("{} (第{}页)", topic_title, app.page),
+        };
+        if app.thread_view {
+            title.push_str(" · 树形");
+        }
+        if let Some(filter) = app.post_filter.describe() {
+            title.push_str(&format!(" · 筛选: {}", filter));
+        }
+        if let Some(level) = app.topic_levels.get(&id) {
+            title.push_str(&format!(" · 通知: {}", level.title()));
+        }
+
+        // 最后一页的帖子之后显示推荐主题，每组的第一项带有分组标题
+        let suggested = app.suggested_topics();
+        let mut group = "";
+        for (name, topic) in &suggested {
+            let mut lines = Vec::new();
+            if *name != group {
+                group = name;
+                lines.push(Line::from(Span::styled(format!("── {} ──", name), app.theme.accent)));
+            }
+            lines.push(suggested_topic_line(app, topic));
+            items.push(ListItem::new(lines));
+        }
+
+        let item_heights: Vec<usize> = items.iter().map(ListItem::height).collect();
+        
         let posts_list = List::new(items)
-            .block(Block::default().borders(Borders::ALL).title(format!("{} (第{}页)", topic_title, app.page)))
+            .block(Block::default().borders(Borders::ALL).title(title))
             .highlight_style(
-                Style::default()
-                    .bg(Color::Yellow)
-                    .fg(Color::Black)
-                    .add_modifier(Modifier::BOLD),
+                app.theme.selection,
             )
             .highlight_symbol("> ");
 
         let mut state = ListState::default();
         
-        // 处理选中索引逻辑
-        if app.selected_index >= posts.len() && !posts.is_empty() {
-            state.select(Some(0)); // 如果选中索引超出范围，选择第一个
-        } else if !posts.is_empty() {
-            state.select(Some(app.selected_index));
-        } else {
+        // 处理选中索引逻辑，选中的帖子不在显示的行中时（如索引超出范围）选择第一行
+        let selected_row = rows
+            .iter()
+            .position(|row| row.index == app.selected_index)
+            .or_else(|| {
+                let offset = app.selected_index.checked_sub(posts.len())?;
+                (offset < suggested.len()).then_some(rows.len() + offset)
+            });
+        if rows.is_empty() && suggested.is_empty() {
             state.select(None); // 空列表不选择任何项
+        } else {
+            state.select(Some(selected_row.unwrap_or(0)));
         }
         
         // 渲染帖子列表
         f.render_stateful_widget(posts_list, area, &mut state);
+        let row_items: Vec<(usize, usize)> = rows
+            .iter()
+            .map(|row| row.index)
+            .chain(posts.len()..posts.len() + suggested.len())
+            .zip(item_heights.iter().copied())
+            .collect();
+        register_list_rows(app, inner_area, state.offset(), &row_items);
+        
+        // 在可见帖子的头部绘制头像，跳过高亮符号和缩进所占的宽度
+        if supports_graphics() {
+            let avatar_x = inner_area.x + "> ".width() as u16;
+            let mut y = inner_area.y;
+            for (row, height) in rows.iter().zip(&item_heights).skip(state.offset()) {
+                if y >= inner_area.bottom() {
+                    break;
+                }
+                let post = &posts[row.index];
+                // 折叠的帖子没有头部
+                let avatar = post.avatar_template.as_deref().filter(|_| !app.is_post_collapsed(post));
+                if let Some(path) = avatar.and_then(|t| avatar_path(app, t)) {
+                    let x = avatar_x + thread_indent_width(row) as u16;
+                    draw_avatar(f, &path, Rect { x, y, width: AVATAR_WIDTH, height: 1 });
+                }
+                y = y.saturating_add(*height as u16);
+            }
+        }
         
         // 添加提示信息
-        let hint_text = "按 Enter 查看帖子完整内容，j/k 或 ↓/↑ 选择帖子，n/p 切换页面";
+        let hint_text = format!(
+            "按 Enter 查看帖子完整内容，j/k 或 ↓/↑ 选择帖子{}，T 树形/平铺显示",
+            page_keys_hint(app)
+        );
         let hint = Paragraph::new(hint_text)
-            .style(Style::default().fg(Color::Gray))
+            .style(app.theme.meta)
             .alignment(Alignment::Center);
             
         let hint_area = Rect {
@@ -713,51 +806,651 @@ fn draw_topic(f: &mut Frame, app: &App, id: u64, area: Rect) {
         // 正在加载帖子
         let paragraph = Paragraph::new("正在加载帖子...")
             .alignment(Alignment::Center)
-            .style(Style::default().fg(Color::White))
+            .style(app.theme.text)
             .wrap(Wrap { trim: true });
         f.render_widget(paragraph, inner_area);
     }
 }
 
-fn draw_user(f: &mut Frame, app: &App, username: &str, area: Rect) {
-    if let Some(user) = app.users.get(username) {
-        let text = vec![
-            Line::from(vec![
-                Span::styled(
-                    format!("用户名: ", ),
-                    Style::default().fg(Color::Gray),
-                ),
-                Span::styled(
-                    user.username.clone(),
-                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
-                ),
-            ]),
-            Line::from(vec![
-                Span::styled(
-                    format!("名称: "),
-                    Style::default().fg(Color::Gray),
-                ),
-                Span::styled(
-                    user.name.clone().unwrap_or_else(|| "-".to_string()),
-                    Style::default().fg(Color::White),
-                ),
-            ]),
-            Line::from(vec![
-                Span::styled(
-                    format!("信任等级: "),
-                    Style::default().fg(Color::Gray),
-                ),
-                Span::styled(
+/// 帖子列表上方的主题信息，收起时只有一行摘要
+fn topic_header_lines(app: &App, details: &TopicDetails, width: usize) -> Vec<Line<'static>> {
+    let mut summary = Vec::new();
+    if let Some(category) = details.category_id.and_then(|id| app.categories.iter().find(|c| c.id == id)) {
+        summary.push(category_badge(&app.theme, category));
+        summary.push(Span::raw(" "));
+    }
+    if app.topic_header_expanded {
+        summary.push(Span::styled(details.title.clone(), app.theme.text.add_modifier(Modifier::BOLD)));
+    }
+    for tag in &details.tags {
+        summary.push(Span::styled(format!(" #{}", tag), app.theme.link));
+    }
+    if details.closed {
+        summary.push(Span::styled(" [已关闭]", app.theme.muted));
+    }
+    if details.archived {
+        summary.push(Span::styled(" [已归档]", app.theme.muted));
+    }
+
+    let counts = format!(
+        "{} 帖子 · {} 浏览 · {} 赞 · {} 位参与者",
+        details.posts_count,
+        compact_count(details.views),
+        compact_count(details.like_count),
+        details.participant_count,
+    );
+    let created_by = details.details.created_by.as_ref().map(|user| user.username.clone());
+
+    if !app.topic_header_expanded {
+        let mut text = String::new();
+        if let Some(username) = &created_by {
+            text.push_str(&format!(" @{} ·", username));
+        }
+        text.push_str(&format!(" {}", counts));
+        if let Some(key) = app.keymap.describe(KeyMode::Normal, Action::ToggleTopicHeader) {
+            text.push_str(&format!(" ({} 展开)", key));
+        }
+        summary.push(Span::styled(text, app.theme.meta));
+        return vec![Line::from(summary)];
+    }
+
+    let mut lines = vec![Line::from(summary)];
+
+    let mut started = match &created_by {
+        Some(username) => format!("由 @{} 发起于 {}", username, format_datetime(&details.created_at)),
+        None => format!("发起于 {}", format_datetime(&details.created_at)),
+    };
+    if let Some(last_posted_at) = details.last_posted_at {
+        started.push_str(&format!(" · 最后回复 {}", format_age(&last_posted_at)));
+        if let Some(poster) = &details.details.last_poster {
+            started.push_str(&format!(" (@{})", poster.username));
+        }
+    }
+    lines.push(Line::from(Span::styled(started, app.theme.meta)));
+    lines.push(Line::from(Span::styled(
+        format!("{} · 共 {} 页", counts, details.page_count()),
+        app.theme.meta,
+    )));
+
+    // 参与者按帖子数排列，放不下时显示剩余人数
+    if !details.details.participants.is_empty() {
+        let mut participants = details.details.participants.clone();
+        participants.sort_by_key(|participant| std::cmp::Reverse(participant.post_count));
+        let mut spans = vec![Span::styled("参与者:", app.theme.meta)];
+        let mut used = "参与者:".width();
+        for (i, participant) in participants.iter().enumerate() {
+            let text = format!(" @{} ({})", participant.username, participant.post_count);
+            let rest = format!(" 等 {} 人", participants.len() - i);
+            if used + text.width() + rest.width() > width && i + 1 < participants.len() {
+                spans.push(Span::styled(rest, app.theme.muted));
+                break;
+            }
+            used += text.width();
+            spans.push(Span::styled(text, app.theme.text));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let meta = &details.details;
+    let permissions: Vec<&str> = [
+        (meta.can_create_post, "回复"),
+        (meta.can_edit, "编辑主题"),
+        (meta.can_delete, "删除主题"),
+        (meta.can_reply_as_new_topic, "作为新主题回复"),
+        (meta.can_flag_topic, "举报"),
+    ]
+    .into_iter()
+    .filter_map(|(allowed, name)| allowed.then_some(name))
+    .collect();
+    let permissions = if meta.can_create_post {
+        Span::styled(format!("权限: {}", permissions.join("、")), app.theme.muted)
+    } else if permissions.is_empty() {
+        Span::styled("只读: 不能回复", app.theme.error)
+    } else {
+        Span::styled(format!("不能回复 · 权限: {}", permissions.join("、")), app.theme.error)
+    };
+    lines.push(Line::from(permissions));
+    lines
+}
+
+/// 完整帖子视图中为内联图片预留的位置
+struct ImageSlot {
+    url: String,
+    /// 占位的第一行（图片标签所在行）
+    row: usize,
+    height: usize,
+    button: Option<usize>,
+}
+
+/// 完整帖子视图中某一行内的可点击区域，绘制时换算为屏幕位置
+struct LineHit {
+    row: usize,
+    column: u16,
+    width: u16,
+    target: ClickTarget,
+}
+
+/// 树形显示时最多缩进的层数，更深的回复与这一层对齐
+const MAX_THREAD_INDENT: usize = 6;
+
+fn thread_indent_width(row: &ThreadRow) -> usize {
+    row.depth.min(MAX_THREAD_INDENT) * 3
+}
+
+/// 为帖子的各行加上树形缩进：第一行为连接线，其余行延续上层的竖线
+fn indent_item(app: &App, row: &ThreadRow, lines: Vec<Line<'static>>) -> ListItem<'static> {
+    if row.depth == 0 {
+        return ListItem::new(lines);
+    }
+    // 超过最大缩进时省略最外层的竖线
+    let skip = row.depth - row.depth.min(MAX_THREAD_INDENT);
+    let guides: String = row
+        .guides
+        .iter()
+        .skip(skip)
+        .map(|&more| if more { "│  " } else { "   " })
+        .collect();
+    let first = format!("{}{}", guides, if row.is_last { "└─ " } else { "├─ " });
+    let rest = format!("{}{}", guides, if row.is_last { "   " } else { "│  " });
+    
+    let lines = lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let prefix = if i == 0 { first.clone() } else { rest.clone() };
+            let mut spans = vec![Span::styled(prefix, app.theme.muted)];
+            spans.extend(line.spans);
+            Line::from(spans)
+        })
+        .collect::<Vec<_>>();
+    ListItem::new(lines)
+}
+
+/// 帖子回复的楼层，如 "↩ 回复 @user #3"
+fn reply_to_line(app: &App, post: &Post) -> Option<Line<'static>> {
+    let number = post.reply_to_post_number?;
+    let username = post
+        .reply_to_user
+        .as_ref()
+        .map_or_else(String::new, |user| format!("@{} ", user.username));
+    let mut spans = vec![Span::styled(format!("↩ 回复 {}#{}", username, number), app.theme.link)];
+    if let Some(key) = app.keymap.describe(KeyMode::Normal, Action::JumpToParent) {
+        spans.push(Span::styled(format!(" ({} 跳转)", key), app.theme.muted));
+    }
+    Some(Line::from(spans))
+}
+
+/// 帖子的回复数，展开后列出每条回复的第一行
+fn replies_lines(app: &App, post: &Post, width: usize) -> Vec<Line<'static>> {
+    if post.reply_count == 0 {
+        return Vec::new();
+    }
+    let Some(replies) = app.post_replies.get(&post.id) else {
+        let mut text = format!("▸ {} 条回复", post.reply_count);
+        if let Some(key) = app.keymap.describe(KeyMode::Normal, Action::ToggleReplies) {
+            text.push_str(&format!(" ({} 展开)", key));
+        }
+        return vec![Line::from(Span::styled(text, app.theme.meta))];
+    };
+    
+    let mut lines = vec![Line::from(Span::styled(format!("▾ {} 条回复", replies.len()), app.theme.meta))];
+    for reply in replies {
+        let head = format!("  ↳ @{} #{}: ", reply.username, reply.post_number);
+        let preview = render_post(&reply.cooked)
+            .blocks
+            .into_iter()
+            .find_map(|block| match block {
+                ContentBlock::Text(text) if !text.trim().is_empty() => Some(text),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let preview = wrap_text(&preview, width.saturating_sub(head.width()).max(1)).swap_remove(0);
+        lines.push(Line::from(vec![
+            Span::styled(head, app.theme.link),
+            Span::styled(preview, app.theme.text),
+        ]));
+    }
+    lines
+}
+
+/// 按字符位置为一行文本设置样式，`offset` 为这一行在原始文本中的起始字符位置
+///
+/// 标记按顺序叠加在基础样式上，后面的标记优先。
+fn mark_spans(text: &str, offset: usize, marks: &[(usize, usize, Style)], base: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let mut current_style = base;
+    for (i, c) in text.chars().enumerate() {
+        let position = offset + i;
+        let style = marks
+            .iter()
+            .rev()
+            .find(|(start, end, _)| (*start..*end).contains(&position))
+            .map_or(base, |(_, _, style)| base.patch(*style));
+        if style != current_style && !current.is_empty() {
+            spans.push(Span::styled(std::mem::take(&mut current), current_style));
+        }
+        current_style = style;
+        current.push(c);
+    }
+    if !current.is_empty() {
+        spans.push(Span::styled(current, current_style));
+    }
+    spans
+}
+
+/// 完整帖子视图的内容行及其中的可点击区域
+struct PostLines {
+    lines: Vec<Line<'static>>,
+    hits: Vec<LineHit>,
+    link_style: Style,
+}
+
+impl PostLines {
+    fn new(link_style: Style) -> Self {
+        PostLines { lines: Vec::new(), hits: Vec::new(), link_style }
+    }
+    
+    /// 没有 <summary> 的折叠区域的标题行
+    fn push_details_summary(&mut self, text: &'static str, section: usize, style: Style) {
+        self.hits.push(LineHit {
+            row: self.lines.len(),
+            column: 0,
+            width: text.width() as u16,
+            target: ClickTarget::Details(section),
+        });
+        self.lines.push(Line::from(Span::styled(text, style)));
+    }
+    
+    /// 链接预览卡片：网站、可点击的标题和最多三行摘要
+    fn push_onebox(&mut self, theme: &Theme, onebox: &Onebox, width: usize) {
+        let border = theme.muted;
+        let inner_width = width.saturating_sub(2);
+        self.lines.push(Line::from(vec![
+            Span::styled("╭ ", border),
+            Span::styled(onebox.domain.clone().unwrap_or_else(|| "链接".to_string()), theme.meta),
+        ]));
+        let title = onebox.title.as_deref().unwrap_or(&onebox.url);
+        for piece in wrap_text(title, inner_width) {
+            self.hits.push(LineHit {
+                row: self.lines.len(),
+                column: 2,
+                width: piece.width() as u16,
+                target: ClickTarget::Link(onebox.url.clone()),
+            });
+            self.lines.push(Line::from(vec![
+                Span::styled("│ ", border),
+                Span::styled(piece, self.link_style.add_modifier(Modifier::BOLD)),
+            ]));
+        }
+        if let Some(excerpt) = &onebox.excerpt {
+            let mut pieces = wrap_text(excerpt, inner_width);
+            if pieces.len() > 3 {
+                pieces.truncate(3);
+                pieces[2].push('…');
+            }
+            for piece in pieces {
+                self.lines.push(Line::from(vec![Span::styled("│ ", border), Span::styled(piece, theme.meta)]));
+            }
+        }
+        self.lines.push(Line::from(Span::styled("╰─", border)));
+    }
+    
+    /// 折行后加入文本行，链接使用链接样式并记录点击区域，`highlights` 叠加在链接样式之上
+    ///
+    /// 返回每一行在文本中的起始字符位置。
+    fn push_text(
+        &mut self,
+        text: &str,
+        width: usize,
+        prefix: Option<Span<'static>>,
+        style: Style,
+        links: &[&Link],
+        highlights: &[(usize, usize, Style)],
+    ) -> Vec<usize> {
+        let prefix_width = prefix.as_ref().map_or(0, Span::width);
+        let marks: Vec<(usize, usize, Style)> = links
+            .iter()
+            .map(|link| (link.start, link.end, self.link_style))
+            .chain(highlights.iter().copied())
+            .collect();
+        let column_of = |chars: &[char]| chars.iter().map(|c| c.width().unwrap_or(0)).sum::<usize>();
+        
+        let mut starts = Vec::new();
+        let mut offset = 0;
+        for piece in wrap_text(text, width.saturating_sub(prefix_width)) {
+            starts.push(offset);
+            let chars: Vec<char> = piece.chars().collect();
+            for link in links {
+                let start = link.start.max(offset);
+                let end = link.end.min(offset + chars.len());
+                if start >= end {
+                    continue;
+                }
+                self.hits.push(LineHit {
+                    row: self.lines.len(),
+                    column: (prefix_width + column_of(&chars[..start - offset])) as u16,
+                    width: column_of(&chars[start - offset..end - offset]) as u16,
+                    target: ClickTarget::Link(link.href.clone()),
+                });
+            }
+            
+            let mut spans: Vec<Span> = prefix.iter().cloned().collect();
+            spans.extend(mark_spans(&piece, offset, &marks, style));
+            self.lines.push(Line::from(spans));
+            offset += chars.len();
+        }
+        starts
+    }
+}
+
+/// 帖子中的搜索匹配，按文本块序号分组，当前匹配使用单独的样式
+fn search_marks(app: &App, post: &Post) -> HashMap<usize, Vec<(usize, usize, Style)>> {
+    let mut marks: HashMap<usize, Vec<(usize, usize, Style)>> = HashMap::new();
+    if let Some(search) = app.search.as_ref().filter(|search| search.topic_id == post.topic_id) {
+        for (found, current) in search.matches_in(post.id) {
+            let style = if current { app.theme.search_current } else { app.theme.search };
+            marks.entry(found.block).or_default().push((found.start, found.end, style));
+        }
+    }
+    marks
+}
+
+/// 图片按钮/标签行，按钮序号只分配给已下载的图片
+fn image_label_line(theme: &Theme, button: Option<usize>, selected: bool) -> Line<'static> {
+    match button {
+        Some(index) => {
+            let style = if selected {
+                theme.selection
+            } else {
+                theme.link.add_modifier(Modifier::ITALIC)
+            };
+            let text = format!("[{} 图片 #{} (按o键查看)]", if selected { "✓" } else { " " }, index + 1);
+            Line::from(Span::styled(text, style))
+        }
+        None => Line::from(Span::styled(
+            "[图片加载中...]",
+            theme.muted.add_modifier(Modifier::ITALIC),
+        )),
+    }
+}
+
+fn draw_full_post(f: &mut Frame, app: &App, post: &Post, area: Rect) {
+    // 创建帖子头部信息
+    let mut title = format!("#{} - {}", post.post_number, post.username);
+    if let (Some(number), Some(user)) = (post.reply_to_post_number, &post.reply_to_user) {
+        title.push_str(&format!(" · ↩ 回复 @{} #{}", user.username, number));
+    }
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let content_area = block.inner(area);
+    f.render_widget(block, area);
+    
+    let image_paths = app.image_paths.lock().unwrap().clone();
+    let width = content_area.width as usize;
+    
+    // 终端支持图形协议时才内联显示图片，否则退回图片按钮
+    let inline = app.config.images.inline && supports_graphics();
+    // 图片区域至少需要5行，再加上标签行
+    let slot_height = app.config.images.max_height.min(content_area.height).max(6) as usize;
+    
+    let mut content = PostLines::new(app.theme.link);
+    let mut slots = Vec::new();
+    let mut button_count = 0;
+    
+    let marks = search_marks(app, post);
+    let current_match = app
+        .search
+        .as_ref()
+        .and_then(|search| search.current_match())
+        .filter(|found| found.post_id == post.id);
+    // 当前匹配所在的行，用于滚动到匹配位置
+    let mut match_row = None;
+    
+    let rendered = rendered_post(app, post);
+    // 当前搜索匹配所在的折叠区域自动展开
+    let expanded = |section: usize| {
+        app.expanded_details.contains(&(post.id, section))
+            || current_match.is_some_and(|found| rendered.details[section].contains(found.block))
+    };
+    let mut details_rows = Vec::new();
+    for (index, block) in rendered.blocks.iter().enumerate() {
+        // 没有 <summary> 的区域使用默认标题
+        for (section, _) in rendered.details.iter().enumerate().filter(|(_, details)| {
+            details.summary.is_none() && details.start == index && details.end > index
+        }) {
+            if !rendered.is_collapsed(index, |other| other == section || expanded(other)) {
+                let marker = if expanded(section) { "▾ 详细信息" } else { "▸ 详细信息" };
+                details_rows.push((section, content.lines.len()));
+                content.push_details_summary(marker, section, app.theme.accent);
+            }
+        }
+        if rendered.is_collapsed(index, expanded) {
+            // 收起的图片仍然占用按钮序号，与图片查看器中的顺序保持一致
+            if let ContentBlock::Image(url) = block {
+                if image_paths.contains_key(url) {
+                    button_count += 1;
+                }
+            }
+            continue;
+        }
+        
+        let links: Vec<&Link> = rendered.links.iter().filter(|link| link.block == index).collect();
+        let highlights = marks.get(&index).map_or(&[][..], Vec::as_slice);
+        let row = content.lines.len();
+        let summary = rendered.details.iter().position(|section| section.summary == Some(index));
+        let line_starts = match block {
+            // <details> 的标题，点击或按键展开
+            ContentBlock::Text(text) if summary.is_some() => {
+                let section = summary.unwrap_or_default();
+                let marker = if expanded(section) { "▾ " } else { "▸ " };
+                details_rows.push((section, row));
+                content.hits.push(LineHit {
+                    row,
+                    column: 0,
+                    width: (marker.width() + text.width()).min(width) as u16,
+                    target: ClickTarget::Details(section),
+                });
+                let prefix = Span::styled(marker, app.theme.accent);
+                let style = app.theme.accent.add_modifier(Modifier::BOLD);
+                Some(content.push_text(text, width, Some(prefix), style, &links, highlights))
+            }
+            ContentBlock::Text(text) => {
+                Some(content.push_text(text, width, None, app.theme.text, &links, highlights))
+            }
+            ContentBlock::Quote(text) => {
+                // 引用前加竖线，折行时预留竖线的宽度
+                let prefix = Span::styled("│ ", app.theme.muted);
+                Some(content.push_text(text, width, Some(prefix), app.theme.quote, &links, highlights))
+            }
+            ContentBlock::Code(text) => {
+                Some(content.push_text(text, width, None, app.theme.code, &links, highlights))
+            }
+            ContentBlock::Onebox(onebox) => {
+                content.push_onebox(&app.theme, onebox, width);
+                None
+            }
+            ContentBlock::Poll(name) => {
+                match post.polls.iter().find(|poll| poll.name == *name) {
+                    Some(poll) => {
+                        let voted = post.polls_votes.get(name).map_or(&[][..], Vec::as_slice);
+                        content.lines.extend(poll_lines(app, poll, voted, None, width));
+                    }
+                    None => content.lines.push(Line::from(Span::styled("[投票]", app.theme.muted))),
+                }
+                None
+            }
+            ContentBlock::Image(url) => {
+                let button = image_paths.contains_key(url).then(|| {
+                    button_count += 1;
+                    button_count - 1
+                });
+                let selected = button.is_some() && button == app.selected_image_button;
+                
+                let label = image_label_line(&app.theme, button, selected);
+                if let Some(button) = button {
+                    content.hits.push(LineHit {
+                        row: content.lines.len(),
+                        column: 0,
+                        width: label.width() as u16,
+                        target: ClickTarget::ImageButton(button),
+                    });
+                }
+                if inline {
+                    slots.push(ImageSlot { url: url.clone(), row: content.lines.len(), height: slot_height, button });
+                    content.lines.push(label);
+                    content.lines.extend((1..slot_height).map(|_| Line::default()));
+                } else {
+                    content.lines.push(label);
+                }
+                None
+            }
+        };
+        if let (Some(starts), Some(found)) = (line_starts, current_match.filter(|found| found.block == index)) {
+            match_row = Some(row + starts.iter().rposition(|&start| start <= found.start).unwrap_or(0));
+        }
+    }
+    *app.details_rows.borrow_mut() = details_rows;
+    let PostLines { mut lines, hits, .. } = content;
+    
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled("[无内容]", app.theme.meta)));
+    }
+    
+    // 限制滚动范围，避免滚动超出内容
+    let visible_height = content_area.height as usize;
+    let max_scroll = lines.len().saturating_sub(visible_height);
+    let mut scroll = app.post_scroll as usize;
+    if let Some(search) = app.search.as_ref() {
+        // 跳到新的匹配时把匹配所在行滚动到上方三分之一处，之后的按键会采用这个滚动位置
+        if let Some(row) = match_row.filter(|_| search.reveal.replace(false)) {
+            search.revealed_scroll.set(Some(row.saturating_sub(visible_height / 3) as u16));
+        }
+        if let Some(revealed) = search.revealed_scroll.get() {
+            scroll = revealed as usize;
+        }
+    }
+    let scroll = scroll.min(max_scroll);
+    
+    let paragraph = Paragraph::new(lines)
+        .style(app.theme.text)
+        .scroll((scroll as u16, 0));
+    f.render_widget(paragraph, content_area);
+    
+    // 记录可见行中链接和图片按钮的点击区域
+    for hit in hits {
+        if hit.row < scroll || hit.row >= scroll + visible_height || hit.column >= content_area.width {
+            continue;
+        }
+        let area = Rect {
+            x: content_area.x + hit.column,
+            y: content_area.y + (hit.row - scroll) as u16,
+            width: hit.width.min(content_area.width - hit.column),
+            height: 1,
+        };
+        app.hit_regions.push(area, hit.target);
+    }
+    
+    // 只渲染完全处于可见区域内的图片，部分可见时保留占位
+    for slot in slots {
+        let image_top = slot.row + 1;
+        let image_bottom = slot.row + slot.height;
+        if image_top < scroll || image_bottom > scroll + visible_height {
+            continue;
+        }
+        
+        let image_area = Rect {
+            x: content_area.x,
+            y: content_area.y + (image_top - scroll) as u16,
+            width: content_area.width,
+            height: (slot.height - 1) as u16,
+        };
+        if let Some(button) = slot.button {
+            app.hit_regions.push(image_area, ClickTarget::ImageButton(button));
+        }
+        
+        match image_paths.get(&slot.url) {
+            Some(path) => {
+                let image_widget = ImageWidget::new(path)
+                    .max_width(image_area.width)
+                    .max_height(image_area.height)
+                    .maintain_aspect_ratio(true);
+                f.render_widget(image_widget, image_area);
+            }
+            None => {
+                let placeholder = Paragraph::new("图片加载中...")
+                    .style(app.theme.muted)
+                    .alignment(Alignment::Center);
+                f.render_widget(placeholder, image_area);
+            }
+        }
+    }
+}
+
+fn draw_user(f: &mut Frame, app: &App, username: &str, area: Rect) {
+    if let Some(user) = app.users.get(username) {
+        let block = Block::default().borders(Borders::ALL).title(format!("用户: {}", username));
+        let mut info_area = block.inner(area);
+        f.render_widget(block, area);
+        
+        // 支持图形协议且头像已下载时在左侧显示大头像
+        let avatar = avatar_path(app, &user.avatar_template).filter(|_| supports_graphics());
+        if let Some(path) = &avatar {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(PROFILE_AVATAR_WIDTH + 2), Constraint::Min(1)].as_ref())
+                .split(info_area);
+            let avatar_area = Rect {
+                height: PROFILE_AVATAR_HEIGHT.min(chunks[0].height),
+                width: PROFILE_AVATAR_WIDTH.min(chunks[0].width),
+                ..chunks[0]
+            };
+            draw_avatar(f, path, avatar_area);
+            info_area = chunks[1];
+        }
+        
+        let mut username_spans = Vec::new();
+        if avatar.is_none() {
+            username_spans.push(initial_badge(&user.username));
+            username_spans.push(Span::raw(" "));
+        }
+        username_spans.extend([
+                Span::styled(
+                    "用户名: ".to_string(),
+                    app.theme.meta,
+                ),
+                Span::styled(
+                    user.username.clone(),
+                    app.theme.title,
+                ),
+        ]);
+        
+        let text = vec![
+            Line::from(username_spans),
+            Line::from(vec![
+                Span::styled(
+                    "名称: ".to_string(),
+                    app.theme.meta,
+                ),
+                Span::styled(
+                    user.name.clone().unwrap_or_else(|| "-".to_string()),
+                    app.theme.text,
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled(
+                    "信任等级: ".to_string(),
+                    app.theme.meta,
+                ),
+                Span::styled(
                     format!("{}", user.trust_level),
-                    Style::default().fg(Color::White),
+                    app.theme.text,
                 ),
             ]),
         ];
 
         let paragraph = Paragraph::new(text)
-            .block(Block::default().borders(Borders::ALL).title(format!("用户: {}", username)))
             .wrap(Wrap { trim: true });
-        f.render_widget(paragraph, area);
+        f.render_widget(paragraph, info_area);
     } else {
         let paragraph = Paragraph::new("正在加载用户信息...")
             .block(Block::default().borders(Borders::ALL).title(format!("用户: {}", username)))
@@ -773,7 +1466,7 @@ fn draw_settings(f: &mut Frame, app: &App, area: Rect) {
         .constraints(
             [
                 Constraint::Min(6),  // 信息区
-                Constraint::Length(3),  // 选项区
+                Constraint::Length(SETTINGS_OPTIONS as u16 + 2),  // 选项区
             ]
             .as_ref(),
         )
@@ -791,7 +1484,7 @@ fn draw_settings(f: &mut Frame, app: &App, area: Rect) {
         Line::from(vec![
             Span::styled(
                 "API 密钥: ",
-                Style::default().fg(Color::Gray),
+                app.theme.meta,
             ),
             Span::styled(
                 if app.config.discourse.api_key.is_empty() { 
@@ -800,9 +1493,9 @@ fn draw_settings(f: &mut Frame, app: &App, area: Rect) {
                     "已设置 (已隐藏)".to_string() 
                 },
                 if app.config.discourse.api_key.is_empty() {
-                    Style::default().fg(Color::Red)
+                    app.theme.error
                 } else {
-                    Style::default().fg(Color::Green)
+                    app.theme.success
                 },
             ),
         ]),
@@ -814,23 +1507,23 @@ fn draw_settings(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(paragraph, chunks[0]);
     
     // 选项区域
-    let options = vec![
-        "生成 API 密钥",
+    let options = [
+        "生成 API 密钥".to_string(),
+        format!("主题: {} (Enter 切换)", app.theme.name),
     ];
     
-    let options_list = List::new(options.iter().map(|&o| ListItem::new(o)).collect::<Vec<_>>())
+    let options_list = List::new(options.into_iter().map(ListItem::new).collect::<Vec<_>>())
         .block(Block::default().borders(Borders::ALL).title("操作"))
         .highlight_style(
-            Style::default()
-                .bg(Color::Yellow)
-                .fg(Color::Black)
-                .add_modifier(Modifier::BOLD),
+            app.theme.selection,
         )
         .highlight_symbol("> ");
 
     let mut state = ListState::default();
     state.select(Some(app.selected_index));
     f.render_stateful_widget(options_list, chunks[1], &mut state);
+    let list_area = Block::default().borders(Borders::ALL).inner(chunks[1]);
+    register_list_items(app, list_area, state.offset(), &[1; SETTINGS_OPTIONS]);
 }
 
 fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
@@ -843,86 +1536,638 @@ fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
         AppTab::Settings => "设置".to_string(),
     };
 
-    let help_text = "按 '?' 查看帮助";
+    let search = app
+        .search
+        .as_ref()
+        .filter(|search| app.current_tab == AppTab::Topic(search.topic_id));
+    
+    // 有未完成的组合键时显示已按下的按键，其次显示搜索输入和状态消息，否则显示搜索进度或帮助提示
+    let help_span = if !app.pending_keys.is_empty() {
+        let keys: String = app.pending_keys.iter().map(ToString::to_string).collect();
+        Span::styled(format!("{}-", keys), app.theme.accent)
+    } else if let Some(search) = search.filter(|search| search.editing) {
+        Span::styled(format!("/{}", search.query), app.theme.text)
+    } else {
+        status_message_span(app).unwrap_or_else(|| match search.filter(|search| !search.query.is_empty()) {
+            Some(search) => Span::styled(search_progress(search), app.theme.accent),
+            None => {
+                let help = match app.keymap.describe(KeyMode::Normal, Action::ToggleHelp) {
+                    Some(keys) => format!("按 '{}' 查看帮助", keys),
+                    None => String::new(),
+                };
+                Span::styled(help, app.theme.link)
+            }
+        })
+    };
+    let cursor_column = help_span.width();
+    let editing = search.is_some_and(|search| search.editing);
     let page_info = if matches!(app.current_tab, AppTab::Topics | AppTab::Topic(_)) {
         format!("第 {} 页", app.page)
     } else {
         "".to_string()
     };
 
-    let status = Paragraph::new(Line::from(vec![
+    let mut spans = vec![
         Span::styled(
             format!("{} ", current_view),
-            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+            app.theme.text.add_modifier(Modifier::BOLD),
         ),
         Span::styled(
             format!("{} ", page_info),
-            Style::default().fg(Color::Gray),
-        ),
-        Span::styled(
-            help_text,
-            Style::default().fg(Color::Blue),
+            app.theme.meta,
         ),
-    ]))
-    .block(Block::default().borders(Borders::ALL));
+        help_span,
+    ];
+    if let Some(search) = search.filter(|search| search.editing && !search.query.is_empty()) {
+        spans.push(Span::styled(format!("  {}", search_progress(search)), app.theme.meta));
+    }
+    let prefix_width: usize = spans[..2].iter().map(Span::width).sum();
+    
+    let status = Paragraph::new(Line::from(spans))
+        .block(Block::default().borders(Borders::ALL));
     f.render_widget(status, area);
+    
+    if editing {
+        f.set_cursor_position((area.x + 1 + (prefix_width + cursor_column) as u16, area.y + 1));
+    }
 }
 
-fn draw_input(f: &mut Frame, app: &App) {
-    let area = centered_rect(60, 20, f.area());
-    let input = Paragraph::new(app.input.as_ref() as &str)
-        .block(Block::default().borders(Borders::ALL).title("输入回复"))
-        .style(Style::default().fg(Color::White))
-        .wrap(Wrap { trim: true });
-    f.render_widget(input, area);
+/// 搜索进度：当前是第几处匹配，以及已搜索的页数
+fn search_progress(search: &TopicSearch) -> String {
+    match search.current {
+        Some(index) => format!(
+            "\"{}\" {}/{} (已搜索 {} 页)",
+            search.query,
+            index + 1,
+            search.matches.len(),
+            search.page_count()
+        ),
+        None => format!("\"{}\" 无匹配 (已搜索 {} 页)", search.query, search.page_count()),
+    }
 }
 
-fn draw_help(f: &mut Frame) {
-    let area = centered_rect(60, 20, f.area());
-    let help_text = vec![
-        Line::from("导航:"),
-        Line::from("  j/↓: 向下移动"),
-        Line::from("  k/↑: 向上移动"),
-        Line::from("  h/←: 返回"),
-        Line::from("  l/→/Enter: 选择/查看详情"),
-        Line::from(""),
-        Line::from("在查看帖子时:"),
-        Line::from("  Enter: 切换完整帖子查看模式"),
-        Line::from("  ↑/↓: 在完整帖子中滚动"),
-        Line::from("  Esc: 退出完整帖子查看模式"),
-        Line::from(""),
-        Line::from("功能:"),
-        Line::from("  t: 查看主题"),
-        Line::from("  c: 查看分类"),
-        Line::from("  s: 设置"),
-        Line::from("  r: 刷新"),
-        Line::from("  n: 下一页"),
-        Line::from("  p: 上一页"),
-        Line::from("  q: 退出"),
-        Line::from(""),
-        Line::from("按任意键关闭此帮助"),
-    ];
+/// 状态消息，错误使用错误样式显示
+fn status_message_span(app: &App) -> Option<Span<'static>> {
+    app.status_message.as_ref().map(|message| {
+        let style = if message.is_error { app.theme.error } else { app.theme.success };
+        Span::styled(message.text.clone(), style)
+    })
+}
+
+fn draw_composer(f: &mut Frame, app: &App, composer: &Composer) {
+    let area = centered_rect(60, 40, f.area());
+    let title = if composer.is_edit() {
+        format!("{} (Enter 保存，Alt+Enter 换行，Tab 修改原因，Esc 放弃)", composer.title())
+    } else {
+        format!("{} (Enter 发布，Alt+Enter 换行，Esc 放弃)", composer.title())
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    
+    let constraints = if composer.is_edit() {
+        vec![Constraint::Min(1), Constraint::Length(1)]
+    } else {
+        vec![Constraint::Min(1)]
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(inner);
+    
+    // 只显示能放下的最后几行，使光标所在的末尾始终可见
+    let lines: Vec<String> = composer
+        .text
+        .split('\n')
+        .flat_map(|line| wrap_text(line, chunks[0].width as usize))
+        .collect();
+    let skip = lines.len().saturating_sub(chunks[0].height as usize);
+    let visible: Vec<Line> = lines[skip..].iter().map(|line| Line::from(line.clone())).collect();
+    let text = Paragraph::new(visible).style(app.theme.text);
+    f.render_widget(text, chunks[0]);
+    
+    if composer.is_edit() {
+        let label_style = if composer.editing_reason { app.theme.accent } else { app.theme.meta };
+        let reason = Paragraph::new(Line::from(vec![
+            Span::styled("修改原因: ", label_style),
+            Span::styled(composer.edit_reason.clone(), app.theme.text),
+        ]));
+        f.render_widget(reason, chunks[1]);
+    }
+    
+    let (x, y) = if composer.editing_reason {
+        (chunks[1].x + "修改原因: ".width() as u16 + composer.edit_reason.width() as u16, chunks[1].y)
+    } else {
+        let last = lines.last().map_or(0, |line| line.width() as u16);
+        let row = lines.len().saturating_sub(skip).saturating_sub(1) as u16;
+        (chunks[0].x + last, chunks[0].y + row)
+    };
+    let x = x.min(inner.right().saturating_sub(1));
+    f.set_cursor_position((x, y));
+    
+    let suggestions = composer.emoji_suggestions();
+    if !suggestions.is_empty() {
+        draw_emoji_suggestions(f, app, &suggestions, composer.emoji_selected, (x, y));
+    }
+}
+
+/// 光标下方的表情补全列表，下方放不下时显示在上方
+fn draw_emoji_suggestions(
+    f: &mut Frame,
+    app: &App,
+    suggestions: &[(&str, &str)],
+    selected: usize,
+    (x, y): (u16, u16),
+) {
+    let screen = f.area();
+    let width = suggestions
+        .iter()
+        .map(|(code, emoji)| emoji.width() + code.width() + 5)
+        .max()
+        .unwrap_or(0)
+        .max(18) as u16;
+    let height = suggestions.len() as u16 + 2;
+    let top = if y + 1 + height <= screen.bottom() { y + 1 } else { y.saturating_sub(height) };
+    let area = Rect {
+        x: x.min(screen.right().saturating_sub(width)),
+        y: top,
+        width: width.min(screen.width),
+        height: height.min(screen.height),
+    };
+    
+    let items: Vec<ListItem> = suggestions
+        .iter()
+        .map(|(code, emoji)| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{} ", emoji)),
+                Span::styled(format!(":{}:", code), app.theme.text),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("表情 (Tab 补全)"))
+        .highlight_style(app.theme.selection);
+    let mut state = ListState::default();
+    state.select(Some(selected));
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+/// 根据当前生效的快捷键生成帮助，先列出当前模式，再列出浏览模式的按键
+fn draw_help(f: &mut Frame, app: &App) {
+    let area = centered_rect(60, 80, f.area());
+    let current = app.key_mode();
+    let mut modes = vec![current];
+    if current != KeyMode::Normal {
+        modes.push(KeyMode::Normal);
+    }
+    
+    let mut help_text = Vec::new();
+    for mode in modes {
+        help_text.push(Line::from(Span::styled(
+            format!("{}:", mode.title()),
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        for (action, sequences) in app.keymap.bindings(mode) {
+            if sequences.is_empty() {
+                continue;
+            }
+            let keys = sequences.iter().map(ToString::to_string).collect::<Vec<_>>().join("/");
+            help_text.push(Line::from(vec![
+                Span::styled(format!("  {}", keys), app.theme.accent),
+                Span::raw(format!(": {}", action.description())),
+            ]));
+        }
+        help_text.push(Line::from(""));
+    }
+    help_text.push(Line::from("快捷键可在配置文件的 [keybindings] 中修改"));
+    help_text.push(Line::from("按任意键关闭此帮助"));
+    
     let help = Paragraph::new(help_text)
         .block(Block::default().borders(Borders::ALL).title("帮助"))
-        .style(Style::default().fg(Color::White))
+        .style(app.theme.text)
         .wrap(Wrap { trim: true });
+    f.render_widget(Clear, area);
     f.render_widget(help, area);
 }
 
-fn draw_loading(f: &mut Frame) {
+/// 命令面板：第一行为输入的筛选条件，下面是匹配的条目，匹配到的字符高亮，按键显示在右侧
+fn draw_palette(f: &mut Frame, app: &App, palette: &Palette) {
+    let area = centered_rect(60, 60, f.area());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("命令面板 ({}/{})", palette.matches.len(), palette.entries.len()));
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1)].as_ref())
+        .split(inner);
+    
+    let input = Paragraph::new(Line::from(vec![
+        Span::styled(": ", app.theme.accent),
+        Span::styled(palette.query.clone(), app.theme.text),
+    ]));
+    f.render_widget(input, chunks[0]);
+    f.set_cursor_position((
+        chunks[0].x + 2 + palette.query.width() as u16,
+        chunks[0].y,
+    ));
+    
+    let width = chunks[1].width as usize;
+    let highlight = app.theme.accent.add_modifier(Modifier::BOLD);
+    let items: Vec<ListItem> = palette
+        .matches
+        .iter()
+        .map(|matched| {
+            let entry = &palette.entries[matched.entry];
+            let marks: Vec<(usize, usize, Style)> = matched
+                .positions
+                .iter()
+                .map(|&position| (position, position + 1, highlight))
+                .collect();
+            let kind = format!("[{}] ", entry.kind());
+            let mut spans = vec![Span::styled(kind.clone(), app.theme.meta)];
+            spans.extend(mark_spans(&entry.label, 0, &marks, app.theme.text));
+            if let Some(ref key) = entry.key {
+                let used = kind.width() + entry.label.width();
+                let padding = width.saturating_sub(used + key.width() + 1).max(1);
+                spans.push(Span::raw(" ".repeat(padding)));
+                spans.push(Span::styled(key.clone(), app.theme.meta));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    
+    let list = List::new(items).highlight_style(app.theme.selection);
+    let mut state = ListState::default();
+    state.select(Some(palette.selected));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+fn draw_notification_picker(f: &mut Frame, app: &App, picker: &NotificationPicker) {
+    let area = centered_rect(50, 40, f.area());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("通知级别: {}", picker.name));
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    
+    let items: Vec<ListItem> = picker
+        .levels
+        .iter()
+        .map(|level| {
+            let mark = if picker.current == Some(*level) { "✓ " } else { "  " };
+            ListItem::new(vec![
+                Line::from(vec![
+                    Span::styled(mark, app.theme.accent),
+                    Span::styled(level.title(), app.theme.text.add_modifier(Modifier::BOLD)),
+                ]),
+                Line::from(Span::styled(format!("  {}", level.description()), app.theme.meta)),
+            ])
+        })
+        .collect();
+    
+    let list = List::new(items).highlight_style(app.theme.selection);
+    let mut state = ListState::default();
+    state.select(Some(picker.selected));
+    f.render_stateful_widget(list, inner, &mut state);
+}
+
+/// 删除帖子前的确认窗口
+fn draw_delete_confirm(f: &mut Frame, app: &App, post_number: u64) {
+    let area = centered_rect(40, 20, f.area());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.theme.error)
+        .title("删除帖子");
+    let text = vec![
+        Line::from(Span::styled(format!("确定要删除帖子 #{} 吗？", post_number), app.theme.text)),
+        Line::from(""),
+        Line::from(Span::styled("y 删除 · 其他键取消", app.theme.meta)),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// 渲染帖子正文，没有显示剧透时遮挡剧透内容
+fn rendered_post(app: &App, post: &Post) -> RenderedPost {
+    let mut rendered = render_post(&post.cooked);
+    if !app.revealed_spoilers.contains(&post.id) {
+        rendered.hide_spoilers();
+    }
+    rendered
+}
+
+/// 帖子预览中的投票摘要
+fn poll_summary(app: &App, post: &Post, name: &str) -> String {
+    let Some(poll) = post.polls.iter().find(|poll| poll.name == name) else {
+        return "[投票]".to_string();
+    };
+    let mut text = format!("[投票] {} 个选项 · {} 人投票", poll.options.len(), poll.voters);
+    if poll.is_closed() {
+        text.push_str(" · 已结束");
+    } else if post.polls_votes.contains_key(name) {
+        text.push_str(" · 已投票");
+    }
+    if let Some(key) = app.keymap.describe(KeyMode::Normal, Action::ShowPoll) {
+        text.push_str(&format!(" ({} 投票)", key));
+    }
+    text
+}
+
+/// 投票的标题、选项和结果条形图
+///
+/// `chosen` 为投票窗口中准备提交的选项，为 None 时按已投的选项标记。
+fn poll_lines(app: &App, poll: &Poll, voted: &[String], chosen: Option<&[String]>, width: usize) -> Vec<Line<'static>> {
+    let kind = match poll.kind {
+        PollKind::Regular => "单选".to_string(),
+        PollKind::Multiple => match (poll.min_choices(), poll.max_choices()) {
+            (min, max) if min == max => format!("多选 {} 项", max),
+            (min, max) => format!("多选 {}-{} 项", min, max),
+        },
+        PollKind::Number => "评分".to_string(),
+        PollKind::Unknown => "不支持的类型".to_string(),
+    };
+    let mut header = format!("▌投票 · {} · {} 人投票", kind, poll.voters);
+    if poll.public {
+        header.push_str(" · 公开");
+    }
+    if !poll.is_supported() {
+        header.push_str(" · 只能查看");
+    } else if poll.is_closed() {
+        header.push_str(" · 已结束");
+    } else if let Some(close) = &poll.close {
+        header.push_str(&format!(" · {} 结束", format_datetime(close)));
+    }
+    let mut lines = vec![Line::from(Span::styled(header, app.theme.accent.add_modifier(Modifier::BOLD)))];
+    
+    let shows_results = poll.shows_results(!voted.is_empty());
+    // 多选投票按投票人数计算比例，与网页一致
+    let total = match poll.kind {
+        PollKind::Multiple => poll.voters,
+        PollKind::Regular | PollKind::Number | PollKind::Unknown => poll.total_votes(),
+    };
+    let bar_width = width.saturating_sub(14).min(30);
+    let multiple = poll.kind == PollKind::Multiple;
+    for option in &poll.options {
+        let marked = chosen.unwrap_or(voted).contains(&option.id);
+        let mark = match (multiple, marked) {
+            (true, true) => "☑",
+            (true, false) => "☐",
+            (false, true) => "◉",
+            (false, false) => "○",
+        };
+        let style = if marked { app.theme.accent } else { app.theme.text };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} ", mark), style),
+            Span::styled(plain_text(&option.html), style),
+        ]));
+        
+        if shows_results {
+            let votes = option.votes.unwrap_or(0);
+            let share = if total == 0 { 0.0 } else { votes as f64 / total as f64 };
+            let filled = ((share * bar_width as f64).round() as usize).min(bar_width);
+            lines.push(Line::from(vec![
+                Span::raw("  "),
+                Span::styled("█".repeat(filled), app.theme.accent),
+                Span::styled("░".repeat(bar_width - filled), app.theme.muted),
+                Span::styled(format!(" {:>3.0}% ({})", share * 100.0, votes), app.theme.meta),
+            ]));
+        }
+    }
+    
+    if !shows_results {
+        let text = match poll.results {
+            PollResults::OnVote => "投票后显示结果",
+            PollResults::OnClose => "投票结束后显示结果",
+            PollResults::StaffOnly => "只有管理员可以查看结果",
+            PollResults::Always | PollResults::Unknown => "没有可显示的结果",
+        };
+        lines.push(Line::from(Span::styled(text, app.theme.muted.add_modifier(Modifier::ITALIC))));
+    }
+    lines
+}
+
+/// 投票窗口，每个选项下方显示结果条形图
+fn draw_poll_voter(f: &mut Frame, app: &App, voter: &PollVoter) {
+    let area = centered_rect(60, 60, f.area());
+    let mut title = format!("投票 #{}", voter.post_number);
+    if voter.poll_count > 1 {
+        title.push_str(&format!(" ({}/{}, Tab 切换)", voter.poll_index + 1, voter.poll_count));
+    }
+    let hint = if !voter.poll.is_supported() {
+        "不支持这种投票，只能查看 · Esc 关闭"
+    } else if voter.poll.is_closed() {
+        "投票已结束 · Esc 关闭"
+    } else if voter.has_voted() {
+        "空格 选择 · Enter 修改投票 · x 撤回投票 · Esc 关闭"
+    } else {
+        "空格 选择 · Enter 投票 · Esc 关闭"
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_bottom(Line::from(Span::styled(hint, app.theme.meta)).alignment(Alignment::Center));
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    
+    // 第一行为标题，其余按选项分组，每个选项一行文字加一行结果
+    let mut lines = poll_lines(app, &voter.poll, &voter.voted, Some(&voter.chosen), inner.width as usize);
+    let header = lines.remove(0);
+    let per_option = if voter.poll.shows_results(voter.has_voted()) { 2 } else { 1 };
+    let mut items = Vec::new();
+    let mut rest = lines.into_iter();
+    for _ in &voter.poll.options {
+        items.push(ListItem::new(rest.by_ref().take(per_option).collect::<Vec<_>>()));
+    }
+    let notes: Vec<Line> = rest.collect();
+    
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0), Constraint::Length(notes.len() as u16)])
+        .split(inner);
+    f.render_widget(Paragraph::new(header), chunks[0]);
+    let list = List::new(items).highlight_style(app.theme.selection);
+    let mut state = ListState::default();
+    state.select(Some(voter.selected));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+    f.render_widget(Paragraph::new(notes), chunks[2]);
+}
+
+fn draw_revisions(f: &mut Frame, app: &App, view: &RevisionView) {
+    let area = centered_rect(90, 90, f.area());
+    let revision = &view.revision;
+    let last = revision.last_revision.unwrap_or(revision.current_revision);
+    let layout = if view.side_by_side { "左右对比" } else { "上下对比" };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.theme.border)
+        .title(format!(
+            "#{} 的修改历史: 版本 {} → {} (共 {} 版) · {}",
+            view.post_number,
+            revision.previous_revision.unwrap_or(revision.current_revision.saturating_sub(1)),
+            revision.current_revision,
+            last,
+            layout,
+        ))
+        .title_bottom("h/l 上一次/下一次修改 · Tab 切换对比方式 · j/k 滚动 · Esc 关闭");
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    
+    let mut lines = Vec::new();
+    let mut info = vec![
+        Span::styled(format!("@{} ", revision.username), app.theme.title),
+        Span::styled(format_datetime(&revision.created_at), app.theme.meta),
+    ];
+    if let Some(reason) = revision.edit_reason.as_deref().filter(|reason| !reason.is_empty()) {
+        info.push(Span::styled(format!(" · 原因: {}", reason), app.theme.meta));
+    }
+    lines.push(Line::from(info));
+    
+    if let Some(ref title) = view.title {
+        let mut spans = vec![Span::styled("标题: ", app.theme.meta)];
+        spans.extend(title.iter().map(|segment| Span::styled(segment.text.clone(), diff_style(&app.theme, segment.kind))));
+        lines.push(Line::from(spans));
+    }
+    if let Some(ref changes) = revision.category_id_changes {
+        let name = |id: Option<u64>| match id {
+            Some(id) => app
+                .categories
+                .iter()
+                .find(|category| category.id == id)
+                .map_or_else(|| format!("#{}", id), |category| category.name.clone()),
+            None => "无".to_string(),
+        };
+        lines.push(Line::from(vec![
+            Span::styled("分类: ", app.theme.meta),
+            Span::styled(name(changes.previous), app.theme.diff_removed),
+            Span::styled(" → ", app.theme.meta),
+            Span::styled(name(changes.current), app.theme.diff_added),
+        ]));
+    }
+    if let Some(ref changes) = revision.tags_changes {
+        let previous = changes.previous.clone().unwrap_or_default();
+        let current = changes.current.clone().unwrap_or_default();
+        let mut spans = vec![Span::styled("标签:", app.theme.meta)];
+        for tag in &previous {
+            let style = if current.contains(tag) { app.theme.text } else { app.theme.diff_removed };
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(format!("#{}", tag), style));
+        }
+        for tag in current.iter().filter(|tag| !previous.contains(tag)) {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(format!("#{}", tag), app.theme.diff_added));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::from(Span::styled("─".repeat(inner.width as usize), app.theme.muted)));
+    
+    if view.side_by_side {
+        // 两列之间留出 " │ " 的宽度
+        let column_width = (inner.width as usize).saturating_sub(3) / 2;
+        for row in &view.body {
+            let old = diff_lines(&app.theme, &row.old, column_width);
+            let new = diff_lines(&app.theme, &row.new, column_width);
+            for i in 0..old.len().max(new.len()) {
+                let left = old.get(i).cloned().unwrap_or_default();
+                let padding = column_width.saturating_sub(left.width());
+                let mut spans = left.spans;
+                spans.push(Span::raw(" ".repeat(padding)));
+                spans.push(Span::styled(" │ ", app.theme.muted));
+                spans.extend(new.get(i).cloned().unwrap_or_default().spans);
+                lines.push(Line::from(spans));
+            }
+        }
+    } else {
+        // 行首两格用于标记增删
+        let width = (inner.width as usize).saturating_sub(2);
+        for row in &view.body {
+            if !row.is_changed() {
+                lines.extend(prefixed(diff_lines(&app.theme, &row.new, width), "  ", app.theme.text));
+                continue;
+            }
+            if !segments_text(&row.old).is_empty() {
+                lines.extend(prefixed(diff_lines(&app.theme, &row.old, width), "- ", app.theme.diff_removed));
+            }
+            if !segments_text(&row.new).is_empty() {
+                lines.extend(prefixed(diff_lines(&app.theme, &row.new, width), "+ ", app.theme.diff_added));
+            }
+        }
+    }
+    
+    f.render_widget(Paragraph::new(lines).scroll((view.scroll, 0)), inner);
+}
+
+fn diff_style(theme: &Theme, kind: DiffKind) -> Style {
+    match kind {
+        DiffKind::Same => theme.text,
+        DiffKind::Added => theme.diff_added,
+        DiffKind::Removed => theme.diff_removed,
+    }
+}
+
+/// 将差异文字按换行和终端宽度折行，保留每段的增删样式
+fn diff_lines(theme: &Theme, segments: &[DiffSegment], width: usize) -> Vec<Line<'static>> {
+    let mut marks = Vec::new();
+    let mut position = 0;
+    for segment in segments {
+        let len = segment.text.chars().count();
+        marks.push((position, position + len, diff_style(theme, segment.kind)));
+        position += len;
+    }
+    
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for part in segments_text(segments).split('\n') {
+        for piece in wrap_text(part, width) {
+            lines.push(Line::from(mark_spans(&piece, offset, &marks, Style::default())));
+            offset += piece.chars().count();
+        }
+        // 换行符本身
+        offset += 1;
+    }
+    lines
+}
+
+/// 在第一行前加上标记，后续行缩进对齐
+fn prefixed(lines: Vec<Line<'static>>, marker: &'static str, style: Style) -> Vec<Line<'static>> {
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let prefix = if i == 0 { marker } else { "  " };
+            let mut spans = vec![Span::styled(prefix, style)];
+            spans.extend(line.spans);
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn draw_loading(f: &mut Frame, theme: &Theme) {
     let area = centered_rect(30, 3, f.area());
     let loading = Paragraph::new("加载中...")
         .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::Yellow))
+        .style(theme.accent)
         .alignment(Alignment::Center);
     f.render_widget(loading, area);
 }
 
-fn draw_error(f: &mut Frame, error: &str) {
+fn draw_error(f: &mut Frame, theme: &Theme, error: &str) {
     let area = centered_rect(60, 5, f.area());
     let error_text = Paragraph::new(error)
         .block(Block::default().borders(Borders::ALL).title("错误"))
-        .style(Style::default().fg(Color::Red))
+        .style(theme.error)
         .alignment(Alignment::Center)
         .wrap(Wrap { trim: true });
     f.render_widget(error_text, area);
@@ -961,136 +2206,485 @@ fn format_datetime(dt: &DateTime<Utc>) -> String {
     local_time.format("%Y-%m-%d %H:%M").to_string()
 }
 
-// 解析颜色字符串为Tui颜色
-fn parse_color(color_str: &str) -> Color {
-    match color_str.trim_start_matches('#') {
-        "ff0000" => Color::Red,
-        "00ff00" => Color::Green,
-        "0000ff" => Color::Blue,
-        "ffff00" => Color::Yellow,
-        "ff00ff" => Color::Magenta,
-        "00ffff" => Color::Cyan,
-        "ffffff" => Color::White,
-        _ => Color::Gray,
+/// 距现在的时间，一个月以前的显示日期
+fn format_age(dt: &DateTime<Utc>) -> String {
+    let minutes = (Utc::now() - *dt).num_minutes().max(0);
+    match minutes {
+        0 => "刚刚".to_string(),
+        1..=59 => format!("{}分钟前", minutes),
+        60..=1439 => format!("{}小时前", minutes / 60),
+        1440..=43199 => format!("{}天前", minutes / 1440),
+        _ => dt.with_timezone(&Local).format("%Y-%m-%d").to_string(),
     }
 }
 
+/// 按显示宽度折行，中文等全角字符按两列计算
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    if width == 0 {
+        return vec![text.to_string()];
+    }
+    
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+    
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if current_width + char_width > width && !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+            current_width = 0;
+        }
+        current.push(c);
+        current_width += char_width;
+    }
+    lines.push(current);
+    
+    lines
+}
+
+/// 帖子头部头像占用的宽度（两格约为正方形）
+const AVATAR_WIDTH: u16 = 2;
+/// 用户资料页头像的大小
+const PROFILE_AVATAR_WIDTH: u16 = 12;
+const PROFILE_AVATAR_HEIGHT: u16 = 6;
+
+/// 没有头像可用时的首字母徽章颜色，按用户名选取
+const BADGE_COLORS: &[Color] = &[
+    Color::Red,
+    Color::Green,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightBlue,
+    Color::LightMagenta,
+];
+
+/// 已下载的头像文件
+fn avatar_path(app: &App, avatar_template: &str) -> Option<std::path::PathBuf> {
+    let url = avatar_url(&app.config.discourse.url, avatar_template);
+    app.image_paths.lock().unwrap().get(&url).cloned()
+}
+
+fn draw_avatar(f: &mut Frame, path: &std::path::Path, area: Rect) {
+    let avatar = ImageWidget::new(path)
+        .max_width(area.width)
+        .max_height(area.height)
+        .min_size(1, 1);
+    f.render_widget(avatar, area);
+}
+
+/// 用户名首字母徽章，与帖子头部头像同宽
+fn initial_badge(username: &str) -> Span<'static> {
+    let initial = username
+        .chars()
+        .next()
+        .map(|c| c.to_uppercase().to_string())
+        .unwrap_or_else(|| "?".to_string());
+    let text = if initial.width() >= AVATAR_WIDTH as usize {
+        initial
+    } else {
+        format!(" {}", initial)
+    };
+    
+    let hash = username.bytes().fold(0usize, |acc, b| acc.wrapping_mul(31).wrapping_add(b as usize));
+    let color = BADGE_COLORS[hash % BADGE_COLORS.len()];
+    Span::styled(
+        text,
+        Style::default().fg(Color::Black).bg(color).add_modifier(Modifier::BOLD),
+    )
+}
+
+/// 将 Discourse 的十六进制颜色转换为当前终端可显示的颜色，无法解析时使用灰色
+fn parse_color(theme: &Theme, color_str: &str) -> Color {
+    parse_hex_color(color_str).map_or(Color::Gray, |color| theme.color(color))
+}
+
+/// 分类徽章，使用分类自身的背景色和文字颜色
+fn category_badge(theme: &Theme, category: &Category) -> Span<'static> {
+    Span::styled(
+        format!(" {} ", category.name),
+        Style::default()
+            .bg(parse_color(theme, &category.color))
+            .fg(parse_color(theme, &category.text_color))
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
 // 在文件末尾添加新函数
 fn draw_image(f: &mut Frame, app: &App) {
-    if let Some(url) = &app.current_image_url {
-        debug!("尝试渲染图片: {}", url);
-        // 使用clone避免长时间持有锁
-        let image_path = app.image_paths.lock().unwrap().get(url).cloned();
+    if let Some(image) = &app.current_image {
+        draw_image_viewer(
+            f,
+            app,
+            image,
+            &app.image_view,
+            "图片预览",
+            None,
+            "+/- 缩放，f 适应/原始大小，0 重置，hjkl 平移，s 保存，y 复制链接，O 外部打开，Enter/Esc/o 返回",
+        );
+    } else {
+        debug!("没有当前图片URL");
+    }
+}
+
+/// 在全屏覆盖层中显示一张图片，底部显示可选的说明、图片信息和链接
+fn draw_image_viewer(
+    f: &mut Frame,
+    app: &App,
+    image: &PostImage,
+    view: &ImageView,
+    title: &str,
+    caption: Option<Line>,
+    hint_text: &str,
+) {
+    let url = image.full_url();
+    debug!("尝试渲染图片: {}", url);
+    // 原图下载完成前先显示缩略图，使用clone避免长时间持有锁
+    let (image_path, showing_original) = {
+        let image_paths = app.image_paths.lock().unwrap();
+        match image_paths.get(url) {
+            Some(path) => (Some(path.clone()), image.original_url.is_some()),
+            None => (image_paths.get(&image.url).cloned(), false),
+        }
+    };
+    
+    // 创建占满整个屏幕的清除层，确保图片显示在最上层
+    f.render_widget(Clear, f.area());
+    
+    // 添加半透明背景
+    let bg_block = Block::default()
+        .style(Style::default().bg(Color::Rgb(0, 0, 0)));
+    f.render_widget(bg_block, f.area());
+    
+    // 计算一个更合适的图片显示区域（根据屏幕大小按比例调整）
+    let screen_width = f.area().width;
+    let screen_height = f.area().height;
+    
+    // 为大屏幕使用更大的显示区域，但限制最大尺寸
+    let percent_x = if screen_width > 100 { 90 } else { 80 };
+    let percent_y = if screen_height > 50 { 80 } else { 70 };
+    
+    let image_area = centered_rect(percent_x, percent_y, f.area());
+    debug!("图片显示区域: {:?}", image_area);
+    
+    // 先渲染边框和背景
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.theme.border)
+        .title(Span::styled(
+            format!("{}{}", title, zoom_label(view)),
+            app.theme.title,
+        ))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(Color::Black));
         
-        if let Some(path) = image_path {
-            debug!("找到图片路径: {:?}", path);
-            // 创建占满整个屏幕的清除层，确保图片显示在最上层
-            f.render_widget(Clear, f.area());
-            
-            // 添加半透明背景
-            let bg_block = Block::default()
-                .style(Style::default().bg(Color::Rgb(0, 0, 0)));
-            f.render_widget(bg_block, f.area());
-            
-            // 计算一个更合适的图片显示区域（根据屏幕大小按比例调整）
-            let screen_width = f.area().width;
-            let screen_height = f.area().height;
-            
-            // 为大屏幕使用更大的显示区域，但限制最大尺寸
-            let percent_x = if screen_width > 100 { 90 } else { 80 };
-            let percent_y = if screen_height > 50 { 80 } else { 70 };
-            
-            let image_area = centered_rect(percent_x, percent_y, f.area());
-            debug!("图片显示区域: {:?}", image_area);
-            
-            // 先渲染边框和背景
-            let block = Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan))
-                .title(Span::styled("图片预览", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)))
-                .title_alignment(Alignment::Center)
-                .style(Style::default().bg(Color::Black));
-                
-            f.render_widget(block.clone(), image_area);
-            
-            // 计算内部区域并渲染图片
-            let inner_area = block.inner(image_area);
-            
-            // 计算图片显示区域（占内部区域的上部分）
-            let img_display_height = inner_area.height.saturating_sub(8); // 留出底部空间显示链接信息
-            let img_area = Rect {
-                x: inner_area.x,
-                y: inner_area.y,
-                width: inner_area.width,
-                height: img_display_height,
-            };
-            
-            // 清除内部区域，防止透明区域堆叠问题
-            f.render_widget(Clear, inner_area);
-            
-            // 检查文件是否存在
-            if !path.exists() {
-                let error_message = format!("图片文件不存在: {:?}", path);
-                let error_text = Paragraph::new(error_message)
-                    .style(Style::default().fg(Color::Red))
-                    .alignment(Alignment::Center);
-                f.render_widget(error_text, inner_area);
-                debug!("图片文件不存在: {:?}", path);
-                return;
-            }
-            
+    f.render_widget(block.clone(), image_area);
+    
+    // 计算内部区域并渲染图片
+    let inner_area = block.inner(image_area);
+    
+    // 计算图片显示区域（占内部区域的上部分）
+    let img_display_height = inner_area.height.saturating_sub(8); // 留出底部空间显示链接信息
+    let img_area = Rect {
+        x: inner_area.x,
+        y: inner_area.y,
+        width: inner_area.width,
+        height: img_display_height,
+    };
+    
+    // 清除内部区域，防止透明区域堆叠问题
+    f.render_widget(Clear, inner_area);
+    
+    // 根据缩放状态计算可见区域，并记录下来供平移使用
+    let region = image_path
+        .as_ref()
+        .and_then(|path| image::image_dimensions(path).ok())
+        .map(|dimensions| {
+            let (font_width, font_height) = font_size();
+            let viewport = (
+                img_area.width as u32 * font_width as u32,
+                img_area.height as u32 * font_height as u32,
+            );
+            view.visible_region(dimensions, viewport)
+        })
+        .unwrap_or(Region::FULL);
+    view.visible_size.set((region.width, region.height));
+    
+    match image_path {
+        Some(path) if path.exists() => {
             debug!("开始渲染图片: {:?}", path);
             // 创建并渲染图片组件
             let image_widget = ImageWidget::new(path)
                 .max_width(img_area.width)
                 .max_height(img_area.height)
-                .maintain_aspect_ratio(true);
+                .maintain_aspect_ratio(true)
+                .crop(region);
             
             f.render_widget(image_widget, img_area);
-            
-            // 显示链接信息
-            let link_info = format!("链接: {}", url);
-            let link_area = Rect {
-                x: inner_area.x + 1,
-                y: inner_area.y + img_display_height + 1,
-                width: inner_area.width.saturating_sub(2),
-                height: 2,
-            };
-            
-            // 添加链接分割线
-            let separator = Line::from(Span::styled(
-                "─".repeat(link_area.width as usize),
-                Style::default().fg(Color::DarkGray),
-            ));
-            
-            // 创建链接信息部分
-            let link_paragraph = Paragraph::new(vec![
-                separator,
-                Line::from(Span::styled(link_info, Style::default().fg(Color::Cyan))),
-            ])
-            .alignment(Alignment::Center);
-            
-            f.render_widget(link_paragraph, link_area);
-            
-            // 在底部添加操作提示
-            let hint_text = "按 Enter、Esc 或 o 键返回";
-            
-            let hint = Paragraph::new(hint_text)
-                .style(Style::default().fg(Color::Yellow))
+        }
+        Some(path) => {
+            let error_text = Paragraph::new(format!("图片文件不存在: {:?}", path))
+                .style(app.theme.error)
                 .alignment(Alignment::Center);
-                
-            let hint_area = Rect {
-                x: f.area().x,
-                y: f.area().height.saturating_sub(2) + f.area().y,
-                width: f.area().width,
-                height: 1,
-            };
-            
-            f.render_widget(hint, hint_area);
-        } else {
+            f.render_widget(error_text, img_area);
+            debug!("图片文件不存在: {:?}", path);
+        }
+        None => {
+            let loading = Paragraph::new("图片加载中...")
+                .style(app.theme.meta)
+                .alignment(Alignment::Center);
+            f.render_widget(loading, img_area);
             debug!("未找到图片路径: {}", url);
         }
+    }
+    
+    // 显示链接信息
+    let link_info = format!("链接: {}", url);
+    let link_area = Rect {
+        x: inner_area.x + 1,
+        y: inner_area.y + img_display_height + 1,
+        width: inner_area.width.saturating_sub(2),
+        height: 4,
+    };
+    
+    // 添加链接分割线
+    let separator = Line::from(Span::styled(
+        "─".repeat(link_area.width as usize),
+        app.theme.muted,
+    ));
+    
+    // 创建链接信息部分
+    let mut info_lines = vec![separator];
+    if let Some(caption) = caption {
+        info_lines.push(caption);
+    }
+    info_lines.push(image_info_line(&app.theme, image, showing_original));
+    info_lines.push(Line::from(Span::styled(link_info, app.theme.link)));
+    
+    let link_paragraph = Paragraph::new(info_lines)
+        .alignment(Alignment::Center);
+    
+    // 放大时在右下角显示小地图，标出可见区域
+    if region.is_full() {
+        f.render_widget(link_paragraph, link_area);
     } else {
-        debug!("没有当前图片URL");
+        let [text_area, minimap_area] = Layout::horizontal([
+            Constraint::Min(1),
+            Constraint::Length(MINIMAP_WIDTH),
+        ])
+        .areas(Rect { height: 6, ..link_area });
+        f.render_widget(link_paragraph, text_area);
+        draw_minimap(f, &app.theme, region, minimap_area);
+    }
+    
+    // 在底部添加操作提示，有状态消息（如保存结果）时优先显示
+    let hint_line = status_message_span(app)
+        .map(Line::from)
+        .unwrap_or_else(|| Line::from(Span::styled(hint_text.to_string(), app.theme.accent)));
+    let hint = Paragraph::new(hint_line)
+        .alignment(Alignment::Center);
+        
+    let hint_area = Rect {
+        x: f.area().x,
+        y: f.area().height.saturating_sub(2) + f.area().y,
+        width: f.area().width,
+        height: 1,
+    };
+    
+    f.render_widget(hint, hint_area);
+}
+
+/// 标题中显示的缩放状态
+fn zoom_label(view: &ImageView) -> String {
+    if view.actual_size {
+        " (原始大小)".to_string()
+    } else if view.zoom > 1.0 {
+        format!(" ({:.1}x)", view.zoom)
+    } else {
+        String::new()
     }
-} 
\ No newline at end of file
+}
+
+/// 小地图的宽度（包含边框）
+const MINIMAP_WIDTH: u16 = 18;
+
+/// 绘制小地图，用实心块标出当前可见的图片区域
+fn draw_minimap(f: &mut Frame, theme: &Theme, region: Region, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.muted)
+        .title("视图");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    
+    let (width, height) = (inner.width as f32, inner.height as f32);
+    let left = (region.x * width).floor() as u16;
+    let right = ((region.x + region.width) * width).ceil() as u16;
+    let top = (region.y * height).floor() as u16;
+    let bottom = ((region.y + region.height) * height).ceil() as u16;
+    
+    let lines: Vec<Line> = (0..inner.height)
+        .map(|row| {
+            let text: String = (0..inner.width)
+                .map(|column| {
+                    if (left..right).contains(&column) && (top..bottom).contains(&row) {
+                        '█'
+                    } else {
+                        '·'
+                    }
+                })
+                .collect();
+            Line::from(Span::styled(text, theme.link))
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// 图片信息：文件名、尺寸、大小以及当前显示的是原图还是缩略图
+fn image_info_line(theme: &Theme, image: &PostImage, showing_original: bool) -> Line<'static> {
+    let mut spans = Vec::new();
+    let fields = [
+        ("文件名", &image.filename),
+        ("尺寸", &image.dimensions),
+        ("大小", &image.file_size),
+    ];
+    for (label, value) in fields {
+        if let Some(value) = value {
+            spans.push(Span::styled(format!("{}: ", label), theme.meta));
+            spans.push(Span::styled(format!("{}  ", value), theme.text));
+        }
+    }
+    
+    let status = if showing_original {
+        "[原图]"
+    } else if image.original_url.is_some() {
+        "[缩略图，原图加载中...]"
+    } else {
+        "[图片]"
+    };
+    spans.push(Span::styled(status, theme.muted));
+    
+    Line::from(spans)
+}
+
+/// 画廊缩略图单元格的尺寸
+const GALLERY_CELL_WIDTH: u16 = 28;
+const GALLERY_CELL_HEIGHT: u16 = 12;
+
+/// 图片说明：帖子楼层与作者
+fn gallery_caption(image: &GalleryImage) -> String {
+    format!("#{} @{}", image.post_number, image.username)
+}
+
+fn draw_gallery(f: &mut Frame, app: &App, gallery: &Gallery) {
+    let Some(current) = gallery.current() else {
+        return;
+    };
+    
+    if gallery.previewing {
+        let caption = Line::from(vec![
+            Span::styled(
+                gallery_caption(current),
+                app.theme.title,
+            ),
+            Span::styled(
+                format!("  ({}/{})", gallery.selected + 1, gallery.images.len()),
+                app.theme.meta,
+            ),
+        ]);
+        draw_image_viewer(
+            f,
+            app,
+            &current.image,
+            &app.image_view,
+            "图片画廊",
+            Some(caption),
+            "←/→ 切换，+/- 缩放，f 适应/原始大小，hjkl 平移，s 保存，y 复制链接，O 外部打开，p 跳转到帖子，Enter/o 返回",
+        );
+        return;
+    }
+    
+    f.render_widget(Clear, f.area());
+    
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.theme.border)
+        .title(Span::styled(
+            format!("图片画廊 ({}/{})", gallery.selected + 1, gallery.images.len()),
+            app.theme.title,
+        ))
+        .title_alignment(Alignment::Center);
+    let outer_area = Rect {
+        height: f.area().height.saturating_sub(2),
+        ..f.area()
+    };
+    let grid_area = block.inner(outer_area);
+    f.render_widget(block, outer_area);
+    
+    // 根据可用宽度计算网格列数，并记录下来供按键导航使用
+    let columns = (grid_area.width / GALLERY_CELL_WIDTH).max(1) as usize;
+    let visible_rows = (grid_area.height / GALLERY_CELL_HEIGHT).max(1) as usize;
+    gallery.columns.set(columns);
+    
+    // 滚动网格，确保选中的缩略图可见
+    let selected_row = gallery.selected / columns;
+    let first_row = selected_row.saturating_sub(visible_rows - 1);
+    
+    let image_paths = app.image_paths.lock().unwrap().clone();
+    
+    for (index, image) in gallery.images.iter().enumerate().skip(first_row * columns) {
+        let row = index / columns - first_row;
+        if row >= visible_rows {
+            break;
+        }
+        let column = index % columns;
+        
+        let cell = Rect {
+            x: grid_area.x + column as u16 * GALLERY_CELL_WIDTH,
+            y: grid_area.y + row as u16 * GALLERY_CELL_HEIGHT,
+            width: GALLERY_CELL_WIDTH.min(grid_area.width),
+            height: GALLERY_CELL_HEIGHT.min(grid_area.height),
+        };
+        
+        let border_style = if index == gallery.selected {
+            app.theme.title
+        } else {
+            app.theme.muted
+        };
+        let cell_block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .title(gallery_caption(image));
+        let thumb_area = cell_block.inner(cell);
+        f.render_widget(cell_block, cell);
+        
+        match image_paths.get(&image.image.url) {
+            Some(path) => {
+                let thumbnail = ImageWidget::new(path)
+                    .max_width(thumb_area.width)
+                    .max_height(thumb_area.height)
+                    .maintain_aspect_ratio(true);
+                f.render_widget(thumbnail, thumb_area);
+            }
+            None => {
+                let loading = Paragraph::new("加载中...")
+                    .style(app.theme.meta)
+                    .alignment(Alignment::Center);
+                f.render_widget(loading, thumb_area);
+            }
+        }
+    }
+    
+    let hint = Paragraph::new("hjkl/方向键 选择图片，Enter/o 查看大图，p 跳转到所在帖子，Esc/a 关闭画廊")
+        .style(app.theme.accent)
+        .alignment(Alignment::Center);
+    let hint_area = Rect {
+        x: f.area().x,
+        y: f.area().height.saturating_sub(2) + f.area().y,
+        width: f.area().width,
+        height: 1,
+    };
+    f.render_widget(hint, hint_area);
+}
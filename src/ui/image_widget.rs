@@ -7,7 +7,7 @@ use ratatui::{
 };
 use ratatui_image::{
     StatefulImage, Resize, FilterType,
-    picker::Picker,
+    picker::{Picker, ProtocolType},
 };
 use image::ImageReader;
 use tracing::{debug, warn};
@@ -15,9 +15,44 @@ use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use lazy_static::lazy_static;
 
+use crate::core::image_view::Region;
+
 // 使用静态缓存存储已处理的图片数据
 lazy_static! {
     static ref IMAGE_CACHE: Arc<RwLock<HashMap<String, Vec<u8>>>> = Arc::new(RwLock::new(HashMap::new()));
+    static ref PICKER: RwLock<Option<Picker>> = RwLock::new(None);
+}
+
+/// 查询终端支持的图形协议和字体大小
+///
+/// 需要在进入备用屏幕之后、读取终端事件之前调用。查询失败时使用半块字符渲染。
+pub fn init_picker() {
+    let picker = Picker::from_query_stdio().unwrap_or_else(|e| {
+        warn!("查询终端图形协议失败: {}", e);
+        Picker::from_fontsize((8, 16))
+    });
+    debug!("终端图形协议: {:?}", picker.protocol_type());
+    if let Ok(mut slot) = PICKER.write() {
+        *slot = Some(picker);
+    }
+}
+
+fn picker() -> Picker {
+    PICKER
+        .read()
+        .ok()
+        .and_then(|picker| *picker)
+        .unwrap_or_else(|| Picker::from_fontsize((8, 16)))
+}
+
+/// 终端单元格的像素大小
+pub fn font_size() -> (u16, u16) {
+    picker().font_size()
+}
+
+/// 终端是否支持真正的图形协议（Sixel、Kitty 或 iTerm2）
+pub fn supports_graphics() -> bool {
+    picker().protocol_type() != ProtocolType::Halfblocks
 }
 
 /// 图片组件，使用ratatui-image库在终端中渲染图片
@@ -27,6 +62,10 @@ pub struct ImageWidget {
     pub max_width: Option<u16>,
     pub max_height: Option<u16>,
     pub maintain_aspect_ratio: bool,
+    /// 只显示图片中的这一部分
+    pub crop: Option<Region>,
+    /// 渲染所需的最小区域（宽, 高），区域更小时跳过渲染
+    pub min_size: (u16, u16),
 }
 
 impl ImageWidget {
@@ -37,6 +76,8 @@ impl ImageWidget {
             max_width: None,
             max_height: None,
             maintain_aspect_ratio: true,
+            crop: None,
+            min_size: (10, 5),
         }
     }
 
@@ -68,6 +109,16 @@ impl ImageWidget {
         self.maintain_aspect_ratio = maintain;
         self
     }
+    
+    pub fn crop(mut self, region: Region) -> Self {
+        self.crop = Some(region);
+        self
+    }
+    
+    pub fn min_size(mut self, width: u16, height: u16) -> Self {
+        self.min_size = (width, height);
+        self
+    }
 
     // 添加图片缓存检查方法
     fn get_cached_data(&self) -> Option<Vec<u8>> {
@@ -80,8 +131,8 @@ impl ImageWidget {
     // 添加图片缓存保存方法
     fn cache_data(&self, data: Vec<u8>) {
         if let Ok(mut cache) = IMAGE_CACHE.write() {
-            // 限制缓存大小，避免内存泄漏 (最多缓存10张图片)
-            if cache.len() > 10 {
+            // 限制缓存大小，避免内存泄漏 (最多缓存64张图片，足够容纳一屏画廊缩略图或一页帖子的头像)
+            if cache.len() > 64 {
                 // 简单实现：清空缓存
                 cache.clear();
             }
@@ -103,7 +154,7 @@ impl Widget for ImageWidget {
         };
         
         // 检查区域大小，如果太小就不渲染
-        if inner_area.width < 10 || inner_area.height < 5 {
+        if inner_area.width < self.min_size.0 || inner_area.height < self.min_size.1 {
             debug!("区域太小，跳过图片渲染");
             return;
         }
@@ -176,15 +227,26 @@ impl ImageWidget {
             // 使用确定的格式解码图片
             match ImageReader::with_format(std::io::Cursor::new(data), img_format).decode() {
                 Ok(img) => {
+                    // 放大查看时只保留可见区域
+                    let img = match self.crop {
+                        Some(region) if !region.is_full() => {
+                            let (width, height) = (img.width() as f32, img.height() as f32);
+                            img.crop_imm(
+                                (region.x * width) as u32,
+                                (region.y * height) as u32,
+                                ((region.width * width) as u32).max(1),
+                                ((region.height * height) as u32).max(1),
+                            )
+                        }
+                        _ => img,
+                    };
+                    
                     // 计算适合的宽高，限制最大尺寸以减轻处理负担
                     let _width = self.max_width.unwrap_or(inner_area.width).min(200);
                     let _height = self.max_height.unwrap_or(inner_area.height).min(100);
                     
-                    // 创建一个固定字体大小的Picker
-                    let picker = Picker::from_fontsize((8, 16));
-                    
                     // 创建协议
-                    let mut protocol = picker.new_resize_protocol(img);
+                    let mut protocol = picker().new_resize_protocol(img);
                     
                     // 使用更高效的缩放算法
                     let resize_mode = if self.maintain_aspect_ratio {
@@ -211,7 +273,7 @@ impl ImageWidget {
             }
         } else {
             // 无法确定图片格式
-            render_error(&format!("无法确定图片格式"), inner_area, buf);
+            render_error("无法确定图片格式", inner_area, buf);
         }
     }
 }
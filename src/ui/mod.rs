@@ -1,4 +1,5 @@
 mod image_widget;
 mod drawing;
 
-pub use drawing::draw_ui; 
\ No newline at end of file
+pub use drawing::draw_ui;
+pub use image_widget::init_picker; 
\ No newline at end of file